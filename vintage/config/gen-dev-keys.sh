@@ -0,0 +1,18 @@
+#!/bin/bash
+# creates the keys of the 4 dev nodes and writes their public keys and pops to the peers of the configs,
+# run it from the vintage directory after the build, the configs refer to config/node{1..4}.key
+set -e
+cd "$(dirname "$0")/.."
+VINTAGE=${VINTAGE:-../target/release/vintage}
+
+for i in 1 2 3 4; do
+  rm -f config/node$i.key
+  { read -r public_key; read -r pop; } < <($VINTAGE --keygen config/node$i.key)
+  for j in 1 2 3 4; do
+    sed -i "/^      name: Node$i\$/,/pop:/ {
+      s/public_key: .*/public_key: $public_key/
+      s/pop: .*/pop: $pop/
+    }" config/node$j.yml
+  done
+  echo "Node$i: $public_key"
+done
@@ -1,12 +1,19 @@
+use std::sync::Arc;
 use tokio::task::JoinHandle;
 use vintage_blockchain::{
     BlockChain, BlockChainApiImpl, BlockChainConfig, BlockChainService, BlockConsensusImpl,
     BlockSyncService, DownloadWasmTasks,
 };
-use vintage_msg::{BlockChainMsgChannels, ProxyMsgChannels};
+use vintage_metrics::{Metrics, MetricsConfig, MetricsService};
+use vintage_msg::{
+    ArcConsensusRound, ArcConsensusRoundRecords, BlockChainMsgChannels, NodeId, ProxyMsgChannels,
+    ValidatorNode,
+};
 use vintage_network::client::NetworkClient;
+use vintage_network::peer_manager::PeerManager;
 use vintage_proxy::{Admin2Vin, Gate2Vin, Proxy, ProxyConfig, Vin2Worker};
-use vintage_utils::ServiceStarter;
+use vintage_rpc::{Rpc, RpcConfig, RpcService};
+use vintage_utils::{ArcQuorum, BlsPrivateKey, ServiceStarter};
 
 #[allow(dead_code)]
 pub struct Vintage {
@@ -16,17 +23,27 @@ pub struct Vintage {
     vin_2_worker_service: ServiceStarter<Vin2Worker>,
     gate_2_vin_service: ServiceStarter<Gate2Vin<BlockChainApiImpl>>,
     admin_2_vin_service: ServiceStarter<Admin2Vin>,
+    rpc_service: Option<ServiceStarter<RpcService<BlockChainApiImpl>>>,
+    metrics_service: Option<ServiceStarter<MetricsService>>,
 }
 
 impl Vintage {
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         blockchain_config: BlockChainConfig,
         proxy_config: ProxyConfig,
+        rpc_config: Option<RpcConfig>,
+        metrics_config: Option<MetricsConfig>,
         block_interval: u64,
-        active_number_of_nodes: usize,
+        private_key: BlsPrivateKey,
+        genesis_validators: Vec<ValidatorNode>,
+        quorum: ArcQuorum<NodeId>,
         blockchain_chn: BlockChainMsgChannels,
         proxy_chn: ProxyMsgChannels,
         client: NetworkClient,
+        peer_manager: Arc<PeerManager>,
+        consensus_round: ArcConsensusRound,
+        round_records: ArcConsensusRoundRecords,
     ) -> anyhow::Result<(Self, BlockConsensusImpl)> {
         let (
             block_consensus,
@@ -37,13 +54,32 @@ impl Vintage {
         ) = BlockChain::create(
             blockchain_config,
             block_interval,
-            active_number_of_nodes,
+            private_key,
+            genesis_validators,
+            quorum,
             blockchain_chn,
             client,
         )
         .await?;
         let (vin_2_worker_service, gate_2_vin_service, admin_2_vin_service) =
-            Proxy::create(proxy_config, proxy_chn, blockchain_api).await?;
+            Proxy::create(proxy_config, proxy_chn, blockchain_api.clone()).await?;
+        let rpc_service = match rpc_config {
+            Some(rpc_config) => Some(
+                Rpc::create(
+                    rpc_config,
+                    blockchain_api,
+                    peer_manager,
+                    consensus_round,
+                    round_records,
+                )
+                .await?,
+            ),
+            None => None,
+        };
+        let metrics_service = match metrics_config {
+            Some(metrics_config) => Some(Metrics::create(metrics_config).await?),
+            None => None,
+        };
 
         Ok((
             Self {
@@ -53,6 +89,8 @@ impl Vintage {
                 vin_2_worker_service,
                 gate_2_vin_service,
                 admin_2_vin_service,
+                rpc_service,
+                metrics_service,
             },
             block_consensus,
         ))
@@ -65,6 +103,10 @@ impl Vintage {
         let join_gate_2_vin_service = self.gate_2_vin_service.start();
         let join_vin_2_worker_service = self.vin_2_worker_service.start();
         let join_admin_2_vin_service = self.admin_2_vin_service.start();
+        let join_rpc_service = self.rpc_service.map(|rpc_service| rpc_service.start());
+        let join_metrics_service = self
+            .metrics_service
+            .map(|metrics_service| metrics_service.start());
 
         tokio::spawn(async {
             let _ = join_blockchain_service.await;
@@ -73,6 +115,16 @@ impl Vintage {
             let _ = join_gate_2_vin_service.await;
             let _ = join_vin_2_worker_service.await;
             let _ = join_admin_2_vin_service.await;
+            if let Some(join_rpc_service) = join_rpc_service {
+                if let Ok(Err(err)) = join_rpc_service.await {
+                    tracing::error!("rpc service error: {:?}", err);
+                }
+            }
+            if let Some(join_metrics_service) = join_metrics_service {
+                if let Ok(Err(err)) = join_metrics_service.await {
+                    tracing::error!("metrics service error: {:?}", err);
+                }
+            }
         })
     }
 }
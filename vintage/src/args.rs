@@ -1,15 +1,67 @@
 use std::{env, process};
 
-pub fn args() -> String {
+pub enum Args {
+    Node {
+        config_path: String,
+        // the blocks to roll back, the node is not started
+        rollback: Option<u64>,
+    },
+    // create a key file and print its public key
+    Keygen {
+        key_path: String,
+    },
+    // sign a validator set tx with a key of the current validator set and print the approval
+    ApproveValidatorSet {
+        key_path: String,
+        tx_path: String,
+        validators_path: String,
+    },
+}
+
+pub fn args() -> Args {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 3 || args[1] != "-c" {
-        print_usage();
-        process::exit(1);
+    match args.as_slice() {
+        [_, c, config_path] if c == "-c" => Args::Node {
+            config_path: config_path.clone(),
+            rollback: None,
+        },
+        [_, c, config_path, r, count] if c == "-c" && r == "--rollback" => match count.parse() {
+            Ok(count) => Args::Node {
+                config_path: config_path.clone(),
+                rollback: Some(count),
+            },
+            Err(_) => exit_with_usage(),
+        },
+        [_, k, key_path] if k == "--keygen" => Args::Keygen {
+            key_path: key_path.clone(),
+        },
+        [_, a, key_path, tx_path, validators_path] if a == "--approve-validator-set" => {
+            Args::ApproveValidatorSet {
+                key_path: key_path.clone(),
+                tx_path: tx_path.clone(),
+                validators_path: validators_path.clone(),
+            }
+        }
+        _ => exit_with_usage(),
     }
-    args[2].clone()
+}
+
+fn exit_with_usage() -> ! {
+    print_usage();
+    process::exit(1);
 }
 
 fn print_usage() {
-    println!("Usage: exe -c [config_path]]");
+    println!("Usage: exe -c [config_path] [--rollback <blocks>]");
+    println!("       exe --keygen [key_path]");
+    println!("       exe --approve-validator-set [key_path] [tx_path] [validators_path]");
     println!("  <config_path>: the configuration file path");
+    println!("  <blocks>: roll back the last blocks of the db and exit, to resync from a conflicting fork");
+    println!(
+        "  <key_path>: the key file, --keygen creates it and prints the public key and its pop"
+    );
+    println!("  <tx_path>: the json file of the validator set tx, its nonce is the number of validator set txs committed before it");
+    println!(
+        "  <validators_path>: the json file of the current validators, as returned by /validators"
+    );
 }
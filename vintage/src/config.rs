@@ -1,9 +1,12 @@
+use crate::logger::LogConfig;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::Read;
 use vintage_blockchain::BlockChainConfig;
+use vintage_metrics::MetricsConfig;
 use vintage_network::config::NodeConfig;
 use vintage_proxy::ProxyConfig;
+use vintage_rpc::RpcConfig;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub enum VintageMode {
@@ -18,6 +21,10 @@ impl VintageMode {
     pub fn multi_nodes_mode(self) -> bool {
         self == Self::Prod || self == Self::Dev || self == Self::Test
     }
+    // the key file is created if missing
+    pub fn dev_mode(self) -> bool {
+        self != Self::Prod
+    }
     pub fn test_mode(self) -> bool {
         self == Self::Test || self == Self::SingleNodeTest
     }
@@ -29,6 +36,12 @@ pub struct VintageConfig {
     pub blockchain: BlockChainConfig,
     pub proxy: ProxyConfig,
     pub node: NodeConfig,
+    #[serde(default)]
+    pub rpc: Option<RpcConfig>,
+    #[serde(default)]
+    pub metrics: Option<MetricsConfig>,
+    #[serde(default)]
+    pub log: LogConfig,
 }
 
 pub fn load_config(file_path: &str) -> Result<VintageConfig, anyhow::Error> {
@@ -36,7 +49,5 @@ pub fn load_config(file_path: &str) -> Result<VintageConfig, anyhow::Error> {
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
     let config: VintageConfig = serde_yaml::from_str(&contents)?;
-
-    log::info!("vintage config: {:?}", config);
     Ok(config)
 }
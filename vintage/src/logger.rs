@@ -1,12 +1,52 @@
-use log::LevelFilter;
-
-pub fn env_logger_init() {
-    env_logger::Builder::new()
-        .filter_level(LevelFilter::Info)
-        // .filter_module("overlord", LevelFilter::Warn)
-        // .filter_module("vintage_consensus", LevelFilter::Warn)
-        // .filter_module("vintage_network", LevelFilter::Warn)
-        // .filter_module("vintage_proxy", LevelFilter::Warn)
-        // .filter_module("vintage_blockchain", LevelFilter::Warn)
-        .init();
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    pub level: String,
+    // the level of each module, e.g. overlord: warn
+    pub modules: BTreeMap<String, String>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            level: "info".to_string(),
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+// RUST_LOG overrides the levels in the config, the log records of the dependencies are forwarded
+pub fn logger_init(config: &LogConfig) -> anyhow::Result<()> {
+    let filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => {
+            let mut directives = config.level.clone();
+            for (module, level) in &config.modules {
+                directives.push_str(&format!(",{}={}", module, level));
+            }
+            EnvFilter::try_new(directives)?
+        }
+    };
+
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    match config.format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder.json().try_init(),
+    }
+    .map_err(|err| anyhow!("logger init err: {}", err))
 }
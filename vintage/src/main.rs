@@ -6,15 +6,21 @@ mod node;
 mod test;
 
 use crate::app::Vintage;
-use crate::args::args;
+use crate::args::{args, Args};
 use crate::config::load_config;
-use crate::logger::env_logger_init;
+use crate::logger::logger_init;
 use crate::node::{VintageMultiNodes, VintageSingleNode};
 use crate::test::start_test;
-use std::sync::Arc;
-use vintage_msg::msg_channels;
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use vintage_blockchain::BlockChain;
+use vintage_msg::{
+    msg_channels, ConsensusRound, ValidatorNode, ValidatorSetApproval, ValidatorSetTx,
+};
 use vintage_network::client::NetworkClient;
+use vintage_network::peer_manager::PeerManager;
 use vintage_network::request::NetworkRequestMgr;
+use vintage_utils::BlsPrivateKey;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -24,14 +30,52 @@ async fn main() -> anyhow::Result<()> {
     //     }
     // }
 
-    // env_logger
-    env_logger_init();
-
     // args
-    let config_file = args();
+    let (config_path, rollback) = match args() {
+        Args::Node {
+            config_path,
+            rollback,
+        } => (config_path, rollback),
+        Args::Keygen { key_path } => {
+            let private_key = BlsPrivateKey::generate();
+            private_key.save(&key_path)?;
+            println!("{}", private_key.public_key());
+            println!("{}", private_key.prove_possession());
+            return Ok(());
+        }
+        Args::ApproveValidatorSet {
+            key_path,
+            tx_path,
+            validators_path,
+        } => {
+            let private_key = BlsPrivateKey::load(&key_path)?;
+            let validator_set_tx: ValidatorSetTx =
+                serde_json::from_str(&std::fs::read_to_string(&tx_path)?)?;
+            let validators: Vec<ValidatorNode> =
+                serde_json::from_str(&std::fs::read_to_string(&validators_path)?)?;
+            let msg = validator_set_tx.approval_msg(&validators);
+            let approval = ValidatorSetApproval {
+                public_key: private_key.public_key().to_string(),
+                signature: private_key.sign(msg.as_bytes()).to_string(),
+            };
+            println!("{}", serde_json::to_string(&approval)?);
+            return Ok(());
+        }
+    };
 
     // config
-    let config = load_config(&config_file)?;
+    let mut config = load_config(&config_path)?;
+
+    // logger
+    logger_init(&config.log)?;
+    tracing::info!("vintage config: {:?}", config);
+
+    // rollback
+    if let Some(count) = rollback {
+        let height = BlockChain::rollback(config.blockchain, count).await?;
+        tracing::info!("rolled back {} blocks, block height: {}", count, height);
+        return Ok(());
+    }
 
     // channels
     #[allow(unused_variables)]
@@ -51,21 +95,45 @@ async fn main() -> anyhow::Result<()> {
         start_test(&config.node.name, blockchain_msg_sender);
     }
 
+    // key and quorum, a single node is the only validator
+    if !config.mode.multi_nodes_mode() {
+        config.node.peers.clear();
+    }
+    let private_key = if config.mode.dev_mode() {
+        BlsPrivateKey::load_or_create(&config.node.key_path)?
+    } else {
+        BlsPrivateKey::load(&config.node.key_path)?
+    };
+    let quorum = Arc::new(RwLock::new(config.node.peer_quorum()?));
+    let genesis_validators = config.node.validators(&private_key)?;
+
     // network client
     let request_mgr = Arc::new(std::sync::Mutex::new(NetworkRequestMgr::new(
         config.node.id,
     )));
     let client = NetworkClient::new(request_mgr.clone(), network_msg_sender.clone());
 
+    // node state shared with the rpc
+    let peer_manager = Arc::new(PeerManager::default());
+    let consensus_round = Arc::new(RwLock::new(ConsensusRound::default()));
+    let round_records = Arc::new(RwLock::new(VecDeque::new()));
+
     // vintage
     let (vintage, block_consensus) = Vintage::create(
         config.blockchain,
         config.proxy,
+        config.rpc,
+        config.metrics,
         config.node.block_interval,
-        config.node.get_number_of_node() * 2 / 3,
+        private_key.clone(),
+        genesis_validators,
+        quorum.clone(),
         blockchain_chn,
         proxy_chn,
         client,
+        peer_manager.clone(),
+        consensus_round.clone(),
+        round_records.clone(),
     )
     .await?;
     let join_vintage = vintage.start_service();
@@ -74,10 +142,15 @@ async fn main() -> anyhow::Result<()> {
     let join_node = if config.mode.multi_nodes_mode() {
         VintageMultiNodes::create(
             config.node,
+            private_key,
+            quorum,
+            consensus_round,
+            round_records,
             consensus_chn,
             network_chn,
             block_consensus,
             request_mgr,
+            peer_manager,
         )
         .await?
         .start()
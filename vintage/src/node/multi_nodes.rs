@@ -2,11 +2,14 @@ use async_trait::async_trait;
 use std::sync::Arc;
 use vintage_blockchain::BlockConsensusImpl;
 use vintage_consensus::Validator;
-use vintage_msg::{ConsensusMsgChannels, NetworkMsgChannels};
+use vintage_msg::{
+    ArcConsensusRound, ArcConsensusRoundRecords, ConsensusMsgChannels, NetworkMsgChannels, NodeId,
+};
 use vintage_network::config::NodeConfig;
+use vintage_network::peer_manager::PeerManager;
 use vintage_network::request::ArcNetworkRequestMgr;
 use vintage_network::Node;
-use vintage_utils::{Service, ServiceStarter};
+use vintage_utils::{ArcQuorum, BlsPrivateKey, Service, ServiceStarter};
 
 pub struct VintageMultiNodes {
     config: NodeConfig,
@@ -15,16 +18,38 @@ pub struct VintageMultiNodes {
 }
 
 impl VintageMultiNodes {
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         config: NodeConfig,
+        private_key: BlsPrivateKey,
+        quorum: ArcQuorum<NodeId>,
+        consensus_round: ArcConsensusRound,
+        round_records: ArcConsensusRoundRecords,
         consensus_chn: ConsensusMsgChannels,
         network_chn: NetworkMsgChannels,
         block_consensus: BlockConsensusImpl,
         request_mgr: ArcNetworkRequestMgr,
+        peer_manager: Arc<PeerManager>,
     ) -> anyhow::Result<ServiceStarter<Self>> {
-        let node = Node::create(&config, network_chn, request_mgr).await?;
+        let node = Node::create(
+            &config,
+            &private_key,
+            network_chn,
+            request_mgr,
+            peer_manager,
+        )
+        .await?;
 
-        let validator = Validator::create(&config, consensus_chn, block_consensus).await?;
+        let validator = Validator::create(
+            &config,
+            private_key,
+            quorum,
+            consensus_round,
+            round_records,
+            consensus_chn,
+            block_consensus,
+        )
+        .await?;
 
         Ok(ServiceStarter::new(Self {
             config,
@@ -44,10 +69,10 @@ impl Service for VintageMultiNodes {
         let node_service = self.node.start_service();
 
         if let Err(err) = validator_service.await {
-            log::error!("Validator service error: {:?}", err)
+            tracing::error!("Validator service error: {:?}", err)
         }
         if let Err(err) = node_service.await {
-            log::error!("Node service error: {:?}", err)
+            tracing::error!("Node service error: {:?}", err)
         }
     }
 }
@@ -4,7 +4,7 @@ use std::error::Error;
 use std::time::Duration;
 use vintage_blockchain::BlockConsensusImpl;
 use vintage_consensus::BlockConsensus;
-use vintage_msg::BlockHeight;
+use vintage_msg::{BlockHeight, BlockProof};
 use vintage_utils::{Service, ServiceStarter};
 
 pub struct VintageSingleNode {
@@ -39,7 +39,7 @@ impl Service for VintageSingleNode {
         loop {
             tokio::time::sleep(Duration::from_millis(self.block_interval)).await;
             if let Err(err) = self.generate_block().await {
-                log::error!("generate_block err: {:?}", err);
+                tracing::error!("generate_block err: {:?}", err);
             }
         }
     }
@@ -51,8 +51,16 @@ impl VintageSingleNode {
         self.block_consensus
             .check_block(self.next_height, block.clone(), hash.clone())
             .await?;
+        // no validators sign the blocks of a single node
+        let proof = BlockProof {
+            height: self.next_height,
+            round: 0,
+            block_hash: (&hash).try_into()?,
+            signature: Vec::new(),
+            voter_bitmap: Vec::new(),
+        };
         self.block_consensus
-            .commit_block(self.next_height, block, hash)
+            .commit_block(self.next_height, block, proof)
             .await?;
         self.next_height += 1;
         Ok(())
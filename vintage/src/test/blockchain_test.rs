@@ -1,8 +1,7 @@
 use rand::{random, thread_rng, Rng};
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::time::Duration;
 use tokio::sync::mpsc;
-use vintage_msg::{ActTx, MsgToBlockChain, UploadWasm};
+use vintage_msg::{ActTx, MsgToBlockChain, NodeId, UploadWasm};
 use vintage_utils::SendMsg;
 
 pub(super) async fn _broadcast_act_to_blockchain(sender: mpsc::Sender<MsgToBlockChain>) {
@@ -10,7 +9,7 @@ pub(super) async fn _broadcast_act_to_blockchain(sender: mpsc::Sender<MsgToBlock
         let millis = thread_rng().gen_range(500..=1000);
         tokio::time::sleep(Duration::from_millis(millis)).await;
         sender.send_msg(MsgToBlockChain::Broadcast(
-            SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8000),
+            NodeId::zero_hash(),
             serde_json::to_vec(&random_act()).unwrap(),
         ));
     }
@@ -1,15 +1,35 @@
-use crate::BlockChainDb;
+use crate::{get_wasm_txs_from_pool, BlockChainDb, TxPool, WasmDb};
+use crate::{MAX_ENTITY_HISTORY_VERSIONS, MAX_POOL_CONTENTS_COUNT, MAX_UPGRADE_SCHEDULE_HEIGHTS};
 use async_trait::async_trait;
-use vintage_msg::{BlockChainApi, BlockHeight, Entity, Model, Proto};
+use std::sync::Arc;
+use vintage_msg::{
+    BlockChainApi, BlockHash, BlockHeight, BlockWithHash, Entity, EntityHash, EntityId,
+    EntityProof, EntityVersion, Model, Proto, TxInBlock, TxPoolContents, TxProof, TxReceipt,
+    ValidatorNode, WasmHash, WasmId,
+};
+use vintage_utils::Hashed;
 
 #[derive(Clone)]
 pub struct BlockChainApiImpl {
     blockchain_db: BlockChainDb,
+    wasm_db: WasmDb,
+    tx_pool: Arc<TxPool>,
+    genesis_validators: Vec<ValidatorNode>,
 }
 
 impl BlockChainApiImpl {
-    pub(crate) fn new(blockchain_db: BlockChainDb) -> Self {
-        Self { blockchain_db }
+    pub(crate) fn new(
+        blockchain_db: BlockChainDb,
+        wasm_db: WasmDb,
+        tx_pool: Arc<TxPool>,
+        genesis_validators: Vec<ValidatorNode>,
+    ) -> Self {
+        Self {
+            blockchain_db,
+            wasm_db,
+            tx_pool,
+            genesis_validators,
+        }
     }
 }
 
@@ -32,11 +52,127 @@ impl BlockChainApi for BlockChainApiImpl {
                     }
                 }
                 Err(err) => {
-                    log::error!("db get_entity err: {:?}", err);
+                    tracing::error!("db get_entity err: {:?}", err);
                     return false;
                 }
             }
         }
         true
     }
+
+    async fn get_entity(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+    ) -> anyhow::Result<EntityHash> {
+        self.blockchain_db.get_entity(proto, model, entity_id).await
+    }
+
+    async fn get_entity_at(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+        height: BlockHeight,
+    ) -> anyhow::Result<EntityHash> {
+        self.blockchain_db
+            .get_entity_at(proto, model, entity_id, height)
+            .await
+    }
+
+    async fn entity_history(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+        begin_height: BlockHeight,
+    ) -> anyhow::Result<Vec<EntityVersion>> {
+        self.blockchain_db
+            .get_entity_history(
+                proto,
+                model,
+                entity_id,
+                begin_height,
+                MAX_ENTITY_HISTORY_VERSIONS,
+            )
+            .await
+    }
+
+    async fn prune_entity_history(&self, before_height: BlockHeight) -> anyhow::Result<usize> {
+        self.blockchain_db.prune_entity_history(before_height).await
+    }
+
+    async fn get_entity_proof(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+    ) -> anyhow::Result<EntityProof> {
+        self.blockchain_db
+            .get_entity_proof(proto, model, entity_id)
+            .await
+    }
+
+    async fn get_tx_proof(&self, height: BlockHeight, tx_id: Hashed) -> anyhow::Result<TxProof> {
+        self.blockchain_db.get_tx_proof(height, tx_id).await
+    }
+
+    async fn get_block(&self, height: BlockHeight) -> anyhow::Result<BlockWithHash> {
+        self.blockchain_db.get_block_with_hash(height).await
+    }
+
+    async fn get_block_by_hash(&self, hash: BlockHash) -> anyhow::Result<BlockWithHash> {
+        self.blockchain_db.get_block_by_hash(hash).await
+    }
+
+    async fn get_tx(&self, tx_id: Hashed) -> anyhow::Result<TxInBlock> {
+        self.blockchain_db.get_tx(tx_id).await
+    }
+
+    async fn get_txs(
+        &self,
+        begin_height: BlockHeight,
+        end_height: BlockHeight,
+    ) -> anyhow::Result<Vec<TxInBlock>> {
+        self.blockchain_db.get_txs(begin_height, end_height).await
+    }
+
+    async fn get_wasm_binary(&self, wasm_hash: WasmHash) -> anyhow::Result<Vec<u8>> {
+        self.wasm_db.get_wasm_binary(wasm_hash).await
+    }
+
+    async fn get_upgrade_schedule(
+        &self,
+        begin_height: BlockHeight,
+    ) -> anyhow::Result<Vec<(BlockHeight, Vec<WasmId>)>> {
+        self.blockchain_db
+            .get_upgrade_schedule(begin_height, MAX_UPGRADE_SCHEDULE_HEIGHTS)
+            .await
+    }
+
+    async fn get_pool_contents(&self) -> anyhow::Result<TxPoolContents> {
+        let (_, ue_txs) = self
+            .blockchain_db
+            .get_ue_txs_in_pool(MAX_POOL_CONTENTS_COUNT)
+            .await?;
+        let (_, act_txs) = { self.tx_pool.act_txs_guard().select(MAX_POOL_CONTENTS_COUNT) };
+        let wasm_txs = { get_wasm_txs_from_pool(&self.tx_pool.wasm_txs_guard()) };
+        let validator_set_tx = { self.tx_pool.validator_set_tx_guard().clone() };
+        Ok(TxPoolContents {
+            act_txs,
+            ue_txs,
+            wasm_txs,
+            validator_set_tx,
+        })
+    }
+
+    async fn get_tx_receipt(&self, tx_id: Hashed) -> anyhow::Result<TxReceipt> {
+        self.blockchain_db.get_tx_receipt(tx_id).await
+    }
+
+    async fn get_validators(&self, height: BlockHeight) -> anyhow::Result<Vec<ValidatorNode>> {
+        let validators = self.blockchain_db.get_validator_set(height).await?;
+        Ok(validators.unwrap_or_else(|| self.genesis_validators.clone()))
+    }
 }
@@ -1,17 +1,24 @@
+use crate::chain::{ForkAlert, RandomBeacon};
 use crate::network::BlockChainNetworkClient;
-use crate::BlockState;
+use crate::tx::check_validator_set_tx;
 use crate::DownloadWasmTask;
 use crate::MsgToProxySender;
 use crate::WasmDb;
-use crate::{get_act_txs_from_pool, get_wasm_txs_from_pool, remove_txs_from_pool, TxId, TxPool};
-use crate::{BlockChainDb, BlockInDb};
-use crate::{MAX_ACT_COUNT_PER_BLOCK, MAX_UE_TX_COUNT_PER_BLOCK};
+use crate::{get_wasm_txs_from_pool, remove_txs_from_pool, TxId, TxPool, TxStatusReporter};
+use crate::{BlockChainDb, BlockInDb, SnapshotManifest};
+use crate::{
+    ENTITY_HISTORY_PRUNE_INTERVAL, MAX_ACT_COUNT_PER_BLOCK, MAX_UE_TX_COUNT_PER_BLOCK,
+    SNAPSHOT_CHUNK_SIZE,
+};
 use anyhow::anyhow;
-use sha2::{Digest, Sha256};
 use std::sync::Arc;
-use std::time::Duration;
-use vintage_msg::{ActTx, Block, BlockHash, BlockHeight, WasmId, WasmTx};
-use vintage_utils::{current_timestamp, CalcHash, ServiceStarter, Timestamp};
+use vintage_consensus::{block_proof_voters, verify_block_proof};
+use vintage_metrics::{BLOCK_COMMIT_SECONDS, BLOCK_HEIGHT, BLOCK_TXS, POOL_TXS, POOL_WAIT_SECONDS};
+use vintage_msg::{
+    ActTx, Block, BlockBody, BlockHash, BlockHeader, BlockHeight, BlockProof, NodeId, TxStatus,
+    ValidatorNode, ValidatorSetTx, WasmId,
+};
+use vintage_utils::{current_timestamp, merkle_root, CalcHash, Hashed, ServiceStarter, Timestamp};
 
 pub type ArcBlockChainCore = Arc<tokio::sync::Mutex<BlockChainCore>>;
 
@@ -21,23 +28,44 @@ pub struct BlockChainCore {
     tx_pool: Arc<TxPool>,
     client: Arc<BlockChainNetworkClient>,
     proxy_msg_sender: MsgToProxySender,
+    tx_status_reporter: TxStatusReporter,
+    random_beacon: RandomBeacon,
+    // the validators before the first on-chain validator set
+    genesis_validators: Vec<ValidatorNode>,
+    entity_history_blocks: u64,
+    entity_history_pruned_at: BlockHeight,
+    snapshot_interval: u64,
     last_commited_time: Timestamp,
 }
 
 impl BlockChainCore {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         blockchain_db: BlockChainDb,
         wasm_db: WasmDb,
         tx_pool: Arc<TxPool>,
         client: Arc<BlockChainNetworkClient>,
         proxy_msg_sender: MsgToProxySender,
+        random_beacon: RandomBeacon,
+        genesis_validators: Vec<ValidatorNode>,
+        entity_history_blocks: u64,
+        snapshot_interval: u64,
     ) -> Self {
         Self {
+            tx_status_reporter: TxStatusReporter::new(
+                blockchain_db.clone(),
+                proxy_msg_sender.clone(),
+            ),
             blockchain_db,
             wasm_db,
             tx_pool,
             client,
             proxy_msg_sender,
+            random_beacon,
+            genesis_validators,
+            entity_history_blocks,
+            entity_history_pruned_at: 0,
+            snapshot_interval,
             last_commited_time: 0,
         }
     }
@@ -52,45 +80,53 @@ impl BlockChainCore {
         self.blockchain_db.get_block_height().await
     }
 
-    pub(crate) async fn new_block(&self, height: u64) -> anyhow::Result<(Block, BlockHash)> {
+    // the same pool and timestamp give the same block, the randomness is a signature of the
+    // previous one
+    pub(crate) async fn new_block(
+        &self,
+        height: u64,
+        timestamp: Timestamp,
+    ) -> anyhow::Result<(Block, BlockHash)> {
         self.check_block_height(height).await?;
         // prev block
         let prev_block = self.get_block(height - 1).await?;
 
-        // tx
-        let (act_tx_ids, act_txs) =
-            { get_act_txs_from_pool(&self.tx_pool.act_txs_guard(), MAX_ACT_COUNT_PER_BLOCK) };
-        let (ue_tx_ids, ue_txs) = self
+        // tx, the acts and wasm txs in pool order, the update entity txs by id
+        let (_, act_txs) = { self.tx_pool.act_txs_guard().select(MAX_ACT_COUNT_PER_BLOCK) };
+        let (_, ue_txs) = self
             .blockchain_db
             .get_ue_txs_in_pool(MAX_UE_TX_COUNT_PER_BLOCK)
             .await?;
         let wasm_txs = { get_wasm_txs_from_pool(&self.tx_pool.wasm_txs_guard()) };
+        let validator_set_tx = self.validator_set_tx_from_pool(height).await?;
 
-        // state
-        let state = Self::block_state(&prev_block, &act_txs);
+        let body = BlockBody {
+            act_txs,
+            ue_txs,
+            wasm_txs,
+            validator_set_tx,
+        };
 
-        // hash
-        let timestamp = current_timestamp();
-        let hash = Self::calc_block_hash(
+        // header
+        let (randomness, randomness_proof) = self
+            .random_beacon
+            .generate(height, &prev_block.header.randomness);
+        let header = BlockHeader {
             height,
+            prev_hash: prev_block.hash.clone(),
+            proposer: self.random_beacon.node_id().clone(),
             timestamp,
-            &state,
-            &act_tx_ids,
-            &ue_tx_ids,
-            &wasm_txs,
-            &prev_block.hash,
-        );
+            total_act_txs: Self::total_act_txs(&prev_block, &body.act_txs),
+            tx_root: Self::tx_root(&body),
+            state_root: self.state_root(&body).await?,
+            randomness,
+            randomness_proof,
+        };
 
-        // new block
-        Ok((
-            Block {
-                timestamp,
-                act_txs,
-                ue_txs,
-                wasm_txs,
-            },
-            hash,
-        ))
+        // hash
+        let hash = header.calc_hash();
+
+        Ok((Block { header, body }, hash))
     }
 
     pub(crate) async fn check_block(
@@ -110,24 +146,27 @@ impl BlockChainCore {
         self.blockchain_db
             .check_ue_txs_not_exist(ue_tx_ids.clone())
             .await?;
-        self.check_ue_txs_exist_in_pool(ue_tx_ids.clone()).await?;
+        self.check_ue_txs_exist_in_pool(ue_tx_ids).await?;
         self.blockchain_db
             .check_wasm_txs_not_exist(wasm_ids)
             .await?;
+        if let Some(validator_set_tx) = &block.body.validator_set_tx {
+            check_validator_set_tx(
+                validator_set_tx,
+                height,
+                &self.get_validators(height).await?,
+                self.blockchain_db
+                    .get_last_validator_set_tx()
+                    .await?
+                    .as_ref(),
+            )?;
+        }
 
-        // state
-        let state = Self::block_state(&prev_block, &block.act_txs);
+        // header
+        self.check_block_header(height, &prev_block, block).await?;
 
         // hash
-        let calc_hash = Self::calc_block_hash(
-            height,
-            block.timestamp,
-            &state,
-            &act_tx_ids,
-            &ue_tx_ids,
-            &block.wasm_txs,
-            &prev_block.hash,
-        );
+        let calc_hash = block.header.calc_hash();
         if *hash == calc_hash {
             Ok(())
         } else {
@@ -135,95 +174,242 @@ impl BlockChainCore {
         }
     }
 
+    // the block hash is in the proof
     pub(crate) async fn commit_block(
         &mut self,
         height: u64,
         block: Block,
-        hash: BlockHash,
+        proof: BlockProof,
     ) -> anyhow::Result<()> {
         self.check_block_height(height).await?;
-        // prev block
-        let prev_block = self.get_block(height - 1).await?;
+        let hash = proof.block_hash.clone();
 
         // tx
         let (act_tx_ids, ue_tx_ids, wasm_ids) = Self::tx_keys_of(&block);
-        let act_txs = block.act_txs.clone();
-        let ue_txs = block.ue_txs.clone();
-
-        // state
-        let state = Self::block_state(&prev_block, &block.act_txs);
+        let act_txs = block.body.act_txs.clone();
+        let ue_txs = block.body.ue_txs.clone();
+        let validator_set_tx = block.body.validator_set_tx.clone();
+        let included_txs: Vec<_> = act_txs
+            .iter()
+            .map(|act_tx| act_tx.proto.clone())
+            .zip(act_tx_ids.iter().cloned())
+            .chain(
+                ue_txs
+                    .iter()
+                    .map(|ue_tx| ue_tx.proto.clone())
+                    .zip(ue_tx_ids.iter().cloned()),
+            )
+            .collect();
 
         // commit block
         let block_hash_cloned = hash.clone();
-        let timestamp = block.timestamp;
-        let total_act_txs = state.total_act_txs;
+        let header = block.header.clone();
         self.try_insert_download_wasm_tasks(&wasm_ids).await;
+        let commit_timer = BLOCK_COMMIT_SECONDS.start_timer();
         self.blockchain_db
             .commit_block(
                 height,
                 hash,
-                state,
                 act_tx_ids.clone(),
-                ue_tx_ids,
+                ue_tx_ids.clone(),
                 wasm_ids.clone(),
                 block,
+                proof,
             )
             .await?;
+        commit_timer.observe_duration();
 
-        if wasm_ids.is_empty() {
-            log::info!(
-                "block commited, height: {},\nhash: {}, timestamp: {}, total_act_txs: {}, act_txs: {}, ue_txs: {}",
-                height,
-                block_hash_cloned,
-                timestamp,
-                total_act_txs,
-                act_txs.len(),
-                ue_txs.len(),
-            );
-        } else {
-            log::info!(
-                "block commited, height: {},\nhash: {}, timestamp: {}, total_act_txs: {}, act_txs: {}, ue_txs: {}, wasm_txs: {}",
-                height,
-                block_hash_cloned,
-                timestamp,
-                total_act_txs,
-                act_txs.len(),
-                ue_txs.len(),
-                wasm_ids.len(),
-            );
-        }
+        tracing::info!(
+            height,
+            hash = %block_hash_cloned,
+            timestamp = header.timestamp,
+            total_act_txs = header.total_act_txs,
+            act_txs = act_txs.len(),
+            ue_txs = ue_txs.len(),
+            wasm_txs = wasm_ids.len(),
+            "block commited"
+        );
 
         // after - commit block
         self.last_commited_time = current_timestamp();
-        {
-            remove_txs_from_pool(&mut self.tx_pool.act_txs_guard(), &act_tx_ids);
+        let (arrival_times, expired, act_pool_count) = {
+            let mut pool = self.tx_pool.act_txs_guard();
+            let arrival_times = pool.remove_txs(&act_tx_ids);
+            let expired = pool.expire(current_timestamp());
+            (arrival_times, expired, pool.len())
+        };
+        let ue_arrival_times: Vec<Timestamp> = {
+            let mut arrival_times = self.tx_pool.ue_arrival_times_guard();
+            ue_tx_ids
+                .iter()
+                .filter_map(|tx_id| arrival_times.remove(tx_id))
+                .collect()
+        };
+        self.update_metrics(
+            height,
+            [act_txs.len(), ue_txs.len(), wasm_ids.len()],
+            [&arrival_times, &ue_arrival_times],
+            act_pool_count,
+        )
+        .await;
+        self.tx_status_reporter
+            .publish(included_txs, TxStatus::Included(height));
+        if !expired.is_empty() {
+            tracing::info!("act txs expired in pool: {}", expired.len());
         }
+        self.tx_status_reporter
+            .report(
+                expired
+                    .into_iter()
+                    .map(|(tx_id, act_tx)| (act_tx.proto, tx_id))
+                    .collect(),
+                TxStatus::Expired,
+                false,
+            )
+            .await;
         {
-            remove_txs_from_pool(&mut self.tx_pool.wasm_txs_guard(), &wasm_ids);
+            let mut pool = self.tx_pool.wasm_txs_guard();
+            remove_txs_from_pool(&mut pool, &wasm_ids);
+            POOL_TXS.with_label_values(&["wasm"]).set(pool.len() as i64);
         }
+        if let Some(validator_set_tx) = validator_set_tx {
+            tracing::info!(
+                "validator set changed, validators: {}, effective height: {}",
+                validator_set_tx.validators.len(),
+                height + validator_set_tx.block_interval,
+            );
+            let mut pool = self.tx_pool.validator_set_tx_guard();
+            if pool.as_ref() == Some(&validator_set_tx) {
+                *pool = None;
+            }
+        }
+        self.try_prune_entity_history(height).await;
+        self.try_create_snapshot(height);
         let upgrade_wasm_ids = self.blockchain_db.get_upgrade_wasm_ids(height).await?;
-        self.proxy_msg_sender.send_block_event(
-            height,
-            timestamp,
-            total_act_txs,
-            act_txs,
-            ue_txs,
-            upgrade_wasm_ids,
-        );
+        let prev_block_acts = self.prev_block_acts(height).await;
+        self.proxy_msg_sender
+            .send_block_event(&header, prev_block_acts, ue_txs, upgrade_wasm_ids);
 
         Ok(())
     }
 
+    // a block finalized by the consensus, proved by the signatures of the validators.
+    // the txs come with the block, they are not required in the local pools or checked against the db
+    pub(crate) async fn check_finalized_block(
+        &self,
+        height: u64,
+        block: &Block,
+        proof: &BlockProof,
+    ) -> anyhow::Result<()> {
+        // proof
+        if proof.height != height {
+            return Err(anyhow!("block {} proof height is {}", height, proof.height));
+        }
+        verify_block_proof(proof, &self.get_validators(height).await?)?;
+
+        // hash
+        let calc_hash = block.header.calc_hash();
+        if proof.block_hash != calc_hash {
+            return Err(anyhow!(
+                "finalized block hash, {} != {}",
+                proof.block_hash,
+                calc_hash
+            ));
+        }
+
+        // header, the txs are bound to it by the tx root and the state root
+        let prev_block = self.get_block(height - 1).await?;
+        self.check_block_header(height, &prev_block, block).await
+    }
+
+    // the synced blocks are imported on their commit proofs, not on the local pools
     pub(crate) async fn import_block(
         &mut self,
         block_height: BlockHeight,
         block: Block,
-        hash: BlockHash,
+        proof: BlockProof,
     ) -> anyhow::Result<()> {
-        self.check_block(block_height, &block, &hash).await?;
-        self.commit_block(block_height, block, hash).await?;
+        self.check_finalized_block(block_height, &block, &proof)
+            .await?;
+        self.commit_block(block_height, block, proof).await?;
+        Ok(())
+    }
+
+    // the wasm binaries of the snapshot are downloaded afterwards
+    pub(crate) async fn restore_snapshot(
+        &mut self,
+        manifest: SnapshotManifest,
+        chunks: Vec<Vec<u8>>,
+        proof: BlockProof,
+    ) -> anyhow::Result<()> {
+        let height = manifest.height;
+        let wasm_ids = self
+            .blockchain_db
+            .restore_snapshot(manifest, chunks, proof, self.genesis_validators.clone())
+            .await?;
+        self.try_insert_download_wasm_tasks(&wasm_ids).await;
+        self.entity_history_pruned_at = height;
+        BLOCK_HEIGHT.set(height as i64);
+        tracing::info!(
+            "snapshot restored, height: {}, wasm txs: {}",
+            height,
+            wasm_ids.len()
+        );
         Ok(())
     }
+
+    pub(crate) fn node_id(&self) -> &NodeId {
+        self.random_beacon.node_id()
+    }
+
+    pub(crate) async fn get_block_hash(&self, height: BlockHeight) -> anyhow::Result<BlockHash> {
+        Ok(self.get_block(height).await?.hash)
+    }
+
+    // the block of the peer differs from the committed one at the height.
+    // if it is proved too, the validators have committed two blocks at the height
+    pub(crate) async fn check_fork(
+        &self,
+        node_id: NodeId,
+        height: BlockHeight,
+        remote_hash: BlockHash,
+        remote_proof: Option<BlockProof>,
+    ) -> anyhow::Result<ForkAlert> {
+        let local_hash = self.get_block_hash(height).await?;
+        let validators = self.get_validators(height).await?;
+        let remote_proof = remote_proof.filter(|proof| {
+            proof.height == height
+                && proof.block_hash == remote_hash
+                && verify_block_proof(proof, &validators).is_ok()
+        });
+        let alert = match remote_proof {
+            Some(remote_proof) => {
+                // none for the genesis block
+                let local_voters = match self.blockchain_db.get_block_proof(height).await? {
+                    Some(local_proof) => block_proof_voters(&local_proof, &validators)?,
+                    None => Vec::new(),
+                };
+                let equivocators = block_proof_voters(&remote_proof, &validators)?
+                    .into_iter()
+                    .filter(|voter| local_voters.contains(voter))
+                    .collect();
+                ForkAlert::ConflictingCommit {
+                    node_id,
+                    height,
+                    local_hash,
+                    remote_hash,
+                    equivocators,
+                }
+            }
+            None => ForkAlert::DivergentChain {
+                node_id,
+                height,
+                local_hash,
+                remote_hash,
+            },
+        };
+        Ok(alert)
+    }
 }
 
 impl BlockChainCore {
@@ -240,32 +426,16 @@ impl BlockChainCore {
         self.blockchain_db.get_block(height).await
     }
 
+    // the update entity txs are gossiped before they are proposed, the consensus waits briefly
+    // for the late ones before taking the lock, a tx still missing fails the block
     async fn check_ue_txs_exist_in_pool(&self, ue_tx_ids: Vec<TxId>) -> anyhow::Result<()> {
-        let mut elapsed = 0;
         for ue_tx_id in ue_tx_ids {
-            self.check_ue_tx_exist_in_pool(&mut elapsed, ue_tx_id)
-                .await?;
-        }
-        Ok(())
-    }
-
-    async fn check_ue_tx_exist_in_pool(
-        &self,
-        elapsed: &mut u64,
-        ue_tx_id: TxId,
-    ) -> anyhow::Result<()> {
-        while !self
-            .blockchain_db
-            .ue_tx_exists_in_pool(ue_tx_id.clone())
-            .await?
-        {
-            // tx not exists
-            if *elapsed < 10_000 {
-                const MILLIS: u64 = 100;
-                tokio::time::sleep(Duration::from_millis(MILLIS)).await;
-                *elapsed += MILLIS;
-            } else {
-                return Err(anyhow!("update entity tx {} not exist.", ue_tx_id));
+            if !self
+                .blockchain_db
+                .ue_tx_exists_in_pool(ue_tx_id.clone())
+                .await?
+            {
+                return Err(anyhow!("update entity tx {} not in pool", ue_tx_id));
             }
         }
         Ok(())
@@ -274,12 +444,19 @@ impl BlockChainCore {
     fn tx_keys_of(block: &Block) -> (Vec<TxId>, Vec<TxId>, Vec<WasmId>) {
         (
             block
+                .body
                 .act_txs
                 .iter()
                 .map(|act_tx| act_tx.calc_hash())
                 .collect(),
-            block.ue_txs.iter().map(|ue_tx| ue_tx.calc_hash()).collect(),
             block
+                .body
+                .ue_txs
+                .iter()
+                .map(|ue_tx| ue_tx.calc_hash())
+                .collect(),
+            block
+                .body
                 .wasm_txs
                 .iter()
                 .map(|wasm_tx| wasm_tx.wasm_id.clone())
@@ -287,38 +464,179 @@ impl BlockChainCore {
         )
     }
 
-    fn block_state(prev_block: &BlockInDb, act_txs: &[ActTx]) -> BlockState {
-        BlockState {
-            total_act_txs: prev_block.state.total_act_txs + act_txs.len() as u64,
+    fn total_act_txs(prev_block: &BlockInDb, act_txs: &[ActTx]) -> u64 {
+        prev_block.header.total_act_txs + act_txs.len() as u64
+    }
+
+    fn tx_root(body: &BlockBody) -> Hashed {
+        merkle_root(&body.tx_items())
+    }
+
+    async fn state_root(&self, body: &BlockBody) -> anyhow::Result<Hashed> {
+        self.blockchain_db
+            .calc_state_root(body.ue_txs.clone())
+            .await
+    }
+
+    async fn check_block_header(
+        &self,
+        height: BlockHeight,
+        prev_block: &BlockInDb,
+        block: &Block,
+    ) -> anyhow::Result<()> {
+        let header = &block.header;
+        if header.height != height {
+            return Err(anyhow!(
+                "block height mismatch, expected {}, got {}",
+                height,
+                header.height
+            ));
+        }
+        if header.prev_hash != prev_block.hash {
+            return Err(anyhow!("block {} prev hash mismatch", height));
+        }
+        if header.total_act_txs != Self::total_act_txs(prev_block, &block.body.act_txs) {
+            return Err(anyhow!("block {} total act txs mismatch", height));
+        }
+        if header.tx_root != Self::tx_root(&block.body) {
+            return Err(anyhow!("block {} tx root mismatch", height));
+        }
+        if header.state_root != self.state_root(&block.body).await? {
+            return Err(anyhow!("block {} state root mismatch", height));
+        }
+        let validators = self.get_validators(height).await?;
+        self.random_beacon
+            .verify(header, &prev_block.header.randomness, &validators)
+    }
+
+    // the acts are sent with the next block, the randomness they draw is fixed after them
+    async fn prev_block_acts(&self, height: BlockHeight) -> Option<(BlockHeader, Vec<ActTx>)> {
+        let prev_height = height - 1;
+        let result = async {
+            let header = self.blockchain_db.get_block_header(prev_height).await?;
+            let body = self.blockchain_db.get_block_body(prev_height).await?;
+            anyhow::Ok((header, body.act_txs))
+        }
+        .await;
+        match result {
+            Ok(prev_block_acts) => Some(prev_block_acts),
+            Err(err) => {
+                // the blocks before a restored snapshot are not in the db
+                tracing::warn!("acts of block {} not sent, err: {:?}", prev_height, err);
+                None
+            }
         }
     }
 
-    fn calc_block_hash(
+    // the pending tx is approved by the validators at the time it was gossiped, it is dropped
+    // once the validator set changed under it
+    async fn validator_set_tx_from_pool(
+        &self,
         height: BlockHeight,
-        timestamp: Timestamp,
-        state: &BlockState,
-        act_tx_ids: &[TxId],
-        ue_tx_ids: &[TxId],
-        wasm_txs: &[WasmTx],
-        prev_hash: &BlockHash,
-    ) -> BlockHash {
-        let mut hasher = Sha256::new();
-        hasher.update(height.to_be_bytes());
-        hasher.update(timestamp.to_be_bytes());
-        hasher.update(state.total_act_txs.to_be_bytes());
-        for act_tx_id in act_tx_ids {
-            hasher.update(act_tx_id);
+    ) -> anyhow::Result<Option<ValidatorSetTx>> {
+        let validator_set_tx = { self.tx_pool.validator_set_tx_guard().clone() };
+        let Some(validator_set_tx) = validator_set_tx else {
+            return Ok(None);
+        };
+        let validators = self.get_validators(height).await?;
+        let last_validator_set = self.blockchain_db.get_last_validator_set_tx().await?;
+        if let Err(err) = check_validator_set_tx(
+            &validator_set_tx,
+            height,
+            &validators,
+            last_validator_set.as_ref(),
+        ) {
+            tracing::warn!("drop validator set tx in pool: {}", err);
+            let mut pool = self.tx_pool.validator_set_tx_guard();
+            if pool.as_ref() == Some(&validator_set_tx) {
+                *pool = None;
+            }
+            return Ok(None);
         }
-        for ue_tx_id in ue_tx_ids {
-            hasher.update(ue_tx_id);
+        Ok(Some(validator_set_tx))
+    }
+
+    async fn get_validators(&self, height: BlockHeight) -> anyhow::Result<Vec<ValidatorNode>> {
+        let validators = self.blockchain_db.get_validator_set(height).await?;
+        Ok(validators.unwrap_or_else(|| self.genesis_validators.clone()))
+    }
+
+    async fn update_metrics(
+        &self,
+        height: BlockHeight,
+        tx_counts: [usize; 3],
+        arrival_times: [&[Timestamp]; 2],
+        act_pool_count: usize,
+    ) {
+        BLOCK_HEIGHT.set(height as i64);
+        for (kind, count) in ["act", "ue", "wasm"].into_iter().zip(tx_counts) {
+            BLOCK_TXS.with_label_values(&[kind]).observe(count as f64);
+        }
+        let now = current_timestamp();
+        // the txs gossiped from the other nodes wait since they arrived at this node
+        for (kind, arrival_times) in ["act", "ue"].into_iter().zip(arrival_times) {
+            for arrival_time in arrival_times {
+                POOL_WAIT_SECONDS
+                    .with_label_values(&[kind])
+                    .observe(now.saturating_sub(*arrival_time) as f64);
+            }
         }
-        for wasm_tx in wasm_txs {
-            hasher.update(&wasm_tx.wasm_id.proto);
-            hasher.update(&wasm_tx.wasm_id.wasm_hash);
-            hasher.update(wasm_tx.wasm_info.block_interval.to_be_bytes());
+        POOL_TXS
+            .with_label_values(&["act"])
+            .set(act_pool_count as i64);
+        match self.blockchain_db.get_ue_tx_pool_count().await {
+            Ok(count) => POOL_TXS.with_label_values(&["ue"]).set(count as i64),
+            Err(err) => tracing::error!("get_ue_tx_pool_count err: {:?}", err),
         }
-        hasher.update(prev_hash);
-        hasher.into()
+    }
+
+    async fn try_prune_entity_history(&mut self, height: BlockHeight) {
+        if self.entity_history_blocks == 0
+            || height < self.entity_history_pruned_at + ENTITY_HISTORY_PRUNE_INTERVAL
+            || height <= self.entity_history_blocks
+        {
+            return;
+        }
+        self.entity_history_pruned_at = height;
+        let before_height = height - self.entity_history_blocks;
+        match self.blockchain_db.prune_entity_history(before_height).await {
+            Ok(count) => {
+                tracing::info!(
+                    "entity history before height {} pruned, versions removed: {}",
+                    before_height,
+                    count
+                );
+            }
+            Err(err) => {
+                tracing::error!("prune entity history err: {:?}", err);
+            }
+        }
+    }
+
+    // at the same heights on all the nodes, so the voted manifests match
+    // created in the background, the next block is not held up by the core lock
+    fn try_create_snapshot(&self, height: BlockHeight) {
+        if height.checked_rem(self.snapshot_interval) != Some(0) {
+            return;
+        }
+        let blockchain_db = self.blockchain_db.clone();
+        tokio::spawn(async move {
+            match blockchain_db
+                .create_snapshot(height, SNAPSHOT_CHUNK_SIZE)
+                .await
+            {
+                Ok(manifest) => {
+                    tracing::info!(
+                        "snapshot created, height: {}, chunks: {}",
+                        manifest.height,
+                        manifest.chunk_hashes.len()
+                    );
+                }
+                Err(err) => {
+                    tracing::error!("create snapshot err: {:?}", err);
+                }
+            }
+        });
     }
 
     async fn try_insert_download_wasm_tasks(&self, wasm_ids: &[WasmId]) {
@@ -340,7 +658,7 @@ impl BlockChainCore {
                     }
                 }
                 Err(err) => {
-                    log::error!(
+                    tracing::error!(
                         "try_insert_download_wasm_task {} err: {:?}",
                         wasm_id.wasm_hash,
                         err
@@ -350,3 +668,138 @@ impl BlockChainCore {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_blockchain_db_inner, create_wasm_db_inner, NetworkClientWrapper};
+    use crate::{ActPoolConfig, BlockChainNetworkClient, WASM_POOL_CAPACITY};
+    use std::path::PathBuf;
+    use tokio::sync::mpsc;
+    use vintage_msg::{Entity, UpdateEntityTx, WasmInfo};
+    use vintage_network::client::NetworkClient;
+    use vintage_network::request::NetworkRequestMgr;
+    use vintage_utils::{BlsPrivateKey, Quorum};
+
+    fn db_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("vintage_core_{}_{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    async fn core(name: &str) -> BlockChainCore {
+        let blockchain_db = BlockChainDb::new(
+            create_blockchain_db_inner(db_path(&format!("{}_chain", name)))
+                .await
+                .unwrap(),
+        );
+        let wasm_db = WasmDb::new(
+            create_wasm_db_inner(db_path(&format!("{}_wasm", name)))
+                .await
+                .unwrap(),
+        );
+        let tx_pool = Arc::new(TxPool::new(
+            ActPoolConfig::default(),
+            true,
+            WASM_POOL_CAPACITY,
+        ));
+        let (network_msg_sender, _) = mpsc::channel(1);
+        let client = NetworkClient::new(
+            Arc::new(std::sync::Mutex::new(NetworkRequestMgr::new(0))),
+            network_msg_sender,
+        );
+        let quorum = Arc::new(std::sync::RwLock::new(Quorum::new([])));
+        let (proxy_msg_sender, _) = mpsc::channel(1);
+        // a fixed key, the randomness is the same in every run
+        let private_key = BlsPrivateKey::from_bytes(&[1; 32]).unwrap();
+        BlockChainCore::new(
+            blockchain_db,
+            wasm_db,
+            tx_pool,
+            Arc::new(BlockChainNetworkClient::new(NetworkClientWrapper::new(
+                client, quorum,
+            ))),
+            MsgToProxySender::new(proxy_msg_sender),
+            RandomBeacon::new(private_key),
+            Vec::new(),
+            0,
+            0,
+        )
+    }
+
+    // the update entity txs arrive in reverse if ue_rev
+    async fn fill_pool(core: &BlockChainCore, ue_rev: bool) {
+        for n in 0..4u8 {
+            let act_tx = ActTx {
+                action: "act".to_owned(),
+                proto: format!("proto{}", n % 2),
+                model: "m".to_owned(),
+                data: vec![n],
+            };
+            core.tx_pool
+                .act_txs_guard()
+                .insert(act_tx.calc_hash(), act_tx, 0)
+                .unwrap();
+        }
+        let ue_ns: Vec<u32> = if ue_rev {
+            (0..4).rev().collect()
+        } else {
+            (0..4).collect()
+        };
+        for n in ue_ns {
+            let ue_tx = UpdateEntityTx {
+                proto: "proto".to_owned(),
+                model: "m".to_owned(),
+                req_id: n.to_string(),
+                entities: vec![Entity {
+                    id: format!("entity{}", n),
+                    hash: format!("hash{}", n),
+                }],
+            };
+            core.blockchain_db
+                .insert_ue_tx_to_pool(ue_tx.calc_hash(), ue_tx)
+                .await
+                .unwrap();
+        }
+        for n in 0..2u8 {
+            core.tx_pool.wasm_txs_guard().insert(
+                WasmId {
+                    proto: format!("proto{}", n),
+                    wasm_hash: (n as u64).calc_hash(),
+                },
+                WasmInfo { block_interval: 1 },
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn new_block_from_the_same_pool_is_reproducible() {
+        let (core1, core2) = (core("reproducible1").await, core("reproducible2").await);
+        fill_pool(&core1, false).await;
+        fill_pool(&core2, true).await;
+
+        let (block1, hash1) = core1.new_block(1, 1_000).await.unwrap();
+        let (block2, hash2) = core2.new_block(1, 1_000).await.unwrap();
+        assert_eq!(block1.body.act_txs.len(), 4);
+        assert_eq!(block1.body.ue_txs.len(), 4);
+        assert_eq!(block1.body.wasm_txs.len(), 2);
+        // the update entity txs by id, whatever their arrival order
+        let ue_tx_ids: Vec<TxId> = block1.body.ue_txs.iter().map(|tx| tx.calc_hash()).collect();
+        let mut sorted_ids = ue_tx_ids.clone();
+        sorted_ids.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        assert_eq!(ue_tx_ids, sorted_ids);
+        assert_eq!(block1, block2);
+        assert_eq!(hash1, hash2);
+
+        // built again from the same pool
+        let (block3, hash3) = core1.new_block(1, 1_000).await.unwrap();
+        assert_eq!(block1, block3);
+        assert_eq!(hash1, hash3);
+
+        // only the clock changes the block
+        let (block4, hash4) = core1.new_block(1, 2_000).await.unwrap();
+        assert_eq!(block4.body, block1.body);
+        assert_ne!(hash4, hash1);
+    }
+}
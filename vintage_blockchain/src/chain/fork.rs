@@ -0,0 +1,89 @@
+use vintage_metrics::FORK_ALERTS;
+use vintage_msg::{BlockHash, BlockHeight, NodeId};
+
+// a chain conflicting with the local one at a height committed by both
+#[derive(Debug)]
+pub(crate) enum ForkAlert {
+    // the block of the peer is not proved by the validators
+    DivergentChain {
+        node_id: NodeId,
+        height: BlockHeight,
+        local_hash: BlockHash,
+        remote_hash: BlockHash,
+    },
+    // both blocks are proved, the validators signing both of them equivocated
+    ConflictingCommit {
+        node_id: NodeId,
+        height: BlockHeight,
+        local_hash: BlockHash,
+        remote_hash: BlockHash,
+        equivocators: Vec<NodeId>,
+    },
+    // the nodes on the other chain reach the quorum, the local chain is the fork
+    LocalFork {
+        height: BlockHeight,
+        node_ids: Vec<NodeId>,
+    },
+}
+
+impl ForkAlert {
+    fn kind(&self) -> &'static str {
+        match self {
+            ForkAlert::DivergentChain { .. } => "divergent_chain",
+            ForkAlert::ConflictingCommit { .. } => "conflicting_commit",
+            ForkAlert::LocalFork { .. } => "local_fork",
+        }
+    }
+
+    // the alerts are logged under their own target, so they can be routed apart from the node log
+    pub fn report(&self) {
+        FORK_ALERTS.with_label_values(&[self.kind()]).inc();
+        match self {
+            ForkAlert::DivergentChain {
+                node_id,
+                height,
+                local_hash,
+                remote_hash,
+            } => tracing::error!(
+                target: "fork_alert",
+                kind = self.kind(),
+                %node_id,
+                height,
+                %local_hash,
+                %remote_hash,
+                "peer is on a divergent chain"
+            ),
+            ForkAlert::ConflictingCommit {
+                node_id,
+                height,
+                local_hash,
+                remote_hash,
+                equivocators,
+            } => tracing::error!(
+                target: "fork_alert",
+                kind = self.kind(),
+                %node_id,
+                height,
+                %local_hash,
+                %remote_hash,
+                equivocators = %join(equivocators),
+                "conflicting blocks committed at the same height"
+            ),
+            ForkAlert::LocalFork { height, node_ids } => tracing::error!(
+                target: "fork_alert",
+                kind = self.kind(),
+                height,
+                node_ids = %join(node_ids),
+                "local chain diverges from the quorum"
+            ),
+        }
+    }
+}
+
+fn join(node_ids: &[NodeId]) -> String {
+    node_ids
+        .iter()
+        .map(|node_id| node_id.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
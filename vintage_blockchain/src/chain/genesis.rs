@@ -1,6 +1,20 @@
-use vintage_msg::{BlockHash, BlockHeight};
-use vintage_utils::Timestamp;
+use vintage_msg::{BlockHash, BlockHeader, BlockHeight, NodeId};
+use vintage_utils::{Hashed, Timestamp};
 
 pub(crate) const GENESIS_BLOCK_HEIGHT: BlockHeight = 0;
 pub(crate) const GENESIS_BLOCK_HASH: BlockHash = BlockHash::zero_hash();
 pub(crate) const GENESIS_BLOCK_TIMESTAMP: Timestamp = 0;
+
+pub(crate) fn genesis_block_header() -> BlockHeader {
+    BlockHeader {
+        height: GENESIS_BLOCK_HEIGHT,
+        prev_hash: BlockHash::zero_hash(),
+        proposer: NodeId::zero_hash(),
+        timestamp: GENESIS_BLOCK_TIMESTAMP,
+        total_act_txs: 0,
+        tx_root: Hashed::zero_hash(),
+        state_root: Hashed::zero_hash(),
+        randomness: Hashed::zero_hash(),
+        randomness_proof: Vec::new(),
+    }
+}
@@ -1,7 +1,9 @@
 mod core;
+mod fork;
 mod genesis;
-mod state;
+mod random_beacon;
 
 pub(crate) use self::core::*;
+pub(crate) use self::fork::*;
 pub(crate) use self::genesis::*;
-pub(crate) use self::state::*;
+pub(crate) use self::random_beacon::*;
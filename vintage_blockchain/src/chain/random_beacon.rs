@@ -0,0 +1,62 @@
+use anyhow::anyhow;
+use vintage_msg::{BlockHeader, BlockHeight, NodeId, ValidatorNode};
+use vintage_utils::{BlsPrivateKey, BlsPublicKey, CalcHash, Hashed};
+
+// Each block carries the bls signature of its proposer over the randomness of the previous block,
+// the randomness of the block is the hash of the signature.
+// The proposer knows the randomness of its block before choosing the txs, so the acts draw theirs
+// from the next block instead.
+pub(crate) struct RandomBeacon {
+    private_key: BlsPrivateKey,
+    node_id: NodeId,
+}
+
+impl RandomBeacon {
+    pub fn new(private_key: BlsPrivateKey) -> Self {
+        let node_id = private_key.public_key().calc_hash();
+        Self {
+            private_key,
+            node_id,
+        }
+    }
+
+    pub fn node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+
+    // randomness and proof of the new block
+    pub fn generate(&self, height: BlockHeight, prev_randomness: &Hashed) -> (Hashed, Vec<u8>) {
+        let proof = self
+            .private_key
+            .sign(&BlockHeader::randomness_msg(height, prev_randomness))
+            .to_bytes()
+            .to_vec();
+        (proof.calc_hash(), proof)
+    }
+
+    // the proposer must be in the validator set of the block height, the consensus engine drops
+    // the proposals whose block proposer is not the overlord leader
+    pub fn verify(
+        &self,
+        header: &BlockHeader,
+        prev_randomness: &Hashed,
+        validators: &[ValidatorNode],
+    ) -> anyhow::Result<()> {
+        let mut proposer_key = None;
+        for validator in validators {
+            let public_key = BlsPublicKey::from_hex(&validator.public_key)?;
+            if public_key.calc_hash() == header.proposer {
+                proposer_key = Some(public_key);
+                break;
+            }
+        }
+        let proposer_key = proposer_key.ok_or_else(|| {
+            anyhow!(
+                "block {} proposer {} is not a validator",
+                header.height,
+                header.proposer
+            )
+        })?;
+        header.verify_randomness(prev_randomness, &proposer_key)
+    }
+}
@@ -1,17 +1,52 @@
 use crate::chain::ArcBlockChainCore;
+use crate::db::BlockChainDb;
 use anyhow::anyhow;
 use async_trait::async_trait;
 use std::error::Error;
+use std::time::Duration;
 use vintage_consensus::BlockConsensus;
-use vintage_msg::{Block, BlockHash, BlockHeight, Hash};
+use vintage_msg::{Block, BlockHash, BlockHeight, BlockProof, Hash, ValidatorNode};
+use vintage_utils::{current_timestamp, CalcHash};
 
 pub struct BlockConsensusImpl {
     blockchain_core: ArcBlockChainCore,
+    blockchain_db: BlockChainDb,
 }
 
 impl BlockConsensusImpl {
-    pub(crate) fn new(blockchain_core: ArcBlockChainCore) -> Self {
-        Self { blockchain_core }
+    pub(crate) fn new(blockchain_core: ArcBlockChainCore, blockchain_db: BlockChainDb) -> Self {
+        Self {
+            blockchain_core,
+            blockchain_db,
+        }
+    }
+}
+
+impl BlockConsensusImpl {
+    // within the propose timeout of the consensus
+    const UE_TX_WAIT_MILLIS: u64 = 1_500;
+
+    // the gossiped update entity txs may arrive after the proposal, they are waited for before
+    // the lock of blockchain_core is taken, the block is rejected if they are still missing
+    async fn wait_ue_txs_in_pool(&self, block: &Block) {
+        const MILLIS: u64 = 100;
+        let mut elapsed = 0;
+        for ue_tx in &block.body.ue_txs {
+            let ue_tx_id = ue_tx.calc_hash();
+            while elapsed < Self::UE_TX_WAIT_MILLIS {
+                match self
+                    .blockchain_db
+                    .ue_tx_exists_in_pool(ue_tx_id.clone())
+                    .await
+                {
+                    Ok(false) => {
+                        tokio::time::sleep(Duration::from_millis(MILLIS)).await;
+                        elapsed += MILLIS;
+                    }
+                    _ => break,
+                }
+            }
+        }
     }
 }
 
@@ -33,7 +68,7 @@ impl BlockConsensus<Block> for BlockConsensusImpl {
             self.blockchain_core
                 .try_lock()
                 .map_err(|err| anyhow!("lock blockchain_core failed {:?}", err))?
-                .new_block(height)
+                .new_block(height, current_timestamp())
                 .await
         }?;
         Ok((block, (&hash).into()))
@@ -46,6 +81,7 @@ impl BlockConsensus<Block> for BlockConsensusImpl {
         hash: Hash,
     ) -> Result<(), Box<dyn Error + Send>> {
         let block_hash: BlockHash = (&hash).try_into()?;
+        self.wait_ue_txs_in_pool(&block).await;
         {
             self.blockchain_core
                 .try_lock()
@@ -60,16 +96,24 @@ impl BlockConsensus<Block> for BlockConsensusImpl {
         &self,
         height: u64,
         block: Block,
-        hash: Hash,
+        proof: BlockProof,
     ) -> Result<(), Box<dyn Error + Send>> {
-        let block_hash: BlockHash = (&hash).try_into()?;
         {
             self.blockchain_core
                 .lock()
                 .await
-                .commit_block(height, block, block_hash)
+                .commit_block(height, block, proof)
                 .await
         }?;
         Ok(())
     }
+
+    // read from db directly, no need to wait for the lock of blockchain_core
+    async fn get_validator_set(
+        &self,
+        height: u64,
+    ) -> Result<Option<Vec<ValidatorNode>>, Box<dyn Error + Send>> {
+        let validators = self.blockchain_db.get_validator_set(height).await?;
+        Ok(validators)
+    }
 }
@@ -1,13 +1,16 @@
-use crate::chain::{BlockState, GENESIS_BLOCK_HASH, GENESIS_BLOCK_HEIGHT, GENESIS_BLOCK_TIMESTAMP};
-use crate::db::{BlockChainDbInner, BlockInDb};
+use crate::chain::{genesis_block_header, GENESIS_BLOCK_HASH, GENESIS_BLOCK_HEIGHT};
+use crate::db::{BlockChainDbInner, BlockInDb, SnapshotManifest};
 use crate::tx::TxId;
+use crate::MAX_TX_RANGE_BLOCKS;
 use std::path::Path;
 use std::sync::Arc;
 use tokio::task::spawn_blocking;
 use vintage_msg::{
-    Block, BlockHash, BlockHeight, EntityHash, EntityId, Model, Proto, UpdateEntityTx, WasmId,
-    WasmInfo,
+    ApiError, Block, BlockBody, BlockHash, BlockHeader, BlockHeight, BlockProof, BlockWithHash,
+    EntityHash, EntityId, EntityProof, EntityVersion, Model, Proto, TxInBlock, TxProof, TxReceipt,
+    UpdateEntityTx, ValidatorNode, ValidatorSetTx, WasmId, WasmInfo,
 };
+use vintage_utils::{merkle_path, Hashed};
 
 #[derive(Clone)]
 pub(crate) struct BlockChainDb {
@@ -39,11 +42,11 @@ impl BlockChainDb {
         if height == GENESIS_BLOCK_HEIGHT {
             Ok(BlockInDb {
                 hash: GENESIS_BLOCK_HASH,
-                timestamp: GENESIS_BLOCK_TIMESTAMP,
-                state: BlockState { total_act_txs: 0 },
+                header: genesis_block_header(),
                 act_tx_ids: Default::default(),
                 ue_tx_ids: Default::default(),
                 wasm_ids: Default::default(),
+                validator_set_tx: None,
             })
         } else {
             let db = self.db.clone();
@@ -51,17 +54,22 @@ impl BlockChainDb {
         }
     }
 
-    pub async fn get_network_block(&self, height: BlockHeight) -> anyhow::Result<Block> {
+    pub async fn get_block_header(&self, height: BlockHeight) -> anyhow::Result<BlockHeader> {
+        let block = self.get_block(height).await?;
+        Ok(block.header)
+    }
+
+    pub async fn get_block_proof(&self, height: BlockHeight) -> anyhow::Result<Option<BlockProof>> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.get_block_proof(height)).await?
+    }
+
+    pub async fn get_block_body(&self, height: BlockHeight) -> anyhow::Result<BlockBody> {
         if height == GENESIS_BLOCK_HEIGHT {
-            Ok(Block {
-                timestamp: GENESIS_BLOCK_TIMESTAMP,
-                act_txs: Default::default(),
-                ue_txs: Default::default(),
-                wasm_txs: Default::default(),
-            })
+            Ok(BlockBody::default())
         } else {
             let db = self.db.clone();
-            spawn_blocking(move || db.get_network_block(height)).await?
+            spawn_blocking(move || db.get_block_body(height)).await?
         }
     }
 
@@ -85,6 +93,11 @@ impl BlockChainDb {
         spawn_blocking(move || db.ue_tx_exists_in_pool(&tx_id)).await?
     }
 
+    pub async fn get_tx_receipt(&self, tx_id: TxId) -> anyhow::Result<TxReceipt> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.get_tx_receipt(&tx_id)).await?
+    }
+
     pub async fn get_ue_txs_in_pool(
         &self,
         count: usize,
@@ -93,6 +106,11 @@ impl BlockChainDb {
         spawn_blocking(move || db.get_ue_txs_in_pool(count)).await?
     }
 
+    pub async fn get_ue_tx_pool_count(&self) -> anyhow::Result<u64> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.get_ue_tx_pool_count()).await?
+    }
+
     pub async fn get_entity(
         &self,
         proto: Proto,
@@ -103,6 +121,122 @@ impl BlockChainDb {
         spawn_blocking(move || db.get_entity(&proto, &model, &entity_id)).await?
     }
 
+    pub async fn get_entity_at(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+        height: BlockHeight,
+    ) -> anyhow::Result<EntityHash> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.get_entity_at(&proto, &model, &entity_id, height)).await?
+    }
+
+    pub async fn get_entity_history(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+        begin_height: BlockHeight,
+        count: usize,
+    ) -> anyhow::Result<Vec<EntityVersion>> {
+        let db = self.db.clone();
+        spawn_blocking(move || {
+            db.get_entity_history(&proto, &model, &entity_id, begin_height, count)
+        })
+        .await?
+    }
+
+    pub async fn calc_state_root(&self, ue_txs: Vec<UpdateEntityTx>) -> anyhow::Result<Hashed> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.calc_state_root(&ue_txs)).await?
+    }
+
+    pub async fn get_entity_proof(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+    ) -> anyhow::Result<EntityProof> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.get_entity_proof(proto, model, entity_id)).await?
+    }
+
+    pub async fn get_block_with_hash(&self, height: BlockHeight) -> anyhow::Result<BlockWithHash> {
+        let block = self.get_block(height).await?;
+        let body = self.get_block_body(height).await?;
+        Ok(BlockWithHash {
+            hash: block.hash,
+            block: Block {
+                header: block.header,
+                body,
+            },
+        })
+    }
+
+    pub async fn get_block_by_hash(&self, hash: BlockHash) -> anyhow::Result<BlockWithHash> {
+        let height = if hash == GENESIS_BLOCK_HASH {
+            GENESIS_BLOCK_HEIGHT
+        } else {
+            let db = self.db.clone();
+            spawn_blocking(move || db.get_block_height_by_hash(&hash)).await??
+        };
+        self.get_block_with_hash(height).await
+    }
+
+    pub async fn get_tx(&self, tx_id: TxId) -> anyhow::Result<TxInBlock> {
+        let height = {
+            let db = self.db.clone();
+            let tx_id = tx_id.clone();
+            spawn_blocking(move || db.get_tx_height(&tx_id)).await??
+        };
+        self.get_block_body(height)
+            .await?
+            .into_txs()
+            .into_iter()
+            .find(|(id, _)| *id == tx_id)
+            .map(|(tx_id, tx)| TxInBlock { tx_id, height, tx })
+            .ok_or_else(|| ApiError::not_found(format!("tx {} not in block {}", tx_id, height)))
+    }
+
+    // the txs of the blocks from begin_height to end_height, inclusive
+    pub async fn get_txs(
+        &self,
+        begin_height: BlockHeight,
+        end_height: BlockHeight,
+    ) -> anyhow::Result<Vec<TxInBlock>> {
+        if end_height < begin_height || end_height - begin_height >= MAX_TX_RANGE_BLOCKS {
+            return Err(ApiError::invalid_arg(format!(
+                "invalid height range {}..={}, at most {} blocks",
+                begin_height, end_height, MAX_TX_RANGE_BLOCKS
+            )));
+        }
+        let end_height = end_height.min(self.get_block_height().await?);
+        let mut txs = Vec::new();
+        for height in begin_height..=end_height {
+            for (tx_id, tx) in self.get_block_body(height).await?.into_txs() {
+                txs.push(TxInBlock { tx_id, height, tx });
+            }
+        }
+        Ok(txs)
+    }
+
+    pub async fn get_tx_proof(&self, height: BlockHeight, tx_id: TxId) -> anyhow::Result<TxProof> {
+        let header = self.get_block_header(height).await?;
+        let body = self.get_block_body(height).await?;
+        let tx_items = body.tx_items();
+        let path = tx_items
+            .iter()
+            .position(|item| *item == tx_id)
+            .and_then(|index| merkle_path(&tx_items, index))
+            .ok_or_else(|| ApiError::not_found(format!("tx {} not in block {}", tx_id, height)))?;
+        Ok(TxProof {
+            header,
+            tx_id,
+            path,
+        })
+    }
+
     pub async fn check_wasm_tx_not_exists(&self, wasm_id: WasmId) -> anyhow::Result<()> {
         let db = self.db.clone();
         spawn_blocking(move || db.check_wasm_tx_not_exists(&wasm_id)).await?
@@ -121,10 +255,48 @@ impl BlockChainDb {
         spawn_blocking(move || db.get_upgrade_wasm_ids(block_height)).await?
     }
 
+    pub async fn get_upgrade_schedule(
+        &self,
+        begin_height: BlockHeight,
+        count: usize,
+    ) -> anyhow::Result<Vec<(BlockHeight, Vec<WasmId>)>> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.get_upgrade_schedule(begin_height, count)).await?
+    }
+
     pub async fn _get_wasm_tx(&self, wasm_id: WasmId) -> anyhow::Result<WasmInfo> {
         let db = self.db.clone();
         spawn_blocking(move || db._get_wasm_tx(&wasm_id)).await?
     }
+
+    pub async fn get_last_validator_set_tx(
+        &self,
+    ) -> anyhow::Result<Option<(BlockHeight, ValidatorSetTx)>> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.get_last_validator_set_tx()).await?
+    }
+
+    pub async fn get_validator_set(
+        &self,
+        block_height: BlockHeight,
+    ) -> anyhow::Result<Option<Vec<ValidatorNode>>> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.get_validator_set(block_height)).await?
+    }
+
+    pub async fn get_snapshot_manifest(&self) -> anyhow::Result<Option<SnapshotManifest>> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.get_snapshot_manifest()).await?
+    }
+
+    pub async fn get_snapshot_chunk(
+        &self,
+        height: BlockHeight,
+        index: u64,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.get_snapshot_chunk(height, index)).await?
+    }
 }
 
 // write
@@ -138,19 +310,59 @@ impl BlockChainDb {
         spawn_blocking(move || db.insert_ue_tx_to_pool(&tx_id, &tx)).await?
     }
 
+    pub async fn update_tx_statuses(
+        &self,
+        receipts: Vec<TxReceipt>,
+        force_rejected: bool,
+    ) -> anyhow::Result<()> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.update_tx_statuses(&receipts, force_rejected)).await?
+    }
+
+    pub async fn prune_entity_history(&self, before_height: BlockHeight) -> anyhow::Result<usize> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.prune_entity_history(before_height)).await?
+    }
+
+    pub async fn create_snapshot(
+        &self,
+        height: BlockHeight,
+        chunk_size: usize,
+    ) -> anyhow::Result<SnapshotManifest> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.create_snapshot(height, chunk_size)).await?
+    }
+
+    pub async fn restore_snapshot(
+        &self,
+        manifest: SnapshotManifest,
+        chunks: Vec<Vec<u8>>,
+        proof: BlockProof,
+        genesis_validators: Vec<ValidatorNode>,
+    ) -> anyhow::Result<Vec<WasmId>> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.restore_snapshot(&manifest, &chunks, &proof, &genesis_validators))
+            .await?
+    }
+
+    pub async fn rollback_blocks(&self, count: u64) -> anyhow::Result<BlockHeight> {
+        let db = self.db.clone();
+        spawn_blocking(move || db.rollback_blocks(count)).await?
+    }
+
     pub async fn commit_block(
         &self,
         height: BlockHeight,
         hash: BlockHash,
-        state: BlockState,
         act_tx_ids: Vec<TxId>,
         ue_tx_ids: Vec<TxId>,
         wasm_ids: Vec<WasmId>,
         block: Block,
+        proof: BlockProof,
     ) -> anyhow::Result<()> {
         let db = self.db.clone();
         spawn_blocking(move || {
-            db.commit_block(height, hash, state, act_tx_ids, ue_tx_ids, wasm_ids, block)
+            db.commit_block(height, hash, act_tx_ids, ue_tx_ids, wasm_ids, block, proof)
         })
         .await?
     }
@@ -1,18 +1,29 @@
-use crate::chain::BlockState;
+use crate::chain::{genesis_block_header, GENESIS_BLOCK_HEIGHT};
 use crate::db::{
-    ActTxTableR, ActTxTableW, BlockHeightTableR, BlockHeightTableW, BlockInDb, BlockTableR,
-    BlockTableW, EntityTableR, EntityTableW, UpdateEntityTxPoolTableR, UpdateEntityTxPoolTableW,
-    UpdateEntityTxTableR, UpdateEntityTxTableW, UpgradeWasmTableR, UpgradeWasmTableW, WasmTxTableR,
+    ActTxTableR, ActTxTableW, BlockHashTableR, BlockHashTableW, BlockHeightTableR,
+    BlockHeightTableW, BlockInDb, BlockProofTableR, BlockProofTableW, BlockTableR, BlockTableW,
+    EntityHistoryPrunedTableR, EntityHistoryPrunedTableW, EntityHistoryTableR, EntityHistoryTableW,
+    EntityStateTableR, EntityStateTableW, EntityTableR, EntityTableW, SnapshotChunkTableR,
+    SnapshotChunkTableW, SnapshotManifest, SnapshotManifestTableR, SnapshotManifestTableW,
+    SnapshotState, TxHeightTableR, TxHeightTableW, TxReceiptTableR, TxReceiptTableW,
+    UpdateEntityTxPoolTableR, UpdateEntityTxPoolTableW, UpdateEntityTxTableR, UpdateEntityTxTableW,
+    UpgradeWasmTableR, UpgradeWasmTableW, ValidatorSetTableR, ValidatorSetTableW, WasmTxTableR,
     WasmTxTableW,
 };
 use crate::tx::TxId;
-use redb::Database;
+use anyhow::anyhow;
+use redb::{Database, WriteTransaction};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::path::Path;
+use vintage_consensus::verify_block_proof;
 use vintage_msg::{
-    Block, BlockHash, BlockHeight, EntityHash, EntityId, Model, Proto, UpdateEntityTx, WasmId,
-    WasmInfo, WasmTx,
+    entity_state_key, entity_state_value, ApiError, Block, BlockBody, BlockHash, BlockHeight,
+    BlockProof, EntityHash, EntityId, EntityProof, EntityVersion, Model, Proto, TxReceipt,
+    TxStatus, UpdateEntityTx, ValidatorNode, ValidatorSetTx, WasmId, WasmInfo, WasmTx,
+};
+use vintage_utils::{
+    BincodeDeserialize, BincodeSerialize, CalcHash, Hashed, SmtOverlay, SmtProof, SmtStore,
 };
 
 pub(crate) struct BlockChainDbInner {
@@ -33,10 +44,21 @@ impl BlockChainDbInner {
         let db_write = self.database.begin_write()?;
         BlockHeightTableW::open_table(&db_write)?;
         BlockTableW::open_table(&db_write)?;
+        BlockProofTableW::open_table(&db_write)?;
         ActTxTableW::open_table(&db_write)?;
         UpdateEntityTxTableW::open_table(&db_write)?;
         UpdateEntityTxPoolTableW::open_table(&db_write)?;
+        EntityTableW::open_table(&db_write)?;
+        EntityStateTableW::open_table(&db_write)?;
+        EntityHistoryTableW::open_table(&db_write)?;
+        EntityHistoryPrunedTableW::open_table(&db_write)?;
         WasmTxTableW::open_table(&db_write)?;
+        ValidatorSetTableW::open_table(&db_write)?;
+        TxReceiptTableW::open_table(&db_write)?;
+        TxHeightTableW::open_table(&db_write)?;
+        BlockHashTableW::open_table(&db_write)?;
+        SnapshotManifestTableW::open_table(&db_write)?;
+        SnapshotChunkTableW::open_table(&db_write)?;
         db_write.commit()?;
         Ok(())
     }
@@ -56,7 +78,29 @@ impl BlockChainDbInner {
         table.get_block(height)
     }
 
-    pub fn get_network_block(&self, height: BlockHeight) -> anyhow::Result<Block> {
+    pub fn get_block_proof(&self, height: BlockHeight) -> anyhow::Result<Option<BlockProof>> {
+        let db_read = self.database.begin_read()?;
+        let table = BlockProofTableR::open_table(&db_read)?;
+        table.get_block_proof(height)
+    }
+
+    pub fn get_block_height_by_hash(&self, hash: &BlockHash) -> anyhow::Result<BlockHeight> {
+        let db_read = self.database.begin_read()?;
+        let table = BlockHashTableR::open_table(&db_read)?;
+        table
+            .get_block_height(hash)?
+            .ok_or_else(|| ApiError::not_found(format!("block {} not found", hash)))
+    }
+
+    pub fn get_tx_height(&self, tx_id: &TxId) -> anyhow::Result<BlockHeight> {
+        let db_read = self.database.begin_read()?;
+        let table = TxHeightTableR::open_table(&db_read)?;
+        table
+            .get_tx_height(tx_id)?
+            .ok_or_else(|| ApiError::not_found(format!("tx {} not found", tx_id)))
+    }
+
+    pub fn get_block_body(&self, height: BlockHeight) -> anyhow::Result<BlockBody> {
         let db_read = self.database.begin_read()?;
         let block = {
             let table = BlockTableR::open_table(&db_read)?;
@@ -86,11 +130,11 @@ impl BlockChainDbInner {
                 wasm_txs.push(WasmTx { wasm_id, wasm_info });
             }
         }
-        Ok(Block {
-            timestamp: block.timestamp,
+        Ok(BlockBody {
             act_txs,
             ue_txs,
             wasm_txs,
+            validator_set_tx: block.validator_set_tx,
         })
     }
 
@@ -119,6 +163,21 @@ impl BlockChainDbInner {
         Ok(exists)
     }
 
+    pub fn get_tx_receipt(&self, tx_id: &TxId) -> anyhow::Result<TxReceipt> {
+        let db_read = self.database.begin_read()?;
+        let table = TxReceiptTableR::open_table(&db_read)?;
+        match table.get_tx_status(tx_id)? {
+            Some(status) => Ok(TxReceipt {
+                tx_id: tx_id.clone(),
+                status,
+            }),
+            None => Err(ApiError::not_found(format!(
+                "receipt of tx {} not found",
+                tx_id
+            ))),
+        }
+    }
+
     pub fn get_ue_txs_in_pool(
         &self,
         count: usize,
@@ -128,6 +187,12 @@ impl BlockChainDbInner {
         table.get_ue_txs_in_pool(count)
     }
 
+    pub fn get_ue_tx_pool_count(&self) -> anyhow::Result<u64> {
+        let db_read = self.database.begin_read()?;
+        let table = UpdateEntityTxPoolTableR::open_table(&db_read)?;
+        table.get_ue_tx_pool_count()
+    }
+
     pub fn get_entity(
         &self,
         proto: &Proto,
@@ -139,6 +204,109 @@ impl BlockChainDbInner {
         table.get_entity(proto, model, entity_id)
     }
 
+    pub fn get_entity_at(
+        &self,
+        proto: &Proto,
+        model: &Model,
+        entity_id: &EntityId,
+        height: BlockHeight,
+    ) -> anyhow::Result<EntityHash> {
+        let db_read = self.database.begin_read()?;
+        let pruned_height = {
+            let table = EntityHistoryPrunedTableR::open_table(&db_read)?;
+            table.get_pruned_height()?
+        };
+        if height < pruned_height {
+            return Err(ApiError::not_found(format!(
+                "entity history before height {} is pruned",
+                pruned_height
+            )));
+        }
+        let table = EntityHistoryTableR::open_table(&db_read)?;
+        match table.get_entity_version_at(&entity_state_key(proto, model, entity_id), height)? {
+            Some(version) => Ok(version.hash),
+            None => Err(ApiError::not_found(format!(
+                "entity {} {} not found at height {}",
+                model, entity_id, height
+            ))),
+        }
+    }
+
+    pub fn get_entity_history(
+        &self,
+        proto: &Proto,
+        model: &Model,
+        entity_id: &EntityId,
+        begin_height: BlockHeight,
+        count: usize,
+    ) -> anyhow::Result<Vec<EntityVersion>> {
+        let db_read = self.database.begin_read()?;
+        let table = EntityHistoryTableR::open_table(&db_read)?;
+        table.get_entity_versions(
+            &entity_state_key(proto, model, entity_id),
+            begin_height,
+            count,
+        )
+    }
+
+    // the state root after applying the ue txs to the current state
+    pub fn calc_state_root(&self, ue_txs: &[UpdateEntityTx]) -> anyhow::Result<Hashed> {
+        let db_read = self.database.begin_read()?;
+        let table = EntityStateTableR::open_table(&db_read)?;
+        let mut overlay = SmtOverlay::new(&table);
+        Self::update_state(&mut overlay, ue_txs)?;
+        overlay.root()
+    }
+
+    // proof against the state root of the last block
+    pub fn get_entity_proof(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+    ) -> anyhow::Result<EntityProof> {
+        let db_read = self.database.begin_read()?;
+        let entity_hash = {
+            let table = EntityTableR::open_table(&db_read)?;
+            table.get_entity(&proto, &model, &entity_id)?
+        };
+        let proof = {
+            let table = EntityStateTableR::open_table(&db_read)?;
+            SmtProof::create(&table, &entity_state_key(&proto, &model, &entity_id))?
+        };
+        let height = {
+            let table = BlockHeightTableR::open_table(&db_read)?;
+            table.get_block_height()?
+        };
+        let header = {
+            let table = BlockTableR::open_table(&db_read)?;
+            table.get_block(height)?.header
+        };
+        Ok(EntityProof {
+            header,
+            proto,
+            model,
+            entity_id,
+            entity_hash,
+            proof,
+        })
+    }
+
+    fn update_state(
+        overlay: &mut SmtOverlay<impl SmtStore>,
+        ue_txs: &[UpdateEntityTx],
+    ) -> anyhow::Result<()> {
+        for ue_tx in ue_txs {
+            for entity in &ue_tx.entities {
+                overlay.update(
+                    &entity_state_key(&ue_tx.proto, &ue_tx.model, &entity.id),
+                    &entity_state_value(&entity.hash),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn check_wasm_tx_not_exists(&self, wasm_id: &WasmId) -> anyhow::Result<()> {
         let db_read = self.database.begin_read()?;
         let table = WasmTxTableR::open_table(&db_read)?;
@@ -157,11 +325,62 @@ impl BlockChainDbInner {
         table.get_upgrade_wasm_ids(block_height)
     }
 
+    pub fn get_upgrade_schedule(
+        &self,
+        begin_height: BlockHeight,
+        count: usize,
+    ) -> anyhow::Result<Vec<(BlockHeight, Vec<WasmId>)>> {
+        let db_read = self.database.begin_read()?;
+        let table = UpgradeWasmTableR::open_table(&db_read)?;
+        table.get_upgrade_schedule(begin_height, count)
+    }
+
     pub fn _get_wasm_tx(&self, wasm_id: &WasmId) -> anyhow::Result<WasmInfo> {
         let db_read = self.database.begin_read()?;
         let table = WasmTxTableR::open_table(&db_read)?;
         table.get_wasm_tx(wasm_id)
     }
+
+    pub fn get_last_validator_set_tx(
+        &self,
+    ) -> anyhow::Result<Option<(BlockHeight, ValidatorSetTx)>> {
+        let db_read = self.database.begin_read()?;
+        let table = ValidatorSetTableR::open_table(&db_read)?;
+        table.get_last_validator_set_tx()
+    }
+
+    pub fn get_validator_set(
+        &self,
+        block_height: BlockHeight,
+    ) -> anyhow::Result<Option<Vec<ValidatorNode>>> {
+        let db_read = self.database.begin_read()?;
+        let table = ValidatorSetTableR::open_table(&db_read)?;
+        table.get_validator_set(block_height)
+    }
+
+    pub fn get_snapshot_manifest(&self) -> anyhow::Result<Option<SnapshotManifest>> {
+        let db_read = self.database.begin_read()?;
+        let table = SnapshotManifestTableR::open_table(&db_read)?;
+        table.get_manifest()
+    }
+
+    // the chunk of the snapshot at the height, none if the snapshot is replaced
+    pub fn get_snapshot_chunk(
+        &self,
+        height: BlockHeight,
+        index: u64,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        let db_read = self.database.begin_read()?;
+        let manifest = {
+            let table = SnapshotManifestTableR::open_table(&db_read)?;
+            table.get_manifest()?
+        };
+        if manifest.map(|manifest| manifest.height) != Some(height) {
+            return Ok(None);
+        }
+        let table = SnapshotChunkTableR::open_table(&db_read)?;
+        table.get_chunk(index)
+    }
 }
 
 // write
@@ -181,19 +400,430 @@ impl BlockChainDbInner {
         Ok(())
     }
 
+    // the status of an included tx is final, a rejection only replaces an existing status if forced,
+    // as resubmitting a pending tx is rejected too
+    pub fn update_tx_statuses(
+        &self,
+        receipts: &[TxReceipt],
+        force_rejected: bool,
+    ) -> anyhow::Result<()> {
+        let db_write = self.database.begin_write()?;
+        {
+            let mut table = TxReceiptTableW::open_table(&db_write)?;
+            for receipt in receipts {
+                let replace = match table.get_tx_status(&receipt.tx_id)? {
+                    None => true,
+                    Some(TxStatus::Included(_)) => false,
+                    Some(_) => force_rejected || !matches!(receipt.status, TxStatus::Rejected(_)),
+                };
+                if replace {
+                    table.insert_tx_status(&receipt.tx_id, &receipt.status)?;
+                }
+            }
+        }
+        db_write.commit()?;
+        Ok(())
+    }
+
+    // complete all operations within a single transaction
+    // the versions at and after the height are kept
+    pub fn prune_entity_history(&self, before_height: BlockHeight) -> anyhow::Result<usize> {
+        let db_write = self.database.begin_write()?;
+        let count = {
+            let mut table_pruned = EntityHistoryPrunedTableW::open_table(&db_write)?;
+            if before_height <= table_pruned.get_pruned_height()? {
+                return Ok(0);
+            }
+            table_pruned.insert((), before_height)?;
+
+            let mut table = EntityHistoryTableW::open_table(&db_write)?;
+            let keys = table.get_prunable_keys(before_height)?;
+            table.remove_keys(&keys)?;
+            keys.len()
+        };
+        db_write.commit()?;
+        Ok(count)
+    }
+
+    // the snapshot of the last block replaces the previous one.
+    // the tables are read in key order, so the nodes produce the same chunks at the same height
+    pub fn create_snapshot(
+        &self,
+        height: BlockHeight,
+        chunk_size: usize,
+    ) -> anyhow::Result<SnapshotManifest> {
+        let state = {
+            let db_read = self.database.begin_read()?;
+            {
+                let table = BlockHeightTableR::open_table(&db_read)?;
+                let last_height = table.get_block_height()?;
+                if last_height != height {
+                    return Err(anyhow!(
+                        "block {} commited before the snapshot of {}",
+                        last_height,
+                        height
+                    ));
+                }
+            }
+            SnapshotState {
+                header: {
+                    let table = BlockTableR::open_table(&db_read)?;
+                    table.get_block(height)?.header
+                },
+                entities: {
+                    let table = EntityTableR::open_table(&db_read)?;
+                    table.get_entities()?
+                },
+                wasm_txs: {
+                    let table = WasmTxTableR::open_table(&db_read)?;
+                    table.get_wasm_txs()?
+                },
+                upgrade_wasm_ids: {
+                    let table = UpgradeWasmTableR::open_table(&db_read)?;
+                    table.get_upgrade_schedule(0, usize::MAX)?
+                },
+                validator_set_txs: {
+                    let table = ValidatorSetTableR::open_table(&db_read)?;
+                    table.get_validator_set_txs()?
+                },
+            }
+        };
+        let bytes = state.bincode_serialize()?;
+        let chunks: Vec<&[u8]> = bytes.chunks(chunk_size).collect();
+        let manifest = SnapshotManifest {
+            height,
+            block_hash: state.header.calc_hash(),
+            chunk_hashes: chunks.iter().map(|chunk| chunk.calc_hash()).collect(),
+        };
+
+        let db_write = self.database.begin_write()?;
+        {
+            let mut table_manifest = SnapshotManifestTableW::open_table(&db_write)?;
+            let old_count = table_manifest
+                .get_manifest()?
+                .map(|manifest| manifest.chunk_hashes.len())
+                .unwrap_or(0);
+            let mut table_chunk = SnapshotChunkTableW::open_table(&db_write)?;
+            table_chunk.replace_chunks(old_count, &chunks)?;
+            table_manifest.insert_manifest(&manifest)?;
+        }
+        db_write.commit()?;
+        Ok(manifest)
+    }
+
+    // complete all operations within a single transaction
+    // the chunks are verified against the manifest, the manifest by the nodes voting for it,
+    // and its block by the commit proof of the validators at the height, proved from the genesis ones
+    pub fn restore_snapshot(
+        &self,
+        manifest: &SnapshotManifest,
+        chunks: &[Vec<u8>],
+        proof: &BlockProof,
+        genesis_validators: &[ValidatorNode],
+    ) -> anyhow::Result<Vec<WasmId>> {
+        if chunks.len() != manifest.chunk_hashes.len() {
+            return Err(anyhow!("snapshot chunk count mismatch"));
+        }
+        let state = {
+            let mut bytes = Vec::new();
+            for (index, (chunk, hash)) in chunks.iter().zip(&manifest.chunk_hashes).enumerate() {
+                if chunk.calc_hash() != *hash {
+                    return Err(anyhow!("snapshot chunk {} hash mismatch", index));
+                }
+                bytes.extend_from_slice(chunk);
+            }
+            let (state, _bytes_read) = SnapshotState::bincode_deserialize(&bytes)?;
+            state
+        };
+        let height = manifest.height;
+        if state.header.height != height || state.header.calc_hash() != manifest.block_hash {
+            return Err(anyhow!("snapshot {} block hash mismatch", height));
+        }
+        if proof.height != height || proof.block_hash != manifest.block_hash {
+            return Err(anyhow!("snapshot {} proof mismatch", height));
+        }
+        let validators = state.verified_validators(genesis_validators, height)?;
+        verify_block_proof(proof, &validators)?;
+
+        let db_write = self.database.begin_write()?;
+        {
+            let table = BlockHeightTableW::open_table(&db_write)?;
+            let last_height = table.get_block_height()?;
+            if last_height != GENESIS_BLOCK_HEIGHT {
+                return Err(anyhow!(
+                    "snapshot can only be restored to an empty db, last height is {}",
+                    last_height
+                ));
+            }
+        }
+        // entities, the state is checked against the root committed in the block
+        {
+            let mut table_entity = EntityTableW::open_table(&db_write)?;
+            let mut table_state = EntityStateTableW::open_table(&db_write)?;
+            let mut overlay = SmtOverlay::new(&table_state);
+            for (key, hash) in &state.entities {
+                table_entity.insert_entity_by_key(key, hash)?;
+                overlay.update(&key.as_bytes().calc_hash(), &entity_state_value(hash))?;
+            }
+            let state_root = overlay.root()?;
+            if state_root != state.header.state_root {
+                return Err(anyhow!(
+                    "snapshot {} state root, {} != {}",
+                    height,
+                    state.header.state_root,
+                    state_root
+                ));
+            }
+            let nodes = overlay.into_nodes();
+            table_state.insert_nodes(nodes)?;
+        }
+        // the versions before the snapshot are not available
+        {
+            let mut table = EntityHistoryPrunedTableW::open_table(&db_write)?;
+            table.insert((), height + 1)?;
+        }
+        let mut wasm_ids = Vec::new();
+        {
+            let mut table = WasmTxTableW::open_table(&db_write)?;
+            for (wasm_id, wasm_info) in state.wasm_txs {
+                table.insert_wasm_tx(&wasm_id, &wasm_info)?;
+                wasm_ids.push(wasm_id);
+            }
+        }
+        {
+            let mut table = UpgradeWasmTableW::open_table(&db_write)?;
+            for (future_height, upgrade_wasm_ids) in state.upgrade_wasm_ids {
+                table.insert_upgrade_wasm_ids(future_height, upgrade_wasm_ids)?;
+            }
+        }
+        {
+            let mut table = ValidatorSetTableW::open_table(&db_write)?;
+            for (effective_height, validator_set_tx) in &state.validator_set_txs {
+                table.insert_validator_set(*effective_height, validator_set_tx)?;
+            }
+        }
+        // the block at the height, without the txs, the next block is imported on it
+        {
+            let mut table = BlockHashTableW::open_table(&db_write)?;
+            table.insert_block_height(&manifest.block_hash, height)?;
+            let mut table_block = BlockTableW::open_table(&db_write)?;
+            table_block.insert_block(
+                height,
+                &BlockInDb {
+                    hash: manifest.block_hash.clone(),
+                    header: state.header,
+                    act_tx_ids: Vec::new(),
+                    ue_tx_ids: Vec::new(),
+                    wasm_ids: Vec::new(),
+                    validator_set_tx: None,
+                },
+            )?;
+        }
+        {
+            let mut table = BlockProofTableW::open_table(&db_write)?;
+            table.insert_block_proof(height, proof)?;
+        }
+        {
+            let mut table_block_height = BlockHeightTableW::open_table(&db_write)?;
+            table_block_height.insert((), height)?;
+        }
+
+        db_write.commit()?;
+        Ok(wasm_ids)
+    }
+
     // complete all operations within a single transaction
+    // the blocks after the target height are removed with their txs, and the state is reverted to the
+    // versions of the entity history at the target height. the removed txs are not returned to the pool
+    pub fn rollback_blocks(&self, count: u64) -> anyhow::Result<BlockHeight> {
+        let db_write = self.database.begin_write()?;
+        let last_height = {
+            let table = BlockHeightTableW::open_table(&db_write)?;
+            table.get_block_height()?
+        };
+        let target_height = last_height.checked_sub(count).ok_or_else(|| {
+            anyhow!(
+                "last height is {}, {} blocks cannot be rolled back",
+                last_height,
+                count
+            )
+        })?;
+        {
+            let table = EntityHistoryPrunedTableW::open_table(&db_write)?;
+            let pruned_height = table.get_pruned_height()?;
+            if target_height < pruned_height {
+                return Err(anyhow!(
+                    "entity history before height {} is pruned, cannot roll back to {}",
+                    pruned_height,
+                    target_height
+                ));
+            }
+        }
+        // restored from a snapshot, the entities of the snapshot have no history
+        let restored = {
+            let table = BlockTableW::open_table(&db_write)?;
+            last_height > GENESIS_BLOCK_HEIGHT && !table.exists(GENESIS_BLOCK_HEIGHT + 1)?
+        };
+
+        {
+            let mut table_state = EntityStateTableW::open_table(&db_write)?;
+            let mut overlay = SmtOverlay::new(&table_state);
+            for height in (target_height + 1..=last_height).rev() {
+                Self::rollback_block(&db_write, &mut overlay, height, restored)?;
+            }
+            let target_state_root = if target_height == GENESIS_BLOCK_HEIGHT {
+                genesis_block_header().state_root
+            } else {
+                let table = BlockTableW::open_table(&db_write)?;
+                table.get_block(target_height)?.header.state_root
+            };
+            let state_root = overlay.root()?;
+            if state_root != target_state_root {
+                return Err(anyhow!(
+                    "block {} state root after rollback, {} != {}",
+                    target_height,
+                    target_state_root,
+                    state_root
+                ));
+            }
+            let nodes = overlay.into_nodes();
+            table_state.insert_nodes(nodes)?;
+        }
+        // the snapshot of a removed block is not served
+        {
+            let mut table_manifest = SnapshotManifestTableW::open_table(&db_write)?;
+            if let Some(manifest) = table_manifest.get_manifest()? {
+                if manifest.height > target_height {
+                    let mut table_chunk = SnapshotChunkTableW::open_table(&db_write)?;
+                    table_chunk.replace_chunks(manifest.chunk_hashes.len(), &[])?;
+                    table_manifest.remove_manifest()?;
+                }
+            }
+        }
+        {
+            let mut table_block_height = BlockHeightTableW::open_table(&db_write)?;
+            table_block_height.insert((), target_height)?;
+        }
+
+        db_write.commit()?;
+        Ok(target_height)
+    }
+
+    fn rollback_block(
+        db_write: &WriteTransaction,
+        overlay: &mut SmtOverlay<impl SmtStore>,
+        height: BlockHeight,
+        restored: bool,
+    ) -> anyhow::Result<()> {
+        let block = {
+            let mut table = BlockTableW::open_table(db_write)?;
+            let block = table.get_block(height)?;
+            table.remove_block(height)?;
+            let mut table_proof = BlockProofTableW::open_table(db_write)?;
+            table_proof.remove_block_proof(height)?;
+            let mut table_hash = BlockHashTableW::open_table(db_write)?;
+            table_hash.remove_block_height(&block.hash)?;
+            block
+        };
+        let mut tx_items: Vec<Hashed> = block
+            .act_tx_ids
+            .iter()
+            .chain(&block.ue_tx_ids)
+            .cloned()
+            .collect();
+        // entities, reverted to the version before the block
+        {
+            let mut table_ue_tx = UpdateEntityTxTableW::open_table(db_write)?;
+            let mut table_entity = EntityTableW::open_table(db_write)?;
+            let mut table_history = EntityHistoryTableW::open_table(db_write)?;
+            for ue_tx_id in &block.ue_tx_ids {
+                let ue_tx = table_ue_tx.get_tx(ue_tx_id)?;
+                for entity in &ue_tx.entities {
+                    let state_key = entity_state_key(&ue_tx.proto, &ue_tx.model, &entity.id);
+                    table_history.remove_entity_version(&state_key, height)?;
+                    match table_history.get_entity_version_at(&state_key, height - 1)? {
+                        Some(version) => {
+                            table_entity.insert_entity(
+                                &ue_tx.proto,
+                                &ue_tx.model,
+                                &entity.id,
+                                &version.hash,
+                            )?;
+                            overlay.update(&state_key, &entity_state_value(&version.hash))?;
+                        }
+                        None if restored => {
+                            return Err(anyhow!(
+                                "entity {} {} may be restored from the snapshot, its version before block {} is unknown",
+                                ue_tx.model,
+                                entity.id,
+                                height
+                            ));
+                        }
+                        None => {
+                            table_entity.remove_entity(&ue_tx.proto, &ue_tx.model, &entity.id)?;
+                            overlay.remove(&state_key)?;
+                        }
+                    }
+                }
+                table_ue_tx.remove_tx(ue_tx_id)?;
+            }
+        }
+        {
+            let mut table = ActTxTableW::open_table(db_write)?;
+            for act_tx_id in &block.act_tx_ids {
+                table.remove_tx(act_tx_id)?;
+            }
+            let mut table = TxReceiptTableW::open_table(db_write)?;
+            for tx_id in block.act_tx_ids.iter().chain(&block.ue_tx_ids) {
+                table.remove_tx_status(tx_id)?;
+            }
+        }
+        {
+            let mut table_wasm_tx = WasmTxTableW::open_table(db_write)?;
+            let mut table_upgrade = UpgradeWasmTableW::open_table(db_write)?;
+            for wasm_id in block.wasm_ids {
+                let wasm_info = table_wasm_tx.get_wasm_tx(&wasm_id)?;
+                table_upgrade
+                    .remove_upgrade_wasm_id(height + wasm_info.block_interval, &wasm_id)?;
+                table_wasm_tx.remove_wasm_tx(&wasm_id)?;
+                tx_items.push(WasmTx { wasm_id, wasm_info }.calc_hash());
+            }
+        }
+        if let Some(validator_set_tx) = &block.validator_set_tx {
+            let mut table = ValidatorSetTableW::open_table(db_write)?;
+            table.remove_validator_set(height + validator_set_tx.block_interval)?;
+            tx_items.push(validator_set_tx.calc_hash());
+        }
+        {
+            let mut table = TxHeightTableW::open_table(db_write)?;
+            for tx_id in &tx_items {
+                table.remove_tx_height(tx_id)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn commit_block(
         &self,
         height: BlockHeight,
         hash: BlockHash,
-        state: BlockState,
         act_tx_ids: Vec<TxId>,
         ue_tx_ids: Vec<TxId>,
         wasm_ids: Vec<WasmId>,
         block: Block,
+        proof: BlockProof,
     ) -> anyhow::Result<()> {
         let db_write = self.database.begin_write()?;
 
+        // index
+        {
+            let mut table = TxHeightTableW::open_table(&db_write)?;
+            for tx_id in block.body.tx_items() {
+                table.insert_tx_height(&tx_id, height)?;
+            }
+            let mut table = BlockHashTableW::open_table(&db_write)?;
+            table.insert_block_height(&hash, height)?;
+        }
         // remove txs in pool
         {
             let mut table_pool = UpdateEntityTxPoolTableW::open_table(&db_write)?;
@@ -202,26 +832,62 @@ impl BlockChainDbInner {
         // insert txs
         {
             let mut table_act_tx = ActTxTableW::open_table(&db_write)?;
-            for act_tx in &block.act_txs {
-                table_act_tx.insert_tx(&hash, &act_tx)?;
+            for (act_tx_id, act_tx) in act_tx_ids.iter().zip(&block.body.act_txs) {
+                table_act_tx.insert_tx(act_tx_id, act_tx)?;
             }
         }
         {
             let mut table_ue_tx = UpdateEntityTxTableW::open_table(&db_write)?;
             let mut table_entity = EntityTableW::open_table(&db_write)?;
-            for ue_tx in &block.ue_txs {
-                table_ue_tx.insert_tx(&hash, &ue_tx)?;
+            let mut table_history = EntityHistoryTableW::open_table(&db_write)?;
+            for (ue_tx_id, ue_tx) in ue_tx_ids.iter().zip(&block.body.ue_txs) {
+                table_ue_tx.insert_tx(ue_tx_id, ue_tx)?;
                 for entity in &ue_tx.entities {
-                    table_entity
-                        .insert_entity(&ue_tx.proto, &ue_tx.model, &entity.id, &entity.hash)
-                        .unwrap()
+                    table_entity.insert_entity(
+                        &ue_tx.proto,
+                        &ue_tx.model,
+                        &entity.id,
+                        &entity.hash,
+                    )?;
+                    table_history.insert_entity_version(
+                        &entity_state_key(&ue_tx.proto, &ue_tx.model, &entity.id),
+                        &EntityVersion {
+                            height,
+                            ue_tx_id: ue_tx_id.clone(),
+                            req_id: ue_tx.req_id.clone(),
+                            hash: entity.hash.clone(),
+                        },
+                    )?;
                 }
             }
         }
+        {
+            let mut table = TxReceiptTableW::open_table(&db_write)?;
+            for tx_id in act_tx_ids.iter().chain(&ue_tx_ids) {
+                table.insert_tx_status(tx_id, &TxStatus::Included(height))?;
+            }
+        }
+        // update state, the block is not committed if the root differs
+        {
+            let mut table = EntityStateTableW::open_table(&db_write)?;
+            let mut overlay = SmtOverlay::new(&table);
+            Self::update_state(&mut overlay, &block.body.ue_txs)?;
+            let state_root = overlay.root()?;
+            if state_root != block.header.state_root {
+                return Err(anyhow!(
+                    "block {} state root, {} != {}",
+                    height,
+                    block.header.state_root,
+                    state_root
+                ));
+            }
+            let nodes = overlay.into_nodes();
+            table.insert_nodes(nodes)?;
+        }
         let mut height_to_wasm_ids: HashMap<BlockHeight, Vec<WasmId>> = HashMap::new();
         {
             let mut table_wasm_tx = WasmTxTableW::open_table(&db_write)?;
-            for wasm_tx in block.wasm_txs {
+            for wasm_tx in block.body.wasm_txs {
                 table_wasm_tx.insert_wasm_tx(&wasm_tx.wasm_id, &wasm_tx.wasm_info)?;
                 match height_to_wasm_ids.entry(height + wasm_tx.wasm_info.block_interval) {
                     Entry::Occupied(mut entry) => {
@@ -241,6 +907,11 @@ impl BlockChainDbInner {
                 table.insert_upgrade_wasm_ids(future_height, wasm_ids)?;
             }
         }
+        if let Some(validator_set_tx) = &block.body.validator_set_tx {
+            let mut table = ValidatorSetTableW::open_table(&db_write)?;
+            table
+                .insert_validator_set(height + validator_set_tx.block_interval, validator_set_tx)?;
+        }
 
         // insert block
         {
@@ -249,13 +920,15 @@ impl BlockChainDbInner {
                 height,
                 &BlockInDb {
                     hash,
-                    state,
-                    timestamp: block.timestamp,
+                    header: block.header,
                     act_tx_ids,
                     ue_tx_ids,
                     wasm_ids,
+                    validator_set_tx: block.body.validator_set_tx,
                 },
             )?;
+            let mut table_proof = BlockProofTableW::open_table(&db_write)?;
+            table_proof.insert_block_proof(height, &proof)?;
         }
 
         // update block height
@@ -1,9 +1,7 @@
-use crate::chain::BlockState;
 use crate::tx::TxId;
-use anyhow::anyhow;
 use redb::ReadableTable;
 use serde::{Deserialize, Serialize};
-use vintage_msg::{BlockHash, BlockHeight, BlockTimestamp, WasmId};
+use vintage_msg::{ApiError, BlockHash, BlockHeader, BlockHeight, ValidatorSetTx, WasmId};
 use vintage_utils::{define_redb_table, BincodeDeserialize, BincodeSerialize, RedbBytes};
 
 define_redb_table! {
@@ -13,11 +11,11 @@ define_redb_table! {
 #[derive(Serialize, Deserialize)]
 pub(crate) struct BlockInDb {
     pub hash: BlockHash,
-    pub timestamp: BlockTimestamp,
-    pub state: BlockState,
+    pub header: BlockHeader,
     pub act_tx_ids: Vec<TxId>,
     pub ue_tx_ids: Vec<TxId>,
     pub wasm_ids: Vec<WasmId>,
+    pub validator_set_tx: Option<ValidatorSetTx>,
 }
 
 impl<TABLE> BlockTable<TABLE>
@@ -30,7 +28,7 @@ where
                 let (value, _bytes_read) = BlockInDb::bincode_deserialize(access.value())?;
                 Ok(value)
             }
-            None => Err(anyhow!("block {} not found", height)),
+            None => Err(ApiError::not_found(format!("block {} not found", height))),
         }
     }
 }
@@ -41,4 +39,9 @@ impl<'db, 'txn> BlockTableW<'db, 'txn> {
         self.insert(height, bytes.as_slice())?;
         Ok(())
     }
+
+    pub fn remove_block(&mut self, height: BlockHeight) -> anyhow::Result<()> {
+        self.table.remove(height)?;
+        Ok(())
+    }
 }
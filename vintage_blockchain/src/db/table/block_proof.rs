@@ -0,0 +1,40 @@
+use redb::ReadableTable;
+use vintage_msg::{BlockHeight, BlockProof};
+use vintage_utils::{define_redb_table, BincodeDeserialize, BincodeSerialize, RedbBytes};
+
+// the commit proofs of the blocks, none for the genesis block
+define_redb_table! {
+    pub(crate) (BlockProofTable, BlockProofTableR, BlockProofTableW) = (BlockHeight, RedbBytes, "block_proof")
+}
+
+impl<TABLE> BlockProofTable<TABLE>
+where
+    TABLE: ReadableTable<BlockHeight, RedbBytes>,
+{
+    pub fn get_block_proof(&self, height: BlockHeight) -> anyhow::Result<Option<BlockProof>> {
+        match self.get(height)? {
+            Some(access) => {
+                let (proof, _bytes_read) = BlockProof::bincode_deserialize(access.value())?;
+                Ok(Some(proof))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'db, 'txn> BlockProofTableW<'db, 'txn> {
+    pub fn insert_block_proof(
+        &mut self,
+        height: BlockHeight,
+        proof: &BlockProof,
+    ) -> anyhow::Result<()> {
+        let bytes = proof.bincode_serialize()?;
+        self.insert(height, bytes.as_slice())?;
+        Ok(())
+    }
+
+    pub fn remove_block_proof(&mut self, height: BlockHeight) -> anyhow::Result<()> {
+        self.table.remove(height)?;
+        Ok(())
+    }
+}
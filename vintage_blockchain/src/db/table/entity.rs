@@ -1,6 +1,5 @@
-use anyhow::anyhow;
 use redb::ReadableTable;
-use vintage_msg::{EntityHash, EntityId, Model, Proto};
+use vintage_msg::{entity_key, ApiError, EntityHash, EntityId, Model, Proto};
 use vintage_utils::{define_redb_table, RedbStr};
 
 define_redb_table! {
@@ -17,11 +16,24 @@ where
         model: &Model,
         entity_id: &EntityId,
     ) -> anyhow::Result<EntityHash> {
-        match self.get(key(proto, model, entity_id).as_str())? {
+        match self.get(entity_key(proto, model, entity_id).as_str())? {
             Some(access) => Ok(access.value().into()),
-            None => Err(anyhow!("entity {} {} not found", model, entity_id)),
+            None => Err(ApiError::not_found(format!(
+                "entity {} {} not found",
+                model, entity_id
+            ))),
         }
     }
+
+    // entity key and hash, in key order
+    pub fn get_entities(&self) -> anyhow::Result<Vec<(String, EntityHash)>> {
+        let mut entities = Vec::new();
+        for result in self.table.iter()? {
+            let (key, hash) = result?;
+            entities.push((key.value().to_string(), hash.value().to_string()));
+        }
+        Ok(entities)
+    }
 }
 
 impl<'db, 'txn> EntityTableW<'db, 'txn> {
@@ -32,11 +44,23 @@ impl<'db, 'txn> EntityTableW<'db, 'txn> {
         entity_id: &EntityId,
         hash: &EntityHash,
     ) -> anyhow::Result<()> {
-        self.insert(key(proto, model, entity_id).as_str(), hash.as_str())?;
+        self.insert(entity_key(proto, model, entity_id).as_str(), hash.as_str())?;
+        Ok(())
+    }
+
+    pub fn insert_entity_by_key(&mut self, key: &str, hash: &EntityHash) -> anyhow::Result<()> {
+        self.insert(key, hash.as_str())?;
         Ok(())
     }
-}
 
-fn key(proto: &Proto, model: &Model, entity_id: &EntityId) -> String {
-    format!("{}:{}:{}", proto, model, entity_id)
+    pub fn remove_entity(
+        &mut self,
+        proto: &Proto,
+        model: &Model,
+        entity_id: &EntityId,
+    ) -> anyhow::Result<()> {
+        self.table
+            .remove(entity_key(proto, model, entity_id).as_str())?;
+        Ok(())
+    }
 }
@@ -0,0 +1,146 @@
+use redb::{ReadableTable, StorageError};
+use vintage_msg::{BlockHeight, EntityVersion};
+use vintage_utils::{
+    define_redb_table, BincodeDeserialize, BincodeSerialize, Hashed, RedbBytes, RedbBytesN,
+    HASH_SIZE,
+};
+
+// key: entity state key + height
+const ENTITY_HISTORY_KEY_SIZE: usize = HASH_SIZE + 8;
+type EntityHistoryKey = [u8; ENTITY_HISTORY_KEY_SIZE];
+
+define_redb_table! {
+    pub(crate) (EntityHistoryTable, EntityHistoryTableR, EntityHistoryTableW) = (RedbBytesN<ENTITY_HISTORY_KEY_SIZE>, RedbBytes, "entity_history")
+}
+
+// the versions before the height are pruned
+define_redb_table! {
+    pub(crate) (EntityHistoryPrunedTable, EntityHistoryPrunedTableR, EntityHistoryPrunedTableW) = ((), BlockHeight, "entity_history_pruned")
+}
+
+fn history_key(state_key: &Hashed, height: BlockHeight) -> EntityHistoryKey {
+    let mut key = [0; ENTITY_HISTORY_KEY_SIZE];
+    key[..HASH_SIZE].copy_from_slice(state_key.as_bytes());
+    key[HASH_SIZE..].copy_from_slice(&height.to_be_bytes());
+    key
+}
+
+fn height_of(key: &EntityHistoryKey) -> BlockHeight {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&key[HASH_SIZE..]);
+    BlockHeight::from_be_bytes(bytes)
+}
+
+impl<TABLE> EntityHistoryTable<TABLE>
+where
+    TABLE: ReadableTable<RedbBytesN<ENTITY_HISTORY_KEY_SIZE>, RedbBytes>,
+{
+    // the last version at or before the height
+    pub fn get_entity_version_at(
+        &self,
+        state_key: &Hashed,
+        height: BlockHeight,
+    ) -> anyhow::Result<Option<EntityVersion>> {
+        let begin = history_key(state_key, 0);
+        let end = history_key(state_key, height);
+        match self
+            .table
+            .range::<&EntityHistoryKey>(&begin..=&end)?
+            .next_back()
+        {
+            Some(result) => {
+                let (_, access) = result?;
+                let (version, _bytes_read) = EntityVersion::bincode_deserialize(access.value())?;
+                Ok(Some(version))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // the versions at and after the height, at most count
+    pub fn get_entity_versions(
+        &self,
+        state_key: &Hashed,
+        begin_height: BlockHeight,
+        count: usize,
+    ) -> anyhow::Result<Vec<EntityVersion>> {
+        let begin = history_key(state_key, begin_height);
+        let end = history_key(state_key, BlockHeight::MAX);
+        let mut versions = Vec::new();
+        for result in self
+            .table
+            .range::<&EntityHistoryKey>(&begin..=&end)?
+            .take(count)
+        {
+            let (_, access) = result?;
+            let (version, _bytes_read) = EntityVersion::bincode_deserialize(access.value())?;
+            versions.push(version);
+        }
+        Ok(versions)
+    }
+
+    // the versions before the height, except the last one of each entity,
+    // which is still the version at the height
+    pub fn get_prunable_keys(
+        &self,
+        before_height: BlockHeight,
+    ) -> anyhow::Result<Vec<EntityHistoryKey>> {
+        let mut keys = Vec::new();
+        let mut last_key: Option<EntityHistoryKey> = None;
+        for result in self.table.iter()? {
+            let (access, _) = result?;
+            let key = *access.value();
+            if height_of(&key) >= before_height {
+                continue;
+            }
+            if let Some(last_key) = last_key {
+                if last_key[..HASH_SIZE] == key[..HASH_SIZE] {
+                    keys.push(last_key);
+                }
+            }
+            last_key = Some(key);
+        }
+        Ok(keys)
+    }
+}
+
+impl<'db, 'txn> EntityHistoryTableW<'db, 'txn> {
+    pub fn insert_entity_version(
+        &mut self,
+        state_key: &Hashed,
+        version: &EntityVersion,
+    ) -> anyhow::Result<()> {
+        let bytes = version.bincode_serialize()?;
+        self.insert(&history_key(state_key, version.height), bytes.as_slice())?;
+        Ok(())
+    }
+
+    pub fn remove_entity_version(
+        &mut self,
+        state_key: &Hashed,
+        height: BlockHeight,
+    ) -> anyhow::Result<()> {
+        self.table.remove(&history_key(state_key, height))?;
+        Ok(())
+    }
+
+    pub fn remove_keys(&mut self, keys: &[EntityHistoryKey]) -> anyhow::Result<()> {
+        for key in keys {
+            self.table.remove(key)?;
+        }
+        Ok(())
+    }
+}
+
+impl<TABLE> EntityHistoryPrunedTable<TABLE>
+where
+    TABLE: ReadableTable<(), BlockHeight>,
+{
+    pub fn get_pruned_height(&self) -> Result<BlockHeight, StorageError> {
+        let height = match self.get(())? {
+            Some(access) => access.value(),
+            None => 0,
+        };
+        Ok(height)
+    }
+}
@@ -0,0 +1,35 @@
+use redb::ReadableTable;
+use std::collections::HashMap;
+use vintage_utils::{
+    define_redb_table, Hashed, RedbBytes32, RedbBytesN, SmtNodeKey, SmtStore, SMT_NODE_KEY_SIZE,
+};
+
+// nodes of the entity state tree
+define_redb_table! {
+    pub(crate) (EntityStateTable, EntityStateTableR, EntityStateTableW) = (RedbBytesN<SMT_NODE_KEY_SIZE>, RedbBytes32, "entity_state")
+}
+
+impl<TABLE> SmtStore for EntityStateTable<TABLE>
+where
+    TABLE: ReadableTable<RedbBytesN<SMT_NODE_KEY_SIZE>, RedbBytes32>,
+{
+    fn get_node(&self, node_key: &SmtNodeKey) -> anyhow::Result<Option<Hashed>> {
+        let node = self
+            .get(&node_key.to_bytes())?
+            .map(|access| Hashed::from(access.value()));
+        Ok(node)
+    }
+}
+
+impl<'db, 'txn> EntityStateTableW<'db, 'txn> {
+    pub fn insert_nodes(&mut self, nodes: HashMap<SmtNodeKey, Hashed>) -> anyhow::Result<()> {
+        for (node_key, hash) in nodes {
+            if hash == Hashed::zero_hash() {
+                self.table.remove(&node_key.to_bytes())?;
+            } else {
+                self.insert(&node_key.to_bytes(), hash.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+}
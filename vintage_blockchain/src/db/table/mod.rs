@@ -2,14 +2,28 @@ mod ue_tx_pool;
 
 mod block;
 mod block_height;
+mod block_proof;
 mod entity;
+mod entity_history;
+mod entity_state;
+mod snapshot;
 mod tx;
+mod tx_height;
+mod tx_receipt;
 mod upgrade_wasm;
+mod validator_set;
 mod wasm_tx;
 
 pub(crate) use self::block::*;
 pub(crate) use self::block_height::*;
+pub(crate) use self::block_proof::*;
 pub(crate) use self::entity::*;
+pub(crate) use self::entity_history::*;
+pub(crate) use self::entity_state::*;
+pub(crate) use self::snapshot::*;
 pub(crate) use self::tx::*;
+pub(crate) use self::tx_height::*;
+pub(crate) use self::tx_receipt::*;
 pub(crate) use self::upgrade_wasm::*;
+pub(crate) use self::validator_set::*;
 pub(crate) use self::wasm_tx::*;
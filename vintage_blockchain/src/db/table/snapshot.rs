@@ -0,0 +1,134 @@
+use crate::tx::check_validator_set_tx;
+use anyhow::anyhow;
+use redb::ReadableTable;
+use serde::{Deserialize, Serialize};
+use vintage_msg::{
+    BlockHash, BlockHeader, BlockHeight, EntityHash, ValidatorNode, ValidatorSetTx, WasmId,
+    WasmInfo,
+};
+use vintage_utils::{define_redb_table, BincodeDeserialize, BincodeSerialize, Hashed, RedbBytes};
+
+// the latest snapshot, only one is kept
+define_redb_table! {
+    pub(crate) (SnapshotManifestTable, SnapshotManifestTableR, SnapshotManifestTableW) = ((), RedbBytes, "snapshot_manifest")
+}
+
+// key: chunk index
+define_redb_table! {
+    pub(crate) (SnapshotChunkTable, SnapshotChunkTableR, SnapshotChunkTableW) = (u64, RedbBytes, "snapshot_chunk")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SnapshotManifest {
+    pub height: BlockHeight,
+    pub block_hash: BlockHash,
+    pub chunk_hashes: Vec<Hashed>,
+}
+
+// the state after the block at the height, split into the chunks of the manifest.
+// the entity state tree is rebuilt from the entities and checked against the state root.
+// only the state is kept, the txs before the snapshot are not in the restored db
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SnapshotState {
+    pub header: BlockHeader,
+    // entity key and hash
+    pub entities: Vec<(String, EntityHash)>,
+    pub wasm_txs: Vec<(WasmId, WasmInfo)>,
+    pub upgrade_wasm_ids: Vec<(BlockHeight, Vec<WasmId>)>,
+    // key: effective height
+    pub validator_set_txs: Vec<(BlockHeight, ValidatorSetTx)>,
+}
+
+impl SnapshotState {
+    // the validator sets of the snapshot are not trusted, each one is checked against the approvals
+    // of the set at the height it is included in, starting from the genesis validators
+    pub fn verified_validators(
+        &self,
+        genesis_validators: &[ValidatorNode],
+        height: BlockHeight,
+    ) -> anyhow::Result<Vec<ValidatorNode>> {
+        let mut validator_sets: Vec<(BlockHeight, &[ValidatorNode])> = Vec::new();
+        let mut last_validator_set = None;
+        for validator_set in &self.validator_set_txs {
+            let (effective_height, validator_set_tx) = validator_set;
+            let included_height = effective_height
+                .checked_sub(validator_set_tx.block_interval)
+                .ok_or_else(|| anyhow!("validator set {} block interval", effective_height))?;
+            let validators = validators_at(&validator_sets, genesis_validators, included_height);
+            check_validator_set_tx(
+                validator_set_tx,
+                included_height,
+                validators,
+                last_validator_set,
+            )
+            .map_err(|err| anyhow!("validator set {} err: {}", effective_height, err))?;
+            validator_sets.push((*effective_height, &validator_set_tx.validators));
+            last_validator_set = Some(validator_set);
+        }
+        Ok(validators_at(&validator_sets, genesis_validators, height).to_vec())
+    }
+}
+
+fn validators_at<'a>(
+    validator_sets: &[(BlockHeight, &'a [ValidatorNode])],
+    genesis_validators: &'a [ValidatorNode],
+    height: BlockHeight,
+) -> &'a [ValidatorNode] {
+    validator_sets
+        .iter()
+        .rev()
+        .find(|(effective_height, _)| *effective_height <= height)
+        .map(|(_, validators)| *validators)
+        .unwrap_or(genesis_validators)
+}
+
+impl<TABLE> SnapshotManifestTable<TABLE>
+where
+    TABLE: ReadableTable<(), RedbBytes>,
+{
+    pub fn get_manifest(&self) -> anyhow::Result<Option<SnapshotManifest>> {
+        match self.get(())? {
+            Some(access) => {
+                let (manifest, _bytes_read) =
+                    SnapshotManifest::bincode_deserialize(access.value())?;
+                Ok(Some(manifest))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'db, 'txn> SnapshotManifestTableW<'db, 'txn> {
+    pub fn insert_manifest(&mut self, manifest: &SnapshotManifest) -> anyhow::Result<()> {
+        let bytes = manifest.bincode_serialize()?;
+        self.insert((), bytes.as_slice())?;
+        Ok(())
+    }
+
+    pub fn remove_manifest(&mut self) -> anyhow::Result<()> {
+        self.table.remove(())?;
+        Ok(())
+    }
+}
+
+impl<TABLE> SnapshotChunkTable<TABLE>
+where
+    TABLE: ReadableTable<u64, RedbBytes>,
+{
+    pub fn get_chunk(&self, index: u64) -> anyhow::Result<Option<Vec<u8>>> {
+        let chunk = self.get(index)?.map(|access| access.value().to_vec());
+        Ok(chunk)
+    }
+}
+
+impl<'db, 'txn> SnapshotChunkTableW<'db, 'txn> {
+    pub fn replace_chunks(&mut self, old_count: usize, chunks: &[&[u8]]) -> anyhow::Result<()> {
+        for index in chunks.len()..old_count {
+            self.table.remove(index as u64)?;
+        }
+        for (index, chunk) in chunks.iter().enumerate() {
+            self.insert(index as u64, *chunk)?;
+        }
+        Ok(())
+    }
+}
@@ -33,7 +33,7 @@ macro_rules! define_tx_table {
                         let (value, _bytes_read) = <$tx as vintage_utils::BincodeDeserialize>::bincode_deserialize(access.value())?;
                         Ok(value)
                     }
-                    None => Err(anyhow::anyhow!("tx {} not found", tx_id)),
+                    None => Err(vintage_msg::ApiError::not_found(format!("tx {} not found", tx_id))),
                 }
             }
         }
@@ -44,6 +44,12 @@ macro_rules! define_tx_table {
                 self.insert(tx_id.as_bytes(), bytes.as_slice())?;
                 Ok(())
             }
+
+            #[allow(dead_code)]
+            pub fn remove_tx(&mut self, tx_id: &$crate::tx::TxId) -> anyhow::Result<()> {
+                self.table.remove(tx_id.as_bytes())?;
+                Ok(())
+            }
         }
     }
 }
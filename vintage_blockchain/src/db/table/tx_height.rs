@@ -0,0 +1,64 @@
+use crate::tx::TxId;
+use redb::{ReadableTable, StorageError};
+use vintage_msg::{BlockHash, BlockHeight};
+use vintage_utils::{define_redb_table, RedbBytes32};
+
+// the height of the block including the tx
+define_redb_table! {
+    pub(crate) (TxHeightTable, TxHeightTableR, TxHeightTableW) = (RedbBytes32, BlockHeight, "tx_height")
+}
+
+// the height of the block by its hash
+define_redb_table! {
+    pub(crate) (BlockHashTable, BlockHashTableR, BlockHashTableW) = (RedbBytes32, BlockHeight, "block_hash")
+}
+
+impl<TABLE> TxHeightTable<TABLE>
+where
+    TABLE: ReadableTable<RedbBytes32, BlockHeight>,
+{
+    pub fn get_tx_height(&self, tx_id: &TxId) -> Result<Option<BlockHeight>, StorageError> {
+        Ok(self.get(tx_id.as_bytes())?.map(|access| access.value()))
+    }
+}
+
+impl<'db, 'txn> TxHeightTableW<'db, 'txn> {
+    pub fn insert_tx_height(
+        &mut self,
+        tx_id: &TxId,
+        height: BlockHeight,
+    ) -> Result<(), StorageError> {
+        self.insert(tx_id.as_bytes(), height)?;
+        Ok(())
+    }
+
+    pub fn remove_tx_height(&mut self, tx_id: &TxId) -> Result<(), StorageError> {
+        self.table.remove(tx_id.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl<TABLE> BlockHashTable<TABLE>
+where
+    TABLE: ReadableTable<RedbBytes32, BlockHeight>,
+{
+    pub fn get_block_height(&self, hash: &BlockHash) -> Result<Option<BlockHeight>, StorageError> {
+        Ok(self.get(hash.as_bytes())?.map(|access| access.value()))
+    }
+}
+
+impl<'db, 'txn> BlockHashTableW<'db, 'txn> {
+    pub fn insert_block_height(
+        &mut self,
+        hash: &BlockHash,
+        height: BlockHeight,
+    ) -> Result<(), StorageError> {
+        self.insert(hash.as_bytes(), height)?;
+        Ok(())
+    }
+
+    pub fn remove_block_height(&mut self, hash: &BlockHash) -> Result<(), StorageError> {
+        self.table.remove(hash.as_bytes())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use crate::tx::TxId;
+use redb::ReadableTable;
+use vintage_msg::TxStatus;
+use vintage_utils::{
+    define_redb_table, BincodeDeserialize, BincodeSerialize, RedbBytes, RedbBytes32,
+};
+
+define_redb_table! {
+    pub(crate) (TxReceiptTable, TxReceiptTableR, TxReceiptTableW) = (RedbBytes32, RedbBytes, "tx_receipt")
+}
+
+impl<TABLE> TxReceiptTable<TABLE>
+where
+    TABLE: ReadableTable<RedbBytes32, RedbBytes>,
+{
+    pub fn get_tx_status(&self, tx_id: &TxId) -> anyhow::Result<Option<TxStatus>> {
+        match self.get(tx_id.as_bytes())? {
+            Some(access) => {
+                let (status, _bytes_read) = TxStatus::bincode_deserialize(access.value())?;
+                Ok(Some(status))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'db, 'txn> TxReceiptTableW<'db, 'txn> {
+    pub fn insert_tx_status(&mut self, tx_id: &TxId, status: &TxStatus) -> anyhow::Result<()> {
+        let bytes = status.bincode_serialize()?;
+        self.insert(tx_id.as_bytes(), bytes.as_slice())?;
+        Ok(())
+    }
+
+    pub fn remove_tx_status(&mut self, tx_id: &TxId) -> anyhow::Result<()> {
+        self.table.remove(tx_id.as_bytes())?;
+        Ok(())
+    }
+}
@@ -36,6 +36,10 @@ where
 
         Ok((tx_ids, txs))
     }
+
+    pub fn get_ue_tx_pool_count(&self) -> anyhow::Result<u64> {
+        Ok(self.table.len()?)
+    }
 }
 
 impl<'db, 'txn> UpdateEntityTxPoolTableW<'db, 'txn> {
@@ -19,6 +19,20 @@ where
             None => Ok(Vec::new()),
         }
     }
+
+    pub fn get_upgrade_schedule(
+        &self,
+        begin_height: BlockHeight,
+        count: usize,
+    ) -> anyhow::Result<Vec<(BlockHeight, Vec<WasmId>)>> {
+        let mut schedule = Vec::new();
+        for result in self.table.range(begin_height..)?.take(count) {
+            let (height, access) = result?;
+            let (wasm_ids, _bytes_read) = Vec::<WasmId>::bincode_deserialize(access.value())?;
+            schedule.push((height.value(), wasm_ids));
+        }
+        Ok(schedule)
+    }
 }
 
 impl<'db, 'txn> UpgradeWasmTableW<'db, 'txn> {
@@ -31,4 +45,19 @@ impl<'db, 'txn> UpgradeWasmTableW<'db, 'txn> {
         self.table.insert(block_height, bytes.as_slice())?;
         Ok(())
     }
+
+    pub fn remove_upgrade_wasm_id(
+        &mut self,
+        block_height: BlockHeight,
+        wasm_id: &WasmId,
+    ) -> anyhow::Result<()> {
+        let mut wasm_ids = self.get_upgrade_wasm_ids(block_height)?;
+        wasm_ids.retain(|id| id != wasm_id);
+        if wasm_ids.is_empty() {
+            self.table.remove(block_height)?;
+            Ok(())
+        } else {
+            self.insert_upgrade_wasm_ids(block_height, wasm_ids)
+        }
+    }
 }
@@ -0,0 +1,71 @@
+use redb::ReadableTable;
+use vintage_msg::{BlockHeight, ValidatorNode, ValidatorSetTx};
+use vintage_utils::{define_redb_table, BincodeDeserialize, BincodeSerialize, RedbBytes};
+
+// key: effective height. the tx is kept with its approvals, so the sets of a snapshot are proved
+// from the genesis validators
+define_redb_table! {
+    pub(crate) (ValidatorSetTable, ValidatorSetTableR, ValidatorSetTableW) = (BlockHeight, RedbBytes, "validator_set")
+}
+
+impl<TABLE> ValidatorSetTable<TABLE>
+where
+    TABLE: ReadableTable<BlockHeight, RedbBytes>,
+{
+    // the last validator set effective at or before the block height
+    pub fn get_validator_set(
+        &self,
+        block_height: BlockHeight,
+    ) -> anyhow::Result<Option<Vec<ValidatorNode>>> {
+        match self.table.range(..=block_height)?.next_back() {
+            Some(result) => {
+                let (_, access) = result?;
+                let (validator_set_tx, _bytes_read) =
+                    ValidatorSetTx::bincode_deserialize(access.value())?;
+                Ok(Some(validator_set_tx.validators))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_last_validator_set_tx(
+        &self,
+    ) -> anyhow::Result<Option<(BlockHeight, ValidatorSetTx)>> {
+        match self.table.last()? {
+            Some((height, access)) => {
+                let (validator_set_tx, _bytes_read) =
+                    ValidatorSetTx::bincode_deserialize(access.value())?;
+                Ok(Some((height.value(), validator_set_tx)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_validator_set_txs(&self) -> anyhow::Result<Vec<(BlockHeight, ValidatorSetTx)>> {
+        let mut validator_set_txs = Vec::new();
+        for result in self.table.iter()? {
+            let (height, access) = result?;
+            let (validator_set_tx, _bytes_read) =
+                ValidatorSetTx::bincode_deserialize(access.value())?;
+            validator_set_txs.push((height.value(), validator_set_tx));
+        }
+        Ok(validator_set_txs)
+    }
+}
+
+impl<'db, 'txn> ValidatorSetTableW<'db, 'txn> {
+    pub fn insert_validator_set(
+        &mut self,
+        effective_height: BlockHeight,
+        validator_set_tx: &ValidatorSetTx,
+    ) -> anyhow::Result<()> {
+        let bytes = validator_set_tx.bincode_serialize()?;
+        self.table.insert(effective_height, bytes.as_slice())?;
+        Ok(())
+    }
+
+    pub fn remove_validator_set(&mut self, effective_height: BlockHeight) -> anyhow::Result<()> {
+        self.table.remove(effective_height)?;
+        Ok(())
+    }
+}
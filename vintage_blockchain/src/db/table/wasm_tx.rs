@@ -45,6 +45,17 @@ where
             )),
         }
     }
+
+    pub fn get_wasm_txs(&self) -> anyhow::Result<Vec<(WasmId, WasmInfo)>> {
+        let mut wasm_txs = Vec::new();
+        for result in self.table.iter()? {
+            let (id_access, info_access) = result?;
+            let (wasm_id, _bytes_read) = WasmId::bincode_deserialize(id_access.value())?;
+            let (wasm_info, _bytes_read) = WasmInfo::bincode_deserialize(info_access.value())?;
+            wasm_txs.push((wasm_id, wasm_info));
+        }
+        Ok(wasm_txs)
+    }
 }
 
 impl<'db, 'txn> WasmTxTableW<'db, 'txn> {
@@ -54,4 +65,10 @@ impl<'db, 'txn> WasmTxTableW<'db, 'txn> {
         self.insert(id_bytes.as_slice(), info_bytes.as_slice())?;
         Ok(())
     }
+
+    pub fn remove_wasm_tx(&mut self, wasm_id: &WasmId) -> anyhow::Result<()> {
+        let id_bytes = wasm_id.bincode_serialize()?;
+        self.table.remove(id_bytes.as_slice())?;
+        Ok(())
+    }
 }
@@ -22,19 +22,59 @@ pub(crate) use self::wasm_db::*;
 
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use vintage_msg::BlockChainMsgChannels;
+use vintage_msg::{BlockChainMsgChannels, BlockHeight, NodeId, ValidatorNode};
 use vintage_network::client::NetworkClient;
-use vintage_utils::ServiceStarter;
+use vintage_utils::{ArcQuorum, BlsPrivateKey, ServiceStarter};
 
-const ACT_POOL_CAPACITY: usize = 1000;
 const WASM_POOL_CAPACITY: usize = 4;
 const MAX_ACT_COUNT_PER_BLOCK: usize = 4000;
 const MAX_UE_TX_COUNT_PER_BLOCK: usize = 4000;
+const ENTITY_HISTORY_PRUNE_INTERVAL: u64 = 1000;
+const MAX_TX_RANGE_BLOCKS: u64 = 100;
+const MAX_UPGRADE_SCHEDULE_HEIGHTS: usize = 100;
+const MAX_ENTITY_HISTORY_VERSIONS: usize = 100;
+const MAX_POOL_CONTENTS_COUNT: usize = 1000;
+const SNAPSHOT_CHUNK_SIZE: usize = 1024 * 1024;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BlockChainConfig {
     pub db_path: String,
     pub wasm_db_path: String,
+    // blocks of entity history kept, 0 keeps all
+    #[serde(default)]
+    pub entity_history_blocks: u64,
+    // the protos take turns when the act txs are selected, instead of the arrival order
+    #[serde(default)]
+    pub act_round_robin: bool,
+    #[serde(default)]
+    pub act_pool: ActPoolConfig,
+    // blocks between the state snapshots served to the new nodes, 0 disables them
+    #[serde(default)]
+    pub snapshot_interval: u64,
+    // an empty node starts from the latest snapshot of the nodes instead of the genesis
+    #[serde(default)]
+    pub fast_sync: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ActPoolConfig {
+    pub max_count: usize,
+    pub max_bytes: usize,
+    pub max_count_per_proto: usize,
+    // the txs not included in this time are dropped
+    pub ttl_secs: u64,
+}
+
+impl Default for ActPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_count: 1000,
+            max_bytes: 16 * 1024 * 1024,
+            max_count_per_proto: 500,
+            ttl_secs: 600,
+        }
+    }
 }
 
 pub enum BlockChain {}
@@ -43,7 +83,9 @@ impl BlockChain {
     pub async fn create(
         config: BlockChainConfig,
         block_interval: u64,
-        active_number_of_nodes: usize,
+        private_key: BlsPrivateKey,
+        genesis_validators: Vec<ValidatorNode>,
+        quorum: ArcQuorum<NodeId>,
         channels: BlockChainMsgChannels,
         client: NetworkClient,
     ) -> anyhow::Result<(
@@ -55,12 +97,15 @@ impl BlockChain {
     )> {
         let blockchain_db = BlockChainDb::new(create_blockchain_db_inner(config.db_path).await?);
         let wasm_db = WasmDb::new(create_wasm_db_inner(config.wasm_db_path).await?);
-        let tx_pool = Arc::new(TxPool::new(ACT_POOL_CAPACITY, WASM_POOL_CAPACITY));
+        let tx_pool = Arc::new(TxPool::new(
+            config.act_pool,
+            config.act_round_robin,
+            WASM_POOL_CAPACITY,
+        ));
         let network_msg_sender = MsgToNetworkSender::new(channels.network_msg_sender);
         let proxy_msg_sender = MsgToProxySender::new(channels.proxy_msg_sender);
         let client = Arc::new(BlockChainNetworkClient::new(NetworkClientWrapper::new(
-            client,
-            active_number_of_nodes,
+            client, quorum,
         )));
 
         let blockchain_core = Arc::new(tokio::sync::Mutex::new(BlockChainCore::new(
@@ -69,25 +114,47 @@ impl BlockChain {
             tx_pool.clone(),
             client.clone(),
             proxy_msg_sender.clone(),
+            RandomBeacon::new(private_key),
+            genesis_validators.clone(),
+            config.entity_history_blocks,
+            config.snapshot_interval,
         )));
-        let block_sync_service =
-            BlockSyncService::new(block_interval, client.clone(), channels.block_synced_sender);
+        let block_sync_service = BlockSyncService::new(
+            block_interval,
+            config.fast_sync,
+            client.clone(),
+            channels.block_synced_sender,
+        );
         let blockchain_service = BlockChainService::new(
             blockchain_db.clone(),
             wasm_db.clone(),
-            tx_pool,
+            tx_pool.clone(),
             channels.msg_receiver,
             proxy_msg_sender.clone(),
             network_msg_sender,
+            client.clone(),
+            genesis_validators.clone(),
+        );
+        let blockchain_api = BlockChainApiImpl::new(
+            blockchain_db.clone(),
+            wasm_db.clone(),
+            tx_pool.clone(),
+            genesis_validators.clone(),
         );
         let download_wasm_tasks = DownloadWasmTasks::new(wasm_db, proxy_msg_sender, client);
 
         Ok((
-            BlockConsensusImpl::new(blockchain_core.clone()),
-            BlockChainApiImpl::new(blockchain_db),
+            BlockConsensusImpl::new(blockchain_core.clone(), blockchain_db.clone()),
+            blockchain_api,
             ServiceStarter::new(blockchain_service),
             ServiceStarter::new_with_input(block_sync_service, blockchain_core),
             ServiceStarter::new(download_wasm_tasks),
         ))
     }
+
+    // the node is stopped, the blocks are synced again from the nodes when it starts
+    pub async fn rollback(config: BlockChainConfig, count: u64) -> anyhow::Result<BlockHeight> {
+        let blockchain_db = BlockChainDb::new(create_blockchain_db_inner(config.db_path).await?);
+        blockchain_db.rollback_blocks(count).await
+    }
 }
@@ -1,5 +1,6 @@
 use crate::network::{
-    NetworkClientWrapper, ReqBlock, ReqBlockHash, RequestMsg, RspBlock, RspBlockHash,
+    NetworkClientWrapper, ReqBlock, ReqBlockHash, ReqBlockHeader, ReqSnapshotChunk, RequestMsg,
+    RspBlock, RspBlockHash, RspBlockHeader, RspSnapshotChunk, RspSnapshotManifest,
 };
 use std::time::Duration;
 use vintage_msg::{NetworkMsgHandler, NodeId, WasmHash};
@@ -16,12 +17,13 @@ impl BlockChainNetworkClient {
 
     const TIMEOUT: Duration = Duration::from_millis(10_000);
 
+    // the nodes voting for the hashes are returned
     pub(crate) async fn request_block_hash(
         &self,
         req: ReqBlockHash,
-    ) -> anyhow::Result<(NodeId, RspBlockHash)> {
+    ) -> anyhow::Result<(Vec<NodeId>, RspBlockHash)> {
         self.client
-            .request_with_vote_1(
+            .request_with_vote(
                 NetworkMsgHandler::BlockChain,
                 RequestMsg::ReqBlockHash(req),
                 Self::TIMEOUT,
@@ -29,6 +31,45 @@ impl BlockChainNetworkClient {
             .await
     }
 
+    // a short timeout, an unresponsive node does not hold up the sync behind the fork check
+    pub(crate) async fn request_block_hash_from(
+        &self,
+        req: ReqBlockHash,
+        node_id: NodeId,
+    ) -> anyhow::Result<RspBlockHash> {
+        self.client
+            .request_with_single_node(
+                NetworkMsgHandler::BlockChain,
+                RequestMsg::ReqBlockHash(req),
+                Duration::from_millis(2_000),
+                node_id,
+            )
+            .await
+    }
+
+    pub(crate) fn quorum_nodes(&self) -> Vec<NodeId> {
+        self.client.quorum().voters().cloned().collect()
+    }
+
+    pub(crate) fn is_quorum_reached(&self, node_ids: &[NodeId]) -> bool {
+        self.client.quorum().is_reached(node_ids)
+    }
+
+    pub(crate) async fn request_block_header(
+        &self,
+        req: ReqBlockHeader,
+        node_id: NodeId,
+    ) -> anyhow::Result<RspBlockHeader> {
+        self.client
+            .request_with_single_node(
+                NetworkMsgHandler::BlockChain,
+                RequestMsg::ReqBlockHeader(req),
+                Self::TIMEOUT,
+                node_id,
+            )
+            .await
+    }
+
     pub(crate) async fn request_block(
         &self,
         req: ReqBlock,
@@ -72,6 +113,34 @@ impl BlockChainNetworkClient {
             )
             .await
     }
+
+    // the nodes voting for the manifest are returned
+    pub(crate) async fn request_snapshot_manifest(
+        &self,
+    ) -> anyhow::Result<(Vec<NodeId>, RspSnapshotManifest)> {
+        self.client
+            .request_with_vote(
+                NetworkMsgHandler::BlockChain,
+                RequestMsg::ReqSnapshotManifest,
+                Self::TIMEOUT,
+            )
+            .await
+    }
+
+    pub(crate) async fn request_snapshot_chunk(
+        &self,
+        req: ReqSnapshotChunk,
+        node_id: NodeId,
+    ) -> anyhow::Result<RspSnapshotChunk> {
+        self.client
+            .request_with_single_node(
+                NetworkMsgHandler::BlockChain,
+                RequestMsg::ReqSnapshotChunk(req),
+                Duration::from_millis(60_000),
+                node_id,
+            )
+            .await
+    }
 }
 
 fn is_true(data: &[u8]) -> bool {
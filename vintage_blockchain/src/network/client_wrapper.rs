@@ -3,19 +3,20 @@ use serde::Serialize;
 use std::time::Duration;
 use vintage_msg::{NetworkMsgHandler, NodeId};
 use vintage_network::client::NetworkClient;
-use vintage_utils::{BincodeDeserialize, BincodeSerialize};
+use vintage_utils::{ArcQuorum, BincodeDeserialize, BincodeSerialize, Quorum};
 
 pub struct NetworkClientWrapper {
     client: NetworkClient,
-    active_number_of_nodes: usize,
+    quorum: ArcQuorum<NodeId>,
 }
 
 impl NetworkClientWrapper {
-    pub fn new(client: NetworkClient, active_number_of_nodes: usize) -> Self {
-        Self {
-            client,
-            active_number_of_nodes,
-        }
+    pub fn new(client: NetworkClient, quorum: ArcQuorum<NodeId>) -> Self {
+        Self { client, quorum }
+    }
+
+    pub fn quorum(&self) -> Quorum<NodeId> {
+        self.quorum.read().unwrap().clone()
     }
 
     pub async fn request_with_single_node<TRequest, TResponse>(
@@ -69,31 +70,19 @@ impl NetworkClientWrapper {
         TRequest: Serialize,
         TResponse: DeserializeOwned,
     {
-        log::info!(
-            "====request_broadcast with active_number_of_nodes: {}",
-            self.active_number_of_nodes
+        let quorum = self.quorum();
+        tracing::info!(
+            "====request_broadcast with quorum: {}/{}",
+            quorum.threshold(),
+            quorum.total_weight()
         );
 
         let encoded = request.bincode_serialize()?;
         let (node_ids, rsp_encoded) = self
             .client
-            .request_with_vote(handler, encoded, timeout, self.active_number_of_nodes)
+            .request_with_vote(handler, encoded, timeout, quorum)
             .await?;
         let (rsp, _bytes_read) = TResponse::bincode_deserialize(&rsp_encoded)?;
         Ok((node_ids, rsp))
     }
-
-    pub async fn request_with_vote_1<TRequest, TResponse>(
-        &self,
-        handler: NetworkMsgHandler,
-        request: TRequest,
-        timeout: Duration,
-    ) -> anyhow::Result<(NodeId, TResponse)>
-    where
-        TRequest: Serialize,
-        TResponse: DeserializeOwned,
-    {
-        let (node_ids, data) = self.request_with_vote(handler, request, timeout).await?;
-        Ok((node_ids.first().unwrap().clone(), data))
-    }
 }
@@ -21,7 +21,7 @@ impl MsgToNetworkSender {
                 msg_encoded,
             )),
             Err(err) => {
-                log::error!("failed to encode msg: {:?}", err);
+                tracing::error!("failed to encode msg: {:?}", err);
                 false
             }
         }
@@ -39,7 +39,7 @@ impl MsgToNetworkSender {
                     .send_msg(MsgToNetwork::Response(node_id, request_id, msg_encoded))
             }
             Err(err) => {
-                log::error!("failed to encode msg: {:?}", err);
+                tracing::error!("failed to encode msg: {:?}", err);
                 false
             }
         }
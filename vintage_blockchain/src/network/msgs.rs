@@ -1,17 +1,29 @@
+use crate::db::SnapshotManifest;
 use serde::{Deserialize, Serialize};
-use vintage_msg::{ActTx, Block, BlockHash, BlockHeight, WasmHash};
+use vintage_msg::{
+    ActTx, BlockBody, BlockHash, BlockHeader, BlockHeight, BlockProof, UpdateEntityTx,
+    ValidatorSetTx, WasmHash, WasmTx,
+};
 
+// the variants are named after the txs they carry
+#[allow(clippy::enum_variant_names)]
 #[derive(Serialize, Deserialize)]
 pub(crate) enum BroadcastMsg {
     ActTx(ActTx),
+    UpdateEntityTx(UpdateEntityTx),
+    WasmTx(WasmTx),
+    ValidatorSetTx(ValidatorSetTx),
 }
 
 #[derive(Serialize, Deserialize)]
 pub(crate) enum RequestMsg {
     ReqBlockHash(ReqBlockHash),
+    ReqBlockHeader(ReqBlockHeader),
     ReqBlock(ReqBlock),
     ReqWasmExists(WasmHash),
     ReqWasm(WasmHash),
+    ReqSnapshotManifest,
+    ReqSnapshotChunk(ReqSnapshotChunk),
 }
 
 #[derive(Serialize, Deserialize)]
@@ -20,18 +32,48 @@ pub(crate) struct ReqBlockHash {
     pub count: u64,
 }
 
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ReqBlockHeader {
+    pub begin_height: BlockHeight,
+    pub count: u64,
+}
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct ReqBlock {
     pub begin_height: BlockHeight,
     pub count: u64,
 }
 
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ReqSnapshotChunk {
+    pub height: BlockHeight,
+    pub index: u64,
+}
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct RspBlockHash {
     pub hash_list: Vec<BlockHash>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RspBlockHeader {
+    pub header_list: Vec<BlockHeader>,
+}
+
+// the proof is none for the blocks without one, as the genesis block
 #[derive(Serialize, Deserialize)]
 pub(crate) struct RspBlock {
-    pub block_list: Vec<Block>,
+    pub body_list: Vec<BlockBody>,
+    pub proof_list: Vec<Option<BlockProof>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RspSnapshotManifest {
+    pub manifest: Option<SnapshotManifest>,
+}
+
+// none if the node has replaced the snapshot
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RspSnapshotChunk {
+    pub chunk: Option<Vec<u8>>,
 }
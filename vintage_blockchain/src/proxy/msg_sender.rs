@@ -1,9 +1,10 @@
+use sha2::{Digest, Sha256};
 use tokio::sync::mpsc;
 use vintage_msg::{
-    ActEvent, ActTx, BlockEvent, BlockHeight, MsgToProxy, UpdateEntityEvent, UpdateEntityTx,
-    WasmHash, WasmId,
+    ActEvent, ActTx, BlockEvent, BlockHeader, MsgToProxy, Proto, TxReceipt, UpdateEntityEvent,
+    UpdateEntityTx, WasmHash, WasmId,
 };
-use vintage_utils::{CalcHash, SendMsg, Timestamp};
+use vintage_utils::{Hashed, SendMsg};
 
 #[derive(Clone)]
 pub(crate) struct MsgToProxySender {
@@ -15,26 +16,27 @@ impl MsgToProxySender {
         Self { sender }
     }
 
+    // the acts of the previous block come with the header of the block
     pub fn send_block_event(
         &self,
-        height: BlockHeight,
-        timestamp: Timestamp,
-        total_act_txs: u64,
-        act_txs: Vec<ActTx>,
+        header: &BlockHeader,
+        prev_block_acts: Option<(BlockHeader, Vec<ActTx>)>,
         ue_txs: Vec<UpdateEntityTx>,
         upgrade_wasm_ids: Vec<WasmId>,
     ) -> bool {
         self.sender
             .send_msg(MsgToProxy::BlockEvent(Self::block_event(
-                height,
-                timestamp,
-                total_act_txs,
-                act_txs,
+                header,
+                prev_block_acts,
                 ue_txs,
                 upgrade_wasm_ids,
             )))
     }
 
+    pub fn send_tx_status(&self, proto: Proto, receipt: TxReceipt) -> bool {
+        self.sender.send_msg(MsgToProxy::TxStatus(proto, receipt))
+    }
+
     pub fn send_wasm_binary(&self, wasm_hash: WasmHash, wash_binary: Vec<u8>) -> bool {
         self.sender
             .send_msg(MsgToProxy::WasmBinary(wasm_hash, wash_binary))
@@ -43,22 +45,23 @@ impl MsgToProxySender {
 
 impl MsgToProxySender {
     fn block_event(
-        height: BlockHeight,
-        timestamp: Timestamp,
-        total_act_txs: u64,
-        act_txs: Vec<ActTx>,
+        header: &BlockHeader,
+        prev_block_acts: Option<(BlockHeader, Vec<ActTx>)>,
         ue_txs: Vec<UpdateEntityTx>,
         upgrade_wasm_ids: Vec<WasmId>,
     ) -> BlockEvent {
-        let mut act_number = total_act_txs - act_txs.len() as u64;
         let mut act_events = Vec::new();
-        for act_tx in act_txs {
-            act_number += 1;
-            act_events.push(ActEvent {
-                act_tx,
-                act_number,
-                random: act_number.calc_hash(),
-            })
+        if let Some((prev_header, act_txs)) = prev_block_acts {
+            let mut act_number = prev_header.total_act_txs - act_txs.len() as u64;
+            for act_tx in act_txs {
+                act_number += 1;
+                act_events.push(ActEvent {
+                    act_tx,
+                    timestamp: prev_header.timestamp,
+                    act_number,
+                    random: Self::act_random(&header.randomness, act_number),
+                })
+            }
         }
 
         let mut ue_events = Vec::new();
@@ -72,11 +75,20 @@ impl MsgToProxySender {
         }
 
         BlockEvent {
-            height,
-            timestamp,
+            height: header.height,
+            timestamp: header.timestamp,
             act_events,
             ue_events,
             upgrade_wasm_ids,
         }
     }
+
+    // the randomness of the block after the one including the act, mixed with the act number.
+    // the proposer choosing and ordering the acts does not know it yet
+    fn act_random(randomness: &Hashed, act_number: u64) -> Hashed {
+        let mut hasher = Sha256::new();
+        hasher.update(randomness);
+        hasher.update(act_number.to_be_bytes());
+        hasher.into()
+    }
 }
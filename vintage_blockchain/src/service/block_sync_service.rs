@@ -1,27 +1,44 @@
-use crate::chain::ArcBlockChainCore;
-use crate::network::{BlockChainNetworkClient, ReqBlock, ReqBlockHash};
+use crate::chain::{ArcBlockChainCore, ForkAlert, GENESIS_BLOCK_HEIGHT};
+use crate::db::SnapshotManifest;
+use crate::network::{
+    BlockChainNetworkClient, ReqBlock, ReqBlockHash, ReqBlockHeader, ReqSnapshotChunk,
+};
+use anyhow::anyhow;
 use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
-use vintage_utils::{current_timestamp, SendMsg, Service};
+use tokio::task::JoinHandle;
+use vintage_metrics::{SYNC_IMPORTED_BLOCKS, SYNC_LAG_BLOCKS};
+use vintage_msg::{Block, BlockHash, BlockHeight, BlockProof, NodeId};
+use vintage_utils::{current_timestamp, merkle_root, CalcHash, Hashed, SendMsg, Service};
 
 pub struct BlockSyncService {
     interval: u64,
+    fast_sync: bool,
     client: Arc<BlockChainNetworkClient>,
     block_synced_sender: mpsc::Sender<u64>,
+    // blocks of a range downloaded from one node, adapted to the download latency
+    batch_size: u64,
+    // the nodes found on a conflicting fork, not synced from
+    conflicting_nodes: HashSet<NodeId>,
 }
 
 impl BlockSyncService {
     pub(crate) fn new(
         block_interval: u64,
+        fast_sync: bool,
         client: Arc<BlockChainNetworkClient>,
         block_synced_sender: mpsc::Sender<u64>,
     ) -> Self {
         Self {
             interval: block_interval * 10,
+            fast_sync,
             client,
             block_synced_sender,
+            batch_size: Self::MIN_BATCH_SIZE,
+            conflicting_nodes: HashSet::new(),
         }
     }
 }
@@ -33,23 +50,41 @@ impl Service for BlockSyncService {
 
     async fn service(mut self, blockchain_core: Self::Input) -> Self::Output {
         loop {
-            log::debug!("====Block sync service loop");
+            tracing::debug!("====Block sync service loop");
             tokio::time::sleep(Duration::from_millis(self.interval)).await;
             {
                 let guard = blockchain_core.lock().await;
                 if guard.get_last_commited_time() + Duration::from_millis(self.interval).as_secs()
                     > current_timestamp()
                 {
-                    log::debug!("=block sync check result in skip: get_last_commited_time: {}, interval: {}, current_timestamp: {}", guard.get_last_commited_time(), Duration::from_millis(self.interval).as_secs(), current_timestamp());
+                    tracing::debug!("=block sync check result in skip: get_last_commited_time: {}, interval: {}, current_timestamp: {}", guard.get_last_commited_time(), Duration::from_millis(self.interval).as_secs(), current_timestamp());
                     continue;
                 }
             }
 
+            if self.fast_sync {
+                match self.sync_snapshot(&blockchain_core).await {
+                    Ok(()) => self.fast_sync = false,
+                    Err(err) => {
+                        tracing::warn!("Snapshot sync err: {:?}", err);
+                        continue;
+                    }
+                }
+            }
+
+            // once per sync run, the nodes found on a fork before a rollback are checked again
+            self.conflicting_nodes.clear();
+            if let Err(err) = self.check_forks(&blockchain_core).await {
+                tracing::warn!("Block sync fork check err: {:?}", err);
+                continue;
+            }
+
             loop {
                 match self.sync_blocks(&blockchain_core).await {
                     Ok((finished, new_height)) => {
                         if finished {
-                            log::info!(
+                            SYNC_LAG_BLOCKS.set(0);
+                            tracing::info!(
                                 "====Block sync completed. notify new height: {}",
                                 new_height
                             );
@@ -58,7 +93,9 @@ impl Service for BlockSyncService {
                         }
                     }
                     Err(err) => {
-                        log::warn!("Block sync service err: {:?}", err);
+                        tracing::warn!("Block sync service err: {:?}", err);
+                        // a smaller window is more likely to be voted near the tip
+                        self.batch_size = Self::MIN_BATCH_SIZE;
                         break;
                     }
                 }
@@ -67,56 +104,458 @@ impl Service for BlockSyncService {
     }
 }
 
+// the downloaded blocks of a range, chained to the voted hashes.
+// the hashes only pick the blocks to download, each block is imported on its commit proof
+struct BlockRange {
+    begin_height: BlockHeight,
+    blocks: Vec<(Block, BlockProof)>,
+    latency: Duration,
+}
+
 impl BlockSyncService {
-    const BLOCK_COUNT: u64 = 10;
+    const MIN_BATCH_SIZE: u64 = 10;
+    const MAX_BATCH_SIZE: u64 = 200;
+    // ranges downloaded concurrently, each from a different node if possible
+    const MAX_PARALLEL_RANGES: u64 = 4;
+    const TARGET_LATENCY: Duration = Duration::from_millis(2_000);
+    // the last blocks compared with the nodes before each sync run
+    const FORK_CHECK_BLOCKS: u64 = 10;
 
+    // the hashes of the window are voted, the ranges of the window are downloaded concurrently
+    // and imported in order while the later ones are still downloading
+    #[tracing::instrument(name = "block_sync", skip_all, fields(begin_height))]
     async fn sync_blocks(
         &mut self,
         blockchain_core: &ArcBlockChainCore,
     ) -> anyhow::Result<(bool, u64)> {
-        log::info!("====Block sync start");
-
-        let mut guard = blockchain_core.lock().await;
+        tracing::info!("====Block sync start");
 
         // block height
-        let block_height = guard.get_block_height().await?;
-        log::info!("====Block sync block_height: {}", block_height);
-        // block hash
-        let (node_id, rsp_block_hash) = self
+        let block_height = { blockchain_core.lock().await.get_block_height().await? };
+        tracing::Span::current().record("begin_height", block_height + 1);
+        // block hash, voted by the nodes
+        let window = self.batch_size * Self::MAX_PARALLEL_RANGES;
+        let (node_ids, rsp_block_hash) = self
             .client
             .request_block_hash(ReqBlockHash {
                 begin_height: block_height + 1,
-                count: Self::BLOCK_COUNT,
+                count: window,
             })
             .await?;
-        let block_count = rsp_block_hash.hash_list.len() as u64;
-        log::info!("====Block sync hash block_count: {}", block_count);
-        // block
-        let rsp_block = self
-            .client
-            .request_block(
-                ReqBlock {
-                    begin_height: block_height + 1,
-                    count: block_count,
-                },
-                node_id,
+        let node_ids: Vec<NodeId> = node_ids
+            .into_iter()
+            .filter(|node_id| !self.conflicting_nodes.contains(node_id))
+            .collect();
+        let hash_list = rsp_block_hash.hash_list;
+        let block_count = hash_list.len() as u64;
+        tracing::info!(
+            "====Block sync hash block_count: {}, batch_size: {}, nodes: {}",
+            block_count,
+            self.batch_size,
+            node_ids.len()
+        );
+        SYNC_LAG_BLOCKS.set(block_count as i64);
+
+        // download
+        let mut downloads: Vec<JoinHandle<anyhow::Result<BlockRange>>> = Vec::new();
+        for (index, hashes) in hash_list.chunks(self.batch_size as usize).enumerate() {
+            let begin_height = block_height + 1 + index as u64 * self.batch_size;
+            let prev_hash = match begin_height - block_height {
+                1 => None,
+                offset => Some(hash_list[offset as usize - 2].clone()),
+            };
+            downloads.push(tokio::spawn(download_range(
+                self.client.clone(),
+                rotate(&node_ids, index),
+                begin_height,
+                hashes.to_vec(),
+                prev_hash,
+            )));
+        }
+
+        // import
+        let mut max_latency = Duration::ZERO;
+        for download in downloads {
+            let range = download.await??;
+            max_latency = max_latency.max(range.latency);
+            self.import_range(blockchain_core, range).await?;
+        }
+        self.adapt_batch_size(max_latency);
+
+        tracing::info!("====Block sync imported block_count: {}", block_count);
+        Ok((block_count < window, block_height + block_count))
+    }
+
+    // the nodes are compared with the last blocks of the local chain. the nodes on a conflicting fork
+    // are reported and not synced from, unless they reach the quorum, then the local chain is the fork
+    // and the sync stops until it is rolled back
+    async fn check_forks(&mut self, blockchain_core: &ArcBlockChainCore) -> anyhow::Result<()> {
+        let block_height = { blockchain_core.lock().await.get_block_height().await? };
+        if block_height == GENESIS_BLOCK_HEIGHT {
+            return Ok(());
+        }
+        let begin_height = block_height
+            .saturating_sub(Self::FORK_CHECK_BLOCKS - 1)
+            .max(GENESIS_BLOCK_HEIGHT + 1);
+        let count = block_height + 1 - begin_height;
+        let local_node_id = { blockchain_core.lock().await.node_id().clone() };
+
+        let mut requests = Vec::new();
+        for node_id in self.client.quorum_nodes() {
+            if node_id == local_node_id || self.conflicting_nodes.contains(&node_id) {
+                continue;
+            }
+            let client = self.client.clone();
+            requests.push(tokio::spawn(async move {
+                let rsp = client
+                    .request_block_hash_from(
+                        ReqBlockHash {
+                            begin_height,
+                            count,
+                        },
+                        node_id.clone(),
+                    )
+                    .await;
+                (node_id, rsp)
+            }));
+        }
+        let mut alerts = Vec::new();
+        for request in requests {
+            let (node_id, rsp) = request.await?;
+            match rsp {
+                Ok(rsp) => {
+                    let fork = self
+                        .find_fork(blockchain_core, node_id, begin_height, rsp.hash_list)
+                        .await?;
+                    alerts.extend(fork);
+                }
+                Err(err) => tracing::debug!("fork check with {}, err: {:?}", node_id, err),
+            }
+        }
+
+        // the nodes on the same chain diverge from the local one at the same block
+        let mut forks: HashMap<(BlockHeight, BlockHash), Vec<NodeId>> = HashMap::new();
+        for alert in &alerts {
+            match alert {
+                ForkAlert::DivergentChain {
+                    node_id,
+                    height,
+                    remote_hash,
+                    ..
+                }
+                | ForkAlert::ConflictingCommit {
+                    node_id,
+                    height,
+                    remote_hash,
+                    ..
+                } => forks
+                    .entry((*height, remote_hash.clone()))
+                    .or_default()
+                    .push(node_id.clone()),
+                ForkAlert::LocalFork { .. } => {}
+            }
+        }
+        if let Some(((height, _), node_ids)) = forks
+            .iter()
+            .find(|(_, node_ids)| self.client.is_quorum_reached(node_ids))
+        {
+            let height = *height;
+            ForkAlert::LocalFork {
+                height,
+                node_ids: node_ids.clone(),
+            }
+            .report();
+            return Err(anyhow!(
+                "local chain diverges from the quorum at height {}, roll back {} blocks to resync",
+                height,
+                block_height + 1 - height
+            ));
+        }
+        for alert in alerts {
+            alert.report();
+        }
+        self.conflicting_nodes.extend(forks.into_values().flatten());
+        Ok(())
+    }
+
+    // the first height where the hash of the node differs from the local one
+    async fn find_fork(
+        &self,
+        blockchain_core: &ArcBlockChainCore,
+        node_id: NodeId,
+        begin_height: BlockHeight,
+        hash_list: Vec<BlockHash>,
+    ) -> anyhow::Result<Option<ForkAlert>> {
+        for (height, remote_hash) in (begin_height..).zip(hash_list) {
+            let local_hash = { blockchain_core.lock().await.get_block_hash(height).await };
+            match local_hash {
+                // before the snapshot the node is restored from
+                Err(_) => continue,
+                Ok(local_hash) if local_hash == remote_hash => continue,
+                Ok(_) => {}
+            }
+            let remote_proof = match self
+                .client
+                .request_block(
+                    ReqBlock {
+                        begin_height: height,
+                        count: 1,
+                    },
+                    node_id.clone(),
+                )
+                .await
+            {
+                Ok(rsp) => rsp.proof_list.into_iter().next().flatten(),
+                Err(_) => None,
+            };
+            let alert = blockchain_core
+                .lock()
+                .await
+                .check_fork(node_id, height, remote_hash, remote_proof)
+                .await?;
+            return Ok(Some(alert));
+        }
+        Ok(None)
+    }
+
+    // only an empty node starts from the snapshot, the blocks after it are synced as usual
+    #[tracing::instrument(name = "snapshot_sync", skip_all)]
+    async fn sync_snapshot(&self, blockchain_core: &ArcBlockChainCore) -> anyhow::Result<()> {
+        let block_height = { blockchain_core.lock().await.get_block_height().await? };
+        if block_height != GENESIS_BLOCK_HEIGHT {
+            return Ok(());
+        }
+        // manifest, voted by the nodes
+        let (node_ids, rsp_snapshot_manifest) = self.client.request_snapshot_manifest().await?;
+        let manifest = match rsp_snapshot_manifest.manifest {
+            Some(manifest) => manifest,
+            None => {
+                tracing::info!("====Snapshot sync skipped, no snapshot on the nodes");
+                return Ok(());
+            }
+        };
+        tracing::info!(
+            "====Snapshot sync start, height: {}, chunks: {}",
+            manifest.height,
+            manifest.chunk_hashes.len()
+        );
+        // proof of the snapshot block, verified on restore
+        let proof = download_proof(&self.client, &node_ids, &manifest).await?;
+        // chunks, the hashes are in the manifest
+        let mut chunks = Vec::with_capacity(manifest.chunk_hashes.len());
+        for (index, hash) in manifest.chunk_hashes.iter().enumerate() {
+            let chunk = download_chunk(
+                &self.client,
+                rotate(&node_ids, index),
+                manifest.height,
+                index as u64,
+                hash,
             )
             .await?;
-        if rsp_block.block_list.len() != block_count as usize {
-            return Err(anyhow::anyhow!("Block count mismatch"));
-        }
-        log::info!("====Block sync start import block count: {}", block_count);
-        // import block
-        for index in 0..block_count {
-            guard
-                .import_block(
-                    block_height + index + 1,
-                    rsp_block.block_list[index as usize].clone(),
-                    rsp_block_hash.hash_list[index as usize].clone(),
-                )
-                .await?
+            chunks.push(chunk);
+        }
+        blockchain_core
+            .lock()
+            .await
+            .restore_snapshot(manifest, chunks, proof)
+            .await
+    }
+
+    // the lock is taken for each block, so the consensus is not stalled by a long sync.
+    // the proofs are verified against the validator set of each height on import
+    async fn import_range(
+        &self,
+        blockchain_core: &ArcBlockChainCore,
+        range: BlockRange,
+    ) -> anyhow::Result<()> {
+        for (height, (block, proof)) in (range.begin_height..).zip(range.blocks) {
+            let mut guard = blockchain_core.lock().await;
+            if height <= guard.get_block_height().await? {
+                // committed by the consensus in the meantime
+                continue;
+            }
+            guard.import_block(height, block, proof).await?;
+            SYNC_IMPORTED_BLOCKS.inc();
+        }
+        Ok(())
+    }
+
+    fn adapt_batch_size(&mut self, latency: Duration) {
+        if latency < Self::TARGET_LATENCY / 2 {
+            self.batch_size = (self.batch_size * 2).min(Self::MAX_BATCH_SIZE);
+        } else if latency > Self::TARGET_LATENCY {
+            self.batch_size = (self.batch_size / 2).max(Self::MIN_BATCH_SIZE);
+        }
+    }
+}
+
+// the nodes in the order to try, a different node first for each range
+fn rotate(node_ids: &[NodeId], index: usize) -> Vec<NodeId> {
+    let mut node_ids = node_ids.to_vec();
+    if !node_ids.is_empty() {
+        let len = node_ids.len();
+        node_ids.rotate_left(index % len);
+    }
+    node_ids
+}
+
+async fn download_proof(
+    client: &BlockChainNetworkClient,
+    node_ids: &[NodeId],
+    manifest: &SnapshotManifest,
+) -> anyhow::Result<BlockProof> {
+    for node_id in node_ids {
+        let req = ReqBlock {
+            begin_height: manifest.height,
+            count: 1,
+        };
+        match client.request_block(req, node_id.clone()).await {
+            Ok(rsp) => match rsp.proof_list.into_iter().next().flatten() {
+                Some(proof) if proof.block_hash == manifest.block_hash => return Ok(proof),
+                Some(_) => tracing::warn!("snapshot proof from {} hash mismatch", node_id),
+                None => tracing::warn!("snapshot proof not on {}", node_id),
+            },
+            Err(err) => {
+                tracing::warn!("download snapshot proof from {}, err: {:?}", node_id, err);
+            }
+        }
+    }
+    Err(anyhow!("snapshot {} proof not downloaded", manifest.height))
+}
+
+async fn download_chunk(
+    client: &BlockChainNetworkClient,
+    node_ids: Vec<NodeId>,
+    height: BlockHeight,
+    index: u64,
+    hash: &Hashed,
+) -> anyhow::Result<Vec<u8>> {
+    for node_id in node_ids {
+        match client
+            .request_snapshot_chunk(ReqSnapshotChunk { height, index }, node_id.clone())
+            .await
+        {
+            Ok(rsp) => match rsp.chunk {
+                Some(chunk) if chunk.calc_hash() == *hash => return Ok(chunk),
+                Some(_) => {
+                    tracing::warn!("snapshot chunk {} from {} hash mismatch", index, node_id)
+                }
+                None => tracing::warn!("snapshot {} replaced on {}", height, node_id),
+            },
+            Err(err) => {
+                tracing::warn!(
+                    "download snapshot chunk {} from {}, err: {:?}",
+                    index,
+                    node_id,
+                    err
+                );
+            }
+        }
+    }
+    Err(anyhow!(
+        "snapshot {} chunk {} not downloaded",
+        height,
+        index
+    ))
+}
+
+async fn download_range(
+    client: Arc<BlockChainNetworkClient>,
+    node_ids: Vec<NodeId>,
+    begin_height: BlockHeight,
+    hashes: Vec<BlockHash>,
+    prev_hash: Option<BlockHash>,
+) -> anyhow::Result<BlockRange> {
+    let mut last_err = anyhow!("no node to download blocks from");
+    for node_id in node_ids {
+        let start = Instant::now();
+        match download_range_from(&client, &node_id, begin_height, &hashes, &prev_hash).await {
+            Ok(blocks) => {
+                return Ok(BlockRange {
+                    begin_height,
+                    blocks,
+                    latency: start.elapsed(),
+                });
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "download blocks from {}, begin height {}, err: {:?}",
+                    node_id,
+                    begin_height,
+                    err
+                );
+                last_err = err;
+            }
+        }
+    }
+    Err(last_err)
+}
+
+async fn download_range_from(
+    client: &BlockChainNetworkClient,
+    node_id: &NodeId,
+    begin_height: BlockHeight,
+    hashes: &[BlockHash],
+    prev_hash: &Option<BlockHash>,
+) -> anyhow::Result<Vec<(Block, BlockProof)>> {
+    let count = hashes.len() as u64;
+    // block header, the hash is voted by the nodes
+    let rsp_block_header = client
+        .request_block_header(
+            ReqBlockHeader {
+                begin_height,
+                count,
+            },
+            node_id.clone(),
+        )
+        .await?;
+    if rsp_block_header.header_list.len() != hashes.len() {
+        return Err(anyhow!("Block header count mismatch"));
+    }
+    let mut prev_hash = prev_hash.as_ref();
+    for (header, hash) in rsp_block_header.header_list.iter().zip(hashes) {
+        if header.calc_hash() != *hash {
+            return Err(anyhow!("Block header {} hash mismatch", header.height));
+        }
+        if prev_hash.is_some_and(|prev_hash| header.prev_hash != *prev_hash) {
+            return Err(anyhow!("Block header {} prev hash mismatch", header.height));
+        }
+        prev_hash = Some(hash);
+    }
+    // block body and proof, the tx root is in the header
+    let rsp_block = client
+        .request_block(
+            ReqBlock {
+                begin_height,
+                count,
+            },
+            node_id.clone(),
+        )
+        .await?;
+    if rsp_block.body_list.len() != hashes.len() || rsp_block.proof_list.len() != hashes.len() {
+        return Err(anyhow!("Block count mismatch"));
+    }
+    for (header, body) in rsp_block_header
+        .header_list
+        .iter()
+        .zip(&rsp_block.body_list)
+    {
+        if merkle_root(&body.tx_items()) != header.tx_root {
+            return Err(anyhow!("Block body {} tx root mismatch", header.height));
+        }
+    }
+    let mut proof_list = Vec::with_capacity(hashes.len());
+    for (proof, hash) in rsp_block.proof_list.into_iter().zip(hashes) {
+        match proof {
+            Some(proof) if proof.block_hash == *hash => proof_list.push(proof),
+            _ => return Err(anyhow!("Block {} proof missing or mismatch", hash)),
         }
-        log::info!("====Block sync imported block_count: {}", block_count);
-        Ok((block_count < Self::BLOCK_COUNT, block_height + block_count))
     }
+    Ok(rsp_block_header
+        .header_list
+        .into_iter()
+        .zip(rsp_block.body_list)
+        .map(|(header, body)| Block { header, body })
+        .zip(proof_list)
+        .collect())
 }
@@ -1,17 +1,25 @@
 use crate::db::BlockChainDb;
-use crate::network::{BroadcastMsg, MsgToNetworkSender, ReqBlock, ReqBlockHash, RequestMsg};
+use crate::network::{
+    BlockChainNetworkClient, BroadcastMsg, MsgToNetworkSender, ReqBlock, ReqBlockHash,
+    ReqBlockHeader, ReqSnapshotChunk, RequestMsg, RspBlock, RspSnapshotChunk, RspSnapshotManifest,
+};
 use crate::proxy::MsgToProxySender;
-use crate::tx::{TxId, TxPool};
+use crate::tx::{check_validator_set_tx, put_wasm_tx_to_pool, TxId, TxPool, TxStatusReporter};
+use crate::wasm::FetchWasmTxTask;
 use crate::wasm_db::WasmDb;
 use anyhow::anyhow;
 use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::mpsc;
+use vintage_metrics::POOL_TXS;
 use vintage_msg::{
-    ActTx, Block, BlockHash, MsgToBlockChain, NetworkRequestId, NodeId, UpdateEntityTx, UploadWasm,
-    WasmHash, WasmId, WasmInfo,
+    ActTx, BlockBody, BlockHash, BlockHeader, BlockProof, MsgToBlockChain, NetworkRequestId,
+    NodeId, TxStatus, UpdateEntityTx, UploadWasm, ValidatorNode, ValidatorSetTx, WasmHash, WasmId,
+    WasmInfo, WasmTx,
+};
+use vintage_utils::{
+    current_timestamp, BincodeDeserialize, BlsPublicKey, CalcHash, Service, ServiceStarter,
 };
-use vintage_utils::{BincodeDeserialize, CalcHash, Service};
 
 pub struct BlockChainService {
     blockchain_db: BlockChainDb,
@@ -20,9 +28,13 @@ pub struct BlockChainService {
     msg_receiver: mpsc::Receiver<MsgToBlockChain>,
     proxy_msg_sender: MsgToProxySender,
     network_msg_sender: MsgToNetworkSender,
+    client: Arc<BlockChainNetworkClient>,
+    tx_status_reporter: TxStatusReporter,
+    genesis_validators: Vec<ValidatorNode>,
 }
 
 impl BlockChainService {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         blockchain_db: BlockChainDb,
         wasm_db: WasmDb,
@@ -30,14 +42,22 @@ impl BlockChainService {
         msg_receiver: mpsc::Receiver<MsgToBlockChain>,
         proxy_msg_sender: MsgToProxySender,
         network_msg_sender: MsgToNetworkSender,
+        client: Arc<BlockChainNetworkClient>,
+        genesis_validators: Vec<ValidatorNode>,
     ) -> Self {
         Self {
+            tx_status_reporter: TxStatusReporter::new(
+                blockchain_db.clone(),
+                proxy_msg_sender.clone(),
+            ),
             blockchain_db,
             wasm_db,
             tx_pool,
             msg_receiver,
             proxy_msg_sender,
             network_msg_sender,
+            client,
+            genesis_validators,
         }
     }
 }
@@ -53,7 +73,7 @@ impl Service for BlockChainService {
                 Some(msg) => match msg {
                     MsgToBlockChain::Broadcast(node_id, msg_encoded) => {
                         if let Err(err) = self.broadcast_handler(node_id, msg_encoded).await {
-                            log::error!("Failed to handle Broadcast: {:?}", err);
+                            tracing::error!("Failed to handle Broadcast: {:?}", err);
                         }
                     }
                     MsgToBlockChain::Request(node_id, request_id, request_encoded) => {
@@ -61,22 +81,27 @@ impl Service for BlockChainService {
                             .request_handler(node_id, request_id, request_encoded)
                             .await
                         {
-                            log::error!("Failed to handle Request: {:?}", err);
+                            tracing::error!("Failed to handle Request: {:?}", err);
                         }
                     }
                     MsgToBlockChain::ActTx(act_tx) => {
                         if let Err(err) = self.act_handler(act_tx).await {
-                            log::error!("Failed to handle ActTx: {:?}", err);
+                            tracing::error!("Failed to handle ActTx: {:?}", err);
                         }
                     }
                     MsgToBlockChain::UpdateEntityTx(tx) => {
                         if let Err(err) = self.ue_tx_handler(tx).await {
-                            log::error!("Failed to handle UpdateEntityTx: {:?}", err);
+                            tracing::error!("Failed to handle UpdateEntityTx: {:?}", err);
                         }
                     }
                     MsgToBlockChain::UploadWasm(upload_wasm) => {
                         if let Err(err) = self.upload_wasm_handler(upload_wasm).await {
-                            log::error!("Failed to handle UploadWasm: {:?}", err);
+                            tracing::error!("Failed to handle UploadWasm: {:?}", err);
+                        }
+                    }
+                    MsgToBlockChain::ValidatorSetTx(validator_set_tx) => {
+                        if let Err(err) = self.validator_set_tx_handler(validator_set_tx).await {
+                            tracing::error!("Failed to handle ValidatorSetTx: {:?}", err);
                         }
                     }
                 },
@@ -90,21 +115,80 @@ impl Service for BlockChainService {
 
 // network
 impl BlockChainService {
-    async fn broadcast_handler(
-        &self,
-        _node_id: NodeId,
-        msg_encoded: Vec<u8>,
-    ) -> anyhow::Result<()> {
+    async fn broadcast_handler(&self, node_id: NodeId, msg_encoded: Vec<u8>) -> anyhow::Result<()> {
         let (msg, _bytes_read) = BroadcastMsg::bincode_deserialize(&msg_encoded)?;
         match msg {
             BroadcastMsg::ActTx(act_tx) => {
-                let act_tx_id = self.put_act_tx_to_pool(act_tx).await?;
-                log::debug!("act tx from network: {}", act_tx_id);
+                let act_tx_id = act_tx.calc_hash();
+                let proto = act_tx.proto.clone();
+                let evicted = self.put_act_tx_to_pool(act_tx_id.clone(), act_tx).await?;
+                tracing::debug!("act tx from network: {}", act_tx_id);
+                self.tx_status_reporter
+                    .record(&[(proto, act_tx_id)], &TxStatus::Pending, false)
+                    .await;
+                self.report_evicted_act_txs(evicted).await;
+                Ok(())
+            }
+            BroadcastMsg::UpdateEntityTx(tx) => {
+                let tx_id = tx.calc_hash();
+                let proto = tx.proto.clone();
+                if self.put_ue_tx_to_pool(tx_id.clone(), tx).await? {
+                    tracing::debug!("update entity tx from network: {}", tx_id);
+                    self.tx_status_reporter
+                        .record(&[(proto, tx_id)], &TxStatus::Pending, false)
+                        .await;
+                }
+                Ok(())
+            }
+            BroadcastMsg::WasmTx(wasm_tx) => {
+                {
+                    if self.tx_pool.wasm_txs_guard().contains_key(&wasm_tx.wasm_id) {
+                        return Ok(());
+                    }
+                }
+                self.wasm_tx_from_network(node_id, wasm_tx).await
+            }
+            BroadcastMsg::ValidatorSetTx(validator_set_tx) => {
+                let tx_id = self.put_validator_set_tx_to_pool(validator_set_tx).await?;
+                tracing::info!("validator set tx from network: {}", tx_id);
                 Ok(())
             }
         }
     }
 
+    // only the validators schedule wasm upgrades, the binary must be known or fetched from the sender
+    async fn wasm_tx_from_network(&self, node_id: NodeId, wasm_tx: WasmTx) -> anyhow::Result<()> {
+        let validators = self.next_validators().await?;
+        if !is_validator(&validators, &node_id)? {
+            return Err(anyhow!("wasm tx from node {}, not a validator", node_id));
+        }
+        let WasmTx { wasm_id, wasm_info } = wasm_tx;
+        tracing::info!(
+            "wasm tx from network, proto: {}, hash: {}",
+            wasm_id.proto,
+            wasm_id.wasm_hash
+        );
+        if self
+            .wasm_db
+            .wasm_binary_exists(wasm_id.wasm_hash.clone())
+            .await?
+        {
+            put_wasm_tx_to_pool(&self.blockchain_db, &self.tx_pool, wasm_id, wasm_info).await
+        } else {
+            ServiceStarter::new(FetchWasmTxTask::new(
+                self.blockchain_db.clone(),
+                self.wasm_db.clone(),
+                self.tx_pool.clone(),
+                self.proxy_msg_sender.clone(),
+                self.client.clone(),
+                node_id,
+                WasmTx { wasm_id, wasm_info },
+            ))
+            .start();
+            Ok(())
+        }
+    }
+
     async fn request_handler(
         &self,
         node_id: NodeId,
@@ -117,6 +201,10 @@ impl BlockChainService {
                 self.request_block_hash_handler(node_id, request_id, req)
                     .await
             }
+            RequestMsg::ReqBlockHeader(req) => {
+                self.request_block_header_handler(node_id, request_id, req)
+                    .await
+            }
             RequestMsg::ReqBlock(req) => self.request_block_handler(node_id, request_id, req).await,
             RequestMsg::ReqWasmExists(wasm_hash) => {
                 self.request_wasm_exists_handler(node_id, request_id, wasm_hash)
@@ -126,6 +214,14 @@ impl BlockChainService {
                 self.request_wasm_handler(node_id, request_id, wasm_hash)
                     .await
             }
+            RequestMsg::ReqSnapshotManifest => {
+                self.request_snapshot_manifest_handler(node_id, request_id)
+                    .await
+            }
+            RequestMsg::ReqSnapshotChunk(req) => {
+                self.request_snapshot_chunk_handler(node_id, request_id, req)
+                    .await
+            }
         }
     }
 
@@ -135,7 +231,7 @@ impl BlockChainService {
         request_id: NetworkRequestId,
         req: ReqBlockHash,
     ) -> anyhow::Result<()> {
-        log::debug!("request_block_hash_handler from node: {}", node_id);
+        tracing::debug!("request_block_hash_handler from node: {}", node_id);
         let mut hash_list: Vec<BlockHash> = Vec::new();
         for index in 0..req.count {
             match self.blockchain_db.get_block(req.begin_height + index).await {
@@ -143,7 +239,7 @@ impl BlockChainService {
                     hash_list.push(block.hash);
                 }
                 Err(e) => {
-                    log::info!(
+                    tracing::info!(
                         "Failed to get block at height {}: error:{:?}, break",
                         req.begin_height + index,
                         e
@@ -157,23 +253,78 @@ impl BlockChainService {
         Ok(())
     }
 
-    async fn request_block_handler(
+    async fn request_block_header_handler(
         &self,
         node_id: NodeId,
         request_id: NetworkRequestId,
-        req: ReqBlock,
+        req: ReqBlockHeader,
     ) -> anyhow::Result<()> {
-        log::info!("request_block_handler from node: {}", node_id);
-        let mut block_list: Vec<Block> = Vec::new();
+        tracing::info!("request_block_header_handler from node: {}", node_id);
+        // the headers up to the first missing one, the requester checks the count
+        let mut header_list: Vec<BlockHeader> = Vec::new();
         for index in 0..req.count {
-            let block = self
+            match self
                 .blockchain_db
-                .get_network_block(req.begin_height + index)
-                .await?;
-            block_list.push(block);
+                .get_block_header(req.begin_height + index)
+                .await
+            {
+                Ok(header) => header_list.push(header),
+                Err(err) => {
+                    tracing::info!(
+                        "Failed to get block header at height {}: error:{:?}, break",
+                        req.begin_height + index,
+                        err
+                    );
+                    break;
+                }
+            }
         }
         self.network_msg_sender
-            .send_response(node_id, request_id, block_list);
+            .send_response(node_id, request_id, header_list);
+        Ok(())
+    }
+
+    async fn request_block_handler(
+        &self,
+        node_id: NodeId,
+        request_id: NetworkRequestId,
+        req: ReqBlock,
+    ) -> anyhow::Result<()> {
+        tracing::info!("request_block_handler from node: {}", node_id);
+        let mut body_list: Vec<BlockBody> = Vec::new();
+        let mut proof_list: Vec<Option<BlockProof>> = Vec::new();
+        // the blocks up to the first missing one, the requester checks the count
+        for index in 0..req.count {
+            let height = req.begin_height + index;
+            let result = async {
+                let body = self.blockchain_db.get_block_body(height).await?;
+                let proof = self.blockchain_db.get_block_proof(height).await?;
+                anyhow::Ok((body, proof))
+            }
+            .await;
+            match result {
+                Ok((body, proof)) => {
+                    body_list.push(body);
+                    proof_list.push(proof);
+                }
+                Err(err) => {
+                    tracing::info!(
+                        "Failed to get block at height {}: error:{:?}, break",
+                        height,
+                        err
+                    );
+                    break;
+                }
+            }
+        }
+        self.network_msg_sender.send_response(
+            node_id,
+            request_id,
+            RspBlock {
+                body_list,
+                proof_list,
+            },
+        );
         Ok(())
     }
 
@@ -200,39 +351,151 @@ impl BlockChainService {
             .send_response(node_id, request_id, wasm_binary);
         Ok(())
     }
+
+    async fn request_snapshot_manifest_handler(
+        &self,
+        node_id: NodeId,
+        request_id: NetworkRequestId,
+    ) -> anyhow::Result<()> {
+        tracing::info!("request_snapshot_manifest_handler from node: {}", node_id);
+        let manifest = self.blockchain_db.get_snapshot_manifest().await?;
+        self.network_msg_sender.send_response(
+            node_id,
+            request_id,
+            RspSnapshotManifest { manifest },
+        );
+        Ok(())
+    }
+
+    async fn request_snapshot_chunk_handler(
+        &self,
+        node_id: NodeId,
+        request_id: NetworkRequestId,
+        req: ReqSnapshotChunk,
+    ) -> anyhow::Result<()> {
+        tracing::debug!(
+            "request_snapshot_chunk_handler from node: {}, height: {}, index: {}",
+            node_id,
+            req.height,
+            req.index
+        );
+        let chunk = self
+            .blockchain_db
+            .get_snapshot_chunk(req.height, req.index)
+            .await?;
+        self.network_msg_sender
+            .send_response(node_id, request_id, RspSnapshotChunk { chunk });
+        Ok(())
+    }
 }
 
 // worker
 impl BlockChainService {
+    // the workers of the other nodes may have sent the same tx, it is gossiped only once
     async fn ue_tx_handler(&self, tx: UpdateEntityTx) -> anyhow::Result<()> {
         let tx_id = tx.calc_hash();
-        self.blockchain_db.insert_ue_tx_to_pool(tx_id, tx).await
+        let txs = vec![(tx.proto.clone(), tx_id.clone())];
+        match self.put_ue_tx_to_pool(tx_id.clone(), tx.clone()).await {
+            Ok(added) => {
+                self.tx_status_reporter
+                    .report(txs, TxStatus::Pending, false)
+                    .await;
+                if added {
+                    tracing::debug!("update entity tx from worker: {}", tx_id);
+                    self.network_msg_sender
+                        .send_broadcast(&BroadcastMsg::UpdateEntityTx(tx));
+                }
+                Ok(())
+            }
+            Err(err) => {
+                self.tx_status_reporter
+                    .report(txs, TxStatus::Rejected(err.to_string()), false)
+                    .await;
+                Err(err)
+            }
+        }
+    }
+
+    // returns false if the tx is already in pool
+    async fn put_ue_tx_to_pool(&self, tx_id: TxId, tx: UpdateEntityTx) -> anyhow::Result<bool> {
+        if self
+            .blockchain_db
+            .ue_tx_exists_in_pool(tx_id.clone())
+            .await?
+        {
+            return Ok(false);
+        }
+        self.blockchain_db
+            .insert_ue_tx_to_pool(tx_id.clone(), tx)
+            .await?;
+        {
+            self.tx_pool
+                .ue_arrival_times_guard()
+                .insert(tx_id, current_timestamp());
+        }
+        POOL_TXS.with_label_values(&["ue"]).inc();
+        Ok(true)
     }
 
     async fn act_handler(&self, act_tx: ActTx) -> anyhow::Result<()> {
-        let act_tx_id = self.put_act_tx_to_pool(act_tx.clone()).await?;
-        log::debug!("act tx from proxy: {}", act_tx_id);
+        let act_tx_id = act_tx.calc_hash();
+        let txs = vec![(act_tx.proto.clone(), act_tx_id.clone())];
+        let evicted = match self
+            .put_act_tx_to_pool(act_tx_id.clone(), act_tx.clone())
+            .await
+        {
+            Ok(evicted) => evicted,
+            Err(err) => {
+                self.tx_status_reporter
+                    .report(txs, TxStatus::Rejected(err.to_string()), false)
+                    .await;
+                return Err(err);
+            }
+        };
+        tracing::debug!("act tx from proxy: {}", act_tx_id);
+        self.tx_status_reporter
+            .report(txs, TxStatus::Pending, false)
+            .await;
+        self.report_evicted_act_txs(evicted).await;
         self.network_msg_sender
             .send_broadcast(&BroadcastMsg::ActTx(act_tx));
         Ok(())
     }
 
-    async fn put_act_tx_to_pool(&self, act_tx: ActTx) -> anyhow::Result<TxId> {
-        let act_tx_id = act_tx.calc_hash();
+    // returns the txs evicted to make room for it
+    async fn put_act_tx_to_pool(
+        &self,
+        act_tx_id: TxId,
+        act_tx: ActTx,
+    ) -> anyhow::Result<Vec<(TxId, ActTx)>> {
         {
-            if self.tx_pool.act_txs_guard().contains_key(&act_tx_id) {
+            if self.tx_pool.act_txs_guard().contains(&act_tx_id) {
                 return Err(anyhow!("act tx already exists in pool"));
             }
         }
         self.blockchain_db
             .check_act_not_exists(act_tx_id.clone())
             .await?;
-        {
-            self.tx_pool
-                .act_txs_guard()
-                .insert(act_tx_id.clone(), act_tx);
-        }
-        Ok(act_tx_id)
+        let evicted = {
+            let mut pool = self.tx_pool.act_txs_guard();
+            let evicted = pool.insert(act_tx_id, act_tx, current_timestamp())?;
+            POOL_TXS.with_label_values(&["act"]).set(pool.len() as i64);
+            evicted
+        };
+        Ok(evicted)
+    }
+
+    async fn report_evicted_act_txs(&self, evicted: Vec<(TxId, ActTx)>) {
+        self.tx_status_reporter
+            .report(
+                evicted
+                    .into_iter()
+                    .map(|(tx_id, act_tx)| (act_tx.proto, tx_id))
+                    .collect(),
+                TxStatus::Rejected("evicted from pool".to_string()),
+                true,
+            )
+            .await;
     }
 }
 
@@ -251,7 +514,7 @@ impl BlockChainService {
             .try_insert_wasm_binary(wasm_hash.clone(), wasm_binary.clone())
             .await?
         {
-            log::info!(
+            tracing::info!(
                 "wasm file from admin, proto: {}, hash: {}, size: {}B, saved in db",
                 proto,
                 wasm_hash,
@@ -260,7 +523,7 @@ impl BlockChainService {
             self.proxy_msg_sender
                 .send_wasm_binary(wasm_hash.clone(), wasm_binary);
         } else {
-            log::info!(
+            tracing::info!(
                 "wasm file from admin, proto: {}, hash: {}, size: {}B, already exists in db",
                 proto,
                 wasm_hash,
@@ -268,28 +531,70 @@ impl BlockChainService {
             );
         }
 
-        self.put_wasm_tx_to_pool(WasmId { proto, wasm_hash }, WasmInfo { block_interval })
-            .await?;
+        let wasm_tx = WasmTx {
+            wasm_id: WasmId { proto, wasm_hash },
+            wasm_info: WasmInfo { block_interval },
+        };
+        put_wasm_tx_to_pool(
+            &self.blockchain_db,
+            &self.tx_pool,
+            wasm_tx.wasm_id.clone(),
+            wasm_tx.wasm_info.clone(),
+        )
+        .await?;
+        self.network_msg_sender
+            .send_broadcast(&BroadcastMsg::WasmTx(wasm_tx));
 
         Ok(())
     }
 
-    async fn put_wasm_tx_to_pool(&self, key: WasmId, info: WasmInfo) -> anyhow::Result<()> {
-        {
-            if self.tx_pool.wasm_txs_guard().contains_key(&key) {
-                return Err(anyhow!(
-                    "wasm tx {} {} already exists in pool",
-                    key.proto,
-                    key.wasm_hash
-                ));
-            }
-        }
-        self.blockchain_db
-            .check_wasm_tx_not_exists(key.clone())
+    async fn validator_set_tx_handler(
+        &self,
+        validator_set_tx: ValidatorSetTx,
+    ) -> anyhow::Result<()> {
+        let tx_id = self
+            .put_validator_set_tx_to_pool(validator_set_tx.clone())
             .await?;
+        tracing::info!("validator set tx from admin: {}", tx_id);
+        self.network_msg_sender
+            .send_broadcast(&BroadcastMsg::ValidatorSetTx(validator_set_tx));
+        Ok(())
+    }
+
+    // a new validator set tx replaces the pending one, it must be approved by the validators
+    // of the next block
+    async fn put_validator_set_tx_to_pool(
+        &self,
+        validator_set_tx: ValidatorSetTx,
+    ) -> anyhow::Result<TxId> {
+        let height = self.blockchain_db.get_block_height().await? + 1;
+        let validators = self.next_validators().await?;
+        let last_validator_set = self.blockchain_db.get_last_validator_set_tx().await?;
+        check_validator_set_tx(
+            &validator_set_tx,
+            height,
+            &validators,
+            last_validator_set.as_ref(),
+        )?;
+        let tx_id = validator_set_tx.calc_hash();
         {
-            self.tx_pool.wasm_txs_guard().insert(key, info);
+            *self.tx_pool.validator_set_tx_guard() = Some(validator_set_tx);
+        }
+        Ok(tx_id)
+    }
+
+    async fn next_validators(&self) -> anyhow::Result<Vec<ValidatorNode>> {
+        let height = self.blockchain_db.get_block_height().await? + 1;
+        let validators = self.blockchain_db.get_validator_set(height).await?;
+        Ok(validators.unwrap_or_else(|| self.genesis_validators.clone()))
+    }
+}
+
+fn is_validator(validators: &[ValidatorNode], node_id: &NodeId) -> anyhow::Result<bool> {
+    for validator in validators {
+        if BlsPublicKey::from_hex(&validator.public_key)?.calc_hash() == *node_id {
+            return Ok(true);
         }
-        Ok(())
     }
+    Ok(false)
 }
@@ -1,13 +1,447 @@
 use crate::tx::TxId;
-use std::collections::HashMap;
-use vintage_msg::ActTx;
+use crate::ActPoolConfig;
+use anyhow::anyhow;
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use vintage_msg::{ActTx, Proto};
+use vintage_utils::Timestamp;
 
-pub fn get_act_txs_from_pool(pool: &HashMap<TxId, ActTx>, count: usize) -> (Vec<TxId>, Vec<ActTx>) {
-    let mut act_tx_ids = Vec::new();
-    let mut act_txs = Vec::new();
-    for (hash, act_tx) in pool.iter().take(count) {
-        act_tx_ids.push(hash.clone());
-        act_txs.push(act_tx.clone());
+struct PooledActTx {
+    act_tx: ActTx,
+    size: usize,
+    arrival_time: Timestamp,
+}
+
+// The act txs in arrival order, bounded by count, bytes and count per proto.
+// When the pool is full, the oldest txs of the proto holding the most txs are evicted,
+// a tx is rejected if it would have to evict txs of a proto not larger than its own.
+pub(crate) struct ActTxPool {
+    config: ActPoolConfig,
+    round_robin: bool,
+    txs: IndexMap<TxId, PooledActTx>,
+    total_size: usize,
+    proto_counts: HashMap<Proto, usize>,
+}
+
+impl ActTxPool {
+    pub fn new(config: ActPoolConfig, round_robin: bool) -> Self {
+        Self {
+            txs: IndexMap::with_capacity(config.max_count),
+            config,
+            round_robin,
+            total_size: 0,
+            proto_counts: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.txs.len()
+    }
+
+    pub fn contains(&self, tx_id: &TxId) -> bool {
+        self.txs.contains_key(tx_id)
+    }
+
+    // returns the evicted txs
+    pub fn insert(
+        &mut self,
+        tx_id: TxId,
+        act_tx: ActTx,
+        now: Timestamp,
+    ) -> anyhow::Result<Vec<(TxId, ActTx)>> {
+        if self.txs.contains_key(&tx_id) {
+            return Err(anyhow!("act tx already exists in pool"));
+        }
+        let size = act_tx_size(&act_tx);
+        if size > self.config.max_bytes {
+            return Err(anyhow!("act tx size {}B exceeds the pool budget", size));
+        }
+        let proto_count = self.proto_count(&act_tx.proto);
+        if proto_count >= self.config.max_count_per_proto {
+            return Err(anyhow!("act tx quota of proto {} reached", act_tx.proto));
+        }
+
+        let evicted_ids = self.plan_eviction(&act_tx.proto, size)?;
+        let mut evicted = Vec::with_capacity(evicted_ids.len());
+        for evicted_id in evicted_ids {
+            if let Some(pooled) = self.remove(&evicted_id) {
+                evicted.push((evicted_id, pooled.act_tx));
+            }
+        }
+
+        self.total_size += size;
+        *self.proto_counts.entry(act_tx.proto.clone()).or_default() += 1;
+        self.txs.insert(
+            tx_id,
+            PooledActTx {
+                act_tx,
+                size,
+                arrival_time: now,
+            },
+        );
+        Ok(evicted)
+    }
+
+    // in arrival order, or one tx of each proto in turn if round robin
+    pub fn select(&self, count: usize) -> (Vec<TxId>, Vec<ActTx>) {
+        let selected = if self.round_robin {
+            self.select_round_robin(count)
+        } else {
+            self.txs
+                .iter()
+                .take(count)
+                .map(|(tx_id, pooled)| (tx_id, &pooled.act_tx))
+                .collect()
+        };
+        let mut act_tx_ids = Vec::new();
+        let mut act_txs = Vec::new();
+        for (tx_id, act_tx) in selected {
+            act_tx_ids.push(tx_id.clone());
+            act_txs.push(act_tx.clone());
+        }
+        (act_tx_ids, act_txs)
+    }
+
+    // keeps the order of the remaining txs, returns the arrival times of the removed txs
+    pub fn remove_txs(&mut self, tx_ids: &[TxId]) -> Vec<Timestamp> {
+        let tx_ids: HashSet<&TxId> = tx_ids.iter().collect();
+        let mut arrival_times = Vec::with_capacity(tx_ids.len());
+        self.retain(|tx_id, pooled| {
+            if tx_ids.contains(tx_id) {
+                arrival_times.push(pooled.arrival_time);
+                false
+            } else {
+                true
+            }
+        });
+        arrival_times
+    }
+
+    // removes the txs waiting longer than the ttl, returns the expired txs
+    pub fn expire(&mut self, now: Timestamp) -> Vec<(TxId, ActTx)> {
+        let ttl = self.config.ttl_secs;
+        let expired_ids: Vec<TxId> = self
+            .txs
+            .iter()
+            .filter(|(_, pooled)| pooled.arrival_time + ttl <= now)
+            .map(|(tx_id, _)| tx_id.clone())
+            .collect();
+        if expired_ids.is_empty() {
+            return Vec::new();
+        }
+        let mut expired = Vec::with_capacity(expired_ids.len());
+        let expired_set: HashSet<&TxId> = expired_ids.iter().collect();
+        self.retain(|tx_id, pooled| {
+            if expired_set.contains(tx_id) {
+                expired.push((tx_id.clone(), pooled.act_tx.clone()));
+                false
+            } else {
+                true
+            }
+        });
+        expired
+    }
+}
+
+impl ActTxPool {
+    fn proto_count(&self, proto: &Proto) -> usize {
+        self.proto_counts.get(proto).copied().unwrap_or(0)
+    }
+
+    fn plan_eviction(&self, proto: &Proto, size: usize) -> anyhow::Result<Vec<TxId>> {
+        let mut evicted = Vec::new();
+        let mut evicted_set = HashSet::new();
+        let mut count = self.txs.len();
+        let mut total_size = self.total_size;
+        let mut proto_counts = self.proto_counts.clone();
+        let incoming_count = self.proto_count(proto) + 1;
+
+        while count + 1 > self.config.max_count || total_size + size > self.config.max_bytes {
+            let (largest_proto, largest_count) = proto_counts
+                .iter()
+                .filter(|(_, count)| **count > 0)
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(proto, count)| (proto.clone(), *count))
+                .ok_or_else(|| anyhow!("act tx pool is full"))?;
+            if largest_count <= incoming_count {
+                return Err(anyhow!("act tx pool is full"));
+            }
+            let (tx_id, pooled) = self
+                .txs
+                .iter()
+                .find(|(tx_id, pooled)| {
+                    pooled.act_tx.proto == largest_proto && !evicted_set.contains(*tx_id)
+                })
+                .ok_or_else(|| anyhow!("act tx pool is full"))?;
+            evicted_set.insert(tx_id.clone());
+            evicted.push(tx_id.clone());
+            count -= 1;
+            total_size -= pooled.size;
+            *proto_counts.get_mut(&largest_proto).unwrap() -= 1;
+        }
+        Ok(evicted)
+    }
+
+    fn remove(&mut self, tx_id: &TxId) -> Option<PooledActTx> {
+        let pooled = self.txs.shift_remove(tx_id)?;
+        self.on_removed(&pooled);
+        Some(pooled)
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&TxId, &PooledActTx) -> bool) {
+        let mut removed = Vec::new();
+        self.txs.retain(|tx_id, pooled| {
+            let retain = keep(tx_id, pooled);
+            if !retain {
+                removed.push((pooled.act_tx.proto.clone(), pooled.size));
+            }
+            retain
+        });
+        for (proto, size) in removed {
+            self.total_size -= size;
+            self.decrease_proto_count(&proto);
+        }
+    }
+
+    fn on_removed(&mut self, pooled: &PooledActTx) {
+        self.total_size -= pooled.size;
+        self.decrease_proto_count(&pooled.act_tx.proto);
+    }
+
+    fn decrease_proto_count(&mut self, proto: &Proto) {
+        if let Some(count) = self.proto_counts.get_mut(proto) {
+            *count -= 1;
+            if *count == 0 {
+                self.proto_counts.remove(proto);
+            }
+        }
+    }
+
+    // the protos take turns in the order of their oldest tx
+    fn select_round_robin(&self, count: usize) -> Vec<(&TxId, &ActTx)> {
+        let mut queues: IndexMap<&Proto, VecDeque<(&TxId, &ActTx)>> = IndexMap::new();
+        for (tx_id, pooled) in &self.txs {
+            queues
+                .entry(&pooled.act_tx.proto)
+                .or_default()
+                .push_back((tx_id, &pooled.act_tx));
+        }
+        let mut selected = Vec::new();
+        while selected.len() < count && !queues.is_empty() {
+            queues.retain(|_, queue| {
+                if selected.len() < count {
+                    if let Some(tx) = queue.pop_front() {
+                        selected.push(tx);
+                    }
+                }
+                !queue.is_empty()
+            });
+        }
+        selected
+    }
+}
+
+fn act_tx_size(act_tx: &ActTx) -> usize {
+    act_tx.action.len() + act_tx.proto.len() + act_tx.model.len() + act_tx.data.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vintage_utils::CalcHash;
+
+    fn act_tx(proto: &str, n: usize) -> ActTx {
+        ActTx {
+            action: "act".to_owned(),
+            proto: proto.to_owned(),
+            model: "m".to_owned(),
+            data: n.to_be_bytes().to_vec(),
+        }
+    }
+
+    fn pool(max_count: usize, round_robin: bool) -> ActTxPool {
+        ActTxPool::new(
+            ActPoolConfig {
+                max_count,
+                max_bytes: 1024 * 1024,
+                max_count_per_proto: max_count,
+                ttl_secs: 600,
+            },
+            round_robin,
+        )
+    }
+
+    fn insert(pool: &mut ActTxPool, proto: &str, n: usize) -> anyhow::Result<Vec<TxId>> {
+        let act_tx = act_tx(proto, n);
+        let evicted = pool.insert(act_tx.calc_hash(), act_tx, n as Timestamp)?;
+        Ok(evicted.into_iter().map(|(tx_id, _)| tx_id).collect())
+    }
+
+    fn selected(pool: &ActTxPool, count: usize) -> Vec<(String, usize)> {
+        let (_, act_txs) = pool.select(count);
+        act_txs
+            .into_iter()
+            .map(|act_tx| {
+                let n = usize::from_be_bytes(act_tx.data.try_into().unwrap());
+                (act_tx.proto, n)
+            })
+            .collect()
+    }
+
+    fn txs(txs: &[(&str, usize)]) -> Vec<(String, usize)> {
+        txs.iter()
+            .map(|(proto, n)| (proto.to_string(), *n))
+            .collect()
+    }
+
+    #[test]
+    fn select_in_arrival_order() {
+        let mut pool = pool(10, false);
+        insert(&mut pool, "a", 1).unwrap();
+        insert(&mut pool, "a", 2).unwrap();
+        insert(&mut pool, "b", 3).unwrap();
+        insert(&mut pool, "a", 4).unwrap();
+        assert_eq!(selected(&pool, 3), txs(&[("a", 1), ("a", 2), ("b", 3)]));
+
+        // the remaining txs keep their order
+        pool.remove_txs(&[act_tx("a", 2).calc_hash()]);
+        assert_eq!(selected(&pool, 10), txs(&[("a", 1), ("b", 3), ("a", 4)]));
+    }
+
+    #[test]
+    fn select_round_robin_interleaves_protos() {
+        let mut pool = pool(10, true);
+        insert(&mut pool, "a", 1).unwrap();
+        insert(&mut pool, "a", 2).unwrap();
+        insert(&mut pool, "a", 3).unwrap();
+        insert(&mut pool, "b", 4).unwrap();
+        insert(&mut pool, "c", 5).unwrap();
+        insert(&mut pool, "b", 6).unwrap();
+        assert_eq!(
+            selected(&pool, 10),
+            txs(&[("a", 1), ("b", 4), ("c", 5), ("a", 2), ("b", 6), ("a", 3)])
+        );
+        assert_eq!(
+            selected(&pool, 4),
+            txs(&[("a", 1), ("b", 4), ("c", 5), ("a", 2)])
+        );
+    }
+
+    #[test]
+    fn oldest_tx_not_starved() {
+        // a proto flooding the pool after the tx does not delay it
+        for round_robin in [false, true] {
+            let mut pool = pool(100, round_robin);
+            insert(&mut pool, "quiet", 0).unwrap();
+            for n in 1..50 {
+                insert(&mut pool, "flood", n).unwrap();
+            }
+            assert_eq!(selected(&pool, 1), txs(&[("quiet", 0)]));
+        }
+
+        // nor is the oldest tx of a quiet proto arriving after the flood, in round robin
+        let mut pool = pool(100, true);
+        for n in 0..50 {
+            insert(&mut pool, "flood", n).unwrap();
+        }
+        insert(&mut pool, "quiet", 50).unwrap();
+        assert_eq!(selected(&pool, 2), txs(&[("flood", 0), ("quiet", 50)]));
+    }
+
+    #[test]
+    fn expire_after_ttl() {
+        let mut pool = pool(10, false);
+        insert(&mut pool, "a", 1).unwrap();
+        insert(&mut pool, "a", 700).unwrap();
+        let expired = pool.expire(601);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(selected(&pool, 10), txs(&[("a", 700)]));
+    }
+
+    #[test]
+    fn evict_oldest_of_the_largest_proto() {
+        let mut pool = pool(4, false);
+        insert(&mut pool, "b", 1).unwrap();
+        insert(&mut pool, "a", 2).unwrap();
+        insert(&mut pool, "a", 3).unwrap();
+        insert(&mut pool, "a", 4).unwrap();
+        let evicted = insert(&mut pool, "c", 5).unwrap();
+        assert_eq!(evicted, vec![act_tx("a", 2).calc_hash()]);
+        assert_eq!(
+            selected(&pool, 10),
+            txs(&[("b", 1), ("a", 3), ("a", 4), ("c", 5)])
+        );
+    }
+
+    #[test]
+    fn reject_when_evicting_a_proto_not_larger() {
+        let mut pool = pool(4, false);
+        insert(&mut pool, "a", 1).unwrap();
+        insert(&mut pool, "a", 2).unwrap();
+        insert(&mut pool, "b", 3).unwrap();
+        insert(&mut pool, "b", 4).unwrap();
+        // the largest proto, 2 txs, is not larger than b with the new tx
+        assert!(insert(&mut pool, "b", 5).is_err());
+        // nor than a new proto if it is the largest itself
+        assert!(insert(&mut pool, "a", 6).is_err());
+        assert_eq!(pool.len(), 4);
+        // a new proto evicts from the largest, ties to the first proto by name
+        let evicted = insert(&mut pool, "c", 7).unwrap();
+        assert_eq!(evicted, vec![act_tx("a", 1).calc_hash()]);
+    }
+
+    #[test]
+    fn evict_for_the_byte_budget() {
+        let size = act_tx_size(&act_tx("a", 0));
+        let mut pool = ActTxPool::new(
+            ActPoolConfig {
+                max_count: 10,
+                max_bytes: size * 3,
+                max_count_per_proto: 10,
+                ttl_secs: 600,
+            },
+            false,
+        );
+        insert(&mut pool, "a", 1).unwrap();
+        insert(&mut pool, "a", 2).unwrap();
+        insert(&mut pool, "a", 3).unwrap();
+        let evicted = insert(&mut pool, "b", 4).unwrap();
+        assert_eq!(evicted, vec![act_tx("a", 1).calc_hash()]);
+
+        // a larger tx evicts as many txs as it needs
+        let big_tx = |proto: &str, n: usize| {
+            let mut act_tx = act_tx(proto, n);
+            act_tx.data.resize(size * 2 - act_tx_size(&act_tx) + 8, 0);
+            act_tx
+        };
+        let mut pool = ActTxPool::new(pool.config.clone(), false);
+        insert(&mut pool, "a", 1).unwrap();
+        insert(&mut pool, "a", 2).unwrap();
+        insert(&mut pool, "a", 3).unwrap();
+        let b = big_tx("b", 4);
+        let evicted = pool.insert(b.calc_hash(), b, 4).unwrap();
+        assert_eq!(evicted.len(), 2);
+        assert_eq!(pool.select(1).0, vec![act_tx("a", 3).calc_hash()]);
+        // but not from a proto not larger than its own
+        let c = big_tx("c", 5);
+        assert!(pool.insert(c.calc_hash(), c, 5).is_err());
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn reject_over_the_proto_quota() {
+        let mut pool = ActTxPool::new(
+            ActPoolConfig {
+                max_count: 10,
+                max_bytes: 1024 * 1024,
+                max_count_per_proto: 2,
+                ttl_secs: 600,
+            },
+            false,
+        );
+        insert(&mut pool, "a", 1).unwrap();
+        insert(&mut pool, "a", 2).unwrap();
+        assert!(insert(&mut pool, "a", 3).is_err());
+        insert(&mut pool, "b", 4).unwrap();
     }
-    (act_tx_ids, act_txs)
 }
@@ -1,7 +1,11 @@
 mod act_tx_pool;
 mod tx_pool;
+mod tx_status;
+mod validator_set_tx;
 mod wasm_tx_pool;
 
 pub(crate) use self::act_tx_pool::*;
 pub(crate) use self::tx_pool::*;
+pub(crate) use self::tx_status::*;
+pub(crate) use self::validator_set_tx::*;
 pub(crate) use self::wasm_tx_pool::*;
@@ -1,38 +1,56 @@
-use std::collections::HashMap;
+use crate::tx::ActTxPool;
+use crate::ActPoolConfig;
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::sync::{Mutex, MutexGuard};
-use vintage_msg::{ActTx, WasmId, WasmInfo};
-use vintage_utils::Hashed;
+use vintage_msg::{ValidatorSetTx, WasmId, WasmInfo};
+use vintage_utils::{Hashed, Timestamp};
 
 pub(crate) type TxId = Hashed;
 
+// the pools keep the arrival order
 pub(crate) struct TxPool {
-    act_txs: Mutex<HashMap<TxId, ActTx>>,
-    wasm_txs: Mutex<HashMap<WasmId, WasmInfo>>,
+    act_txs: Mutex<ActTxPool>,
+    // the update entity txs are pooled in the db, only their arrival times are kept here
+    ue_arrival_times: Mutex<HashMap<TxId, Timestamp>>,
+    wasm_txs: Mutex<IndexMap<WasmId, WasmInfo>>,
+    // at most one pending validator set change
+    validator_set_tx: Mutex<Option<ValidatorSetTx>>,
 }
 
 impl TxPool {
-    pub fn new(act_capacity: usize, wasm_capacity: usize) -> Self {
+    pub fn new(act_config: ActPoolConfig, act_round_robin: bool, wasm_capacity: usize) -> Self {
         Self {
-            act_txs: Mutex::new(HashMap::with_capacity(act_capacity)),
-            wasm_txs: Mutex::new(HashMap::with_capacity(wasm_capacity)),
+            act_txs: Mutex::new(ActTxPool::new(act_config, act_round_robin)),
+            ue_arrival_times: Mutex::new(HashMap::new()),
+            wasm_txs: Mutex::new(IndexMap::with_capacity(wasm_capacity)),
+            validator_set_tx: Mutex::new(None),
         }
     }
 
-    pub fn act_txs_guard(&self) -> MutexGuard<'_, HashMap<TxId, ActTx>> {
+    pub fn act_txs_guard(&self) -> MutexGuard<'_, ActTxPool> {
         self.act_txs.lock().unwrap()
     }
 
-    pub fn wasm_txs_guard(&self) -> MutexGuard<'_, HashMap<WasmId, WasmInfo>> {
+    pub fn ue_arrival_times_guard(&self) -> MutexGuard<'_, HashMap<TxId, Timestamp>> {
+        self.ue_arrival_times.lock().unwrap()
+    }
+
+    pub fn wasm_txs_guard(&self) -> MutexGuard<'_, IndexMap<WasmId, WasmInfo>> {
         self.wasm_txs.lock().unwrap()
     }
+
+    pub fn validator_set_tx_guard(&self) -> MutexGuard<'_, Option<ValidatorSetTx>> {
+        self.validator_set_tx.lock().unwrap()
+    }
 }
 
-pub fn remove_txs_from_pool<TTxId, TTx>(pool: &mut HashMap<TTxId, TTx>, tx_ids: &[TTxId])
+// keeps the order of the remaining txs
+pub fn remove_txs_from_pool<TTxId, TTx>(pool: &mut IndexMap<TTxId, TTx>, tx_ids: &[TTxId])
 where
     TTxId: Hash + Eq,
 {
-    for tx_id in tx_ids {
-        pool.remove(tx_id);
-    }
+    let tx_ids: HashSet<&TTxId> = tx_ids.iter().collect();
+    pool.retain(|tx_id, _| !tx_ids.contains(tx_id));
 }
@@ -0,0 +1,59 @@
+use crate::tx::TxId;
+use crate::{BlockChainDb, MsgToProxySender};
+use vintage_msg::{Proto, TxReceipt, TxStatus};
+
+// records the status of the txs and publishes it to the workers of the protos
+#[derive(Clone)]
+pub(crate) struct TxStatusReporter {
+    blockchain_db: BlockChainDb,
+    proxy_msg_sender: MsgToProxySender,
+}
+
+impl TxStatusReporter {
+    pub fn new(blockchain_db: BlockChainDb, proxy_msg_sender: MsgToProxySender) -> Self {
+        Self {
+            blockchain_db,
+            proxy_msg_sender,
+        }
+    }
+
+    // force_rejected: the rejection replaces the pending status, e.g. evicted from the pool
+    pub async fn report(&self, txs: Vec<(Proto, TxId)>, status: TxStatus, force_rejected: bool) {
+        self.record(&txs, &status, force_rejected).await;
+        self.publish(txs, status);
+    }
+
+    // the txs from the other nodes are not published to the local workers
+    pub async fn record(&self, txs: &[(Proto, TxId)], status: &TxStatus, force_rejected: bool) {
+        if txs.is_empty() {
+            return;
+        }
+        let receipts = txs
+            .iter()
+            .map(|(_, tx_id)| TxReceipt {
+                tx_id: tx_id.clone(),
+                status: status.clone(),
+            })
+            .collect();
+        if let Err(err) = self
+            .blockchain_db
+            .update_tx_statuses(receipts, force_rejected)
+            .await
+        {
+            tracing::error!("failed to update tx statuses: {:?}", err);
+        }
+    }
+
+    // the status is already recorded
+    pub fn publish(&self, txs: Vec<(Proto, TxId)>, status: TxStatus) {
+        for (proto, tx_id) in txs {
+            self.proxy_msg_sender.send_tx_status(
+                proto,
+                TxReceipt {
+                    tx_id,
+                    status: status.clone(),
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::anyhow;
+use std::collections::HashSet;
+use vintage_msg::{BlockHeight, ValidatorNode, ValidatorSetTx};
+use vintage_utils::{BlsPublicKey, BlsSignature, CalcHash, Quorum};
+
+// the current validators are the validator set at the height the tx is included in,
+// the last validator set is the last committed tx with its effective height
+pub fn check_validator_set_tx(
+    validator_set_tx: &ValidatorSetTx,
+    height: BlockHeight,
+    current_validators: &[ValidatorNode],
+    last_validator_set: Option<&(BlockHeight, ValidatorSetTx)>,
+) -> anyhow::Result<()> {
+    if validator_set_tx.validators.is_empty() {
+        return Err(anyhow!("validator set is empty"));
+    }
+    // the authority list of the next height is decided when a block is commited
+    if validator_set_tx.block_interval == 0 {
+        return Err(anyhow!("validator set block interval is 0"));
+    }
+    let nonce = last_validator_set.map_or(0, |(_, last_tx)| last_tx.nonce + 1);
+    if validator_set_tx.nonce != nonce {
+        return Err(anyhow!(
+            "validator set nonce {} != {}",
+            validator_set_tx.nonce,
+            nonce
+        ));
+    }
+    // the sets take effect in the order they are committed
+    let effective_height = height + validator_set_tx.block_interval;
+    if let Some((last_effective_height, _)) = last_validator_set {
+        if *last_effective_height >= effective_height {
+            return Err(anyhow!(
+                "validator set effective at {}, not after the last one at {}",
+                effective_height,
+                last_effective_height
+            ));
+        }
+    }
+    let mut public_keys = HashSet::new();
+    let mut total_vote_weight = 0u64;
+    for validator in &validator_set_tx.validators {
+        let public_key = BlsPublicKey::from_hex(&validator.public_key)
+            .map_err(|err| anyhow!("public key of validator {} err: {}", validator.name, err))?;
+        if !public_keys.insert(public_key.to_string()) {
+            return Err(anyhow!("validator {} duplicated", validator.name));
+        }
+        BlsSignature::from_hex(&validator.pop)
+            .and_then(|pop| pop.verify_possession(&public_key))
+            .map_err(|err| anyhow!("pop of validator {} err: {}", validator.name, err))?;
+        total_vote_weight += validator.vote_weight as u64;
+    }
+    if total_vote_weight == 0 {
+        return Err(anyhow!("total vote weight of validator set is 0"));
+    }
+    check_approvals(validator_set_tx, current_validators)
+}
+
+fn check_approvals(
+    validator_set_tx: &ValidatorSetTx,
+    current_validators: &[ValidatorNode],
+) -> anyhow::Result<()> {
+    let msg = validator_set_tx.approval_msg(current_validators);
+    let mut quorum_weights = Vec::with_capacity(current_validators.len());
+    for validator in current_validators {
+        let public_key = BlsPublicKey::from_hex(&validator.public_key)
+            .map_err(|err| anyhow!("public key of validator {} err: {}", validator.name, err))?;
+        quorum_weights.push((public_key.calc_hash(), validator.vote_weight));
+    }
+    let quorum = Quorum::new(quorum_weights);
+
+    let mut approvers = HashSet::new();
+    for approval in &validator_set_tx.approvals {
+        let public_key = BlsPublicKey::from_hex(&approval.public_key)
+            .map_err(|err| anyhow!("public key of approval err: {}", err))?;
+        let node_id = public_key.calc_hash();
+        if quorum.weight_of([&node_id]) == 0 {
+            return Err(anyhow!(
+                "approval of {} not in the validator set",
+                public_key
+            ));
+        }
+        BlsSignature::from_hex(&approval.signature)
+            .and_then(|signature| signature.verify(msg.as_bytes(), &public_key))
+            .map_err(|err| anyhow!("approval of {} err: {}", public_key, err))?;
+        if !approvers.insert(node_id) {
+            return Err(anyhow!("approval of {} duplicated", public_key));
+        }
+    }
+    if !quorum.is_reached(&approvers) {
+        return Err(anyhow!(
+            "validator set approval weight {} below threshold {}",
+            quorum.weight_of(&approvers),
+            quorum.threshold()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vintage_msg::ValidatorSetApproval;
+    use vintage_utils::BlsPrivateKey;
+
+    fn validator(id: u16, private_key: &BlsPrivateKey) -> ValidatorNode {
+        ValidatorNode {
+            id,
+            name: format!("Node{}", id),
+            address: format!("127.0.0.1:{}", 8000 + id).parse().unwrap(),
+            public_key: private_key.public_key().to_string(),
+            pop: private_key.prove_possession().to_string(),
+            propose_weight: 1,
+            vote_weight: 1,
+        }
+    }
+
+    fn approve(
+        tx: &mut ValidatorSetTx,
+        current_validators: &[ValidatorNode],
+        private_key: &BlsPrivateKey,
+    ) {
+        let msg = tx.approval_msg(current_validators);
+        tx.approvals.push(ValidatorSetApproval {
+            public_key: private_key.public_key().to_string(),
+            signature: private_key.sign(msg.as_bytes()).to_string(),
+        });
+    }
+
+    fn setup() -> (Vec<BlsPrivateKey>, Vec<ValidatorNode>, ValidatorSetTx) {
+        let keys: Vec<_> = (0..5).map(|_| BlsPrivateKey::generate()).collect();
+        let current: Vec<_> = (0..4)
+            .map(|i| validator(i + 1, &keys[i as usize]))
+            .collect();
+        let tx = ValidatorSetTx {
+            validators: (0..5)
+                .map(|i| validator(i + 1, &keys[i as usize]))
+                .collect(),
+            block_interval: 10,
+            nonce: 0,
+            approvals: vec![],
+        };
+        (keys, current, tx)
+    }
+
+    #[test]
+    fn approved_by_quorum() {
+        let (keys, current, mut tx) = setup();
+        approve(&mut tx, &current, &keys[0]);
+        approve(&mut tx, &current, &keys[1]);
+        assert!(check_validator_set_tx(&tx, 1, &current, None).is_err());
+        approve(&mut tx, &current, &keys[2]);
+        assert!(check_validator_set_tx(&tx, 1, &current, None).is_ok());
+    }
+
+    #[test]
+    fn approvals_outside_the_set_or_duplicated() {
+        let (keys, current, mut tx) = setup();
+        approve(&mut tx, &current, &keys[0]);
+        approve(&mut tx, &current, &keys[1]);
+        approve(&mut tx, &current, &keys[1]);
+        assert!(check_validator_set_tx(&tx, 1, &current, None).is_err());
+
+        let (keys, current, mut tx) = setup();
+        approve(&mut tx, &current, &keys[0]);
+        approve(&mut tx, &current, &keys[1]);
+        approve(&mut tx, &current, &keys[4]);
+        assert!(check_validator_set_tx(&tx, 1, &current, None).is_err());
+    }
+
+    #[test]
+    fn approvals_of_another_set() {
+        let (keys, current, mut tx) = setup();
+        let other = current[..3].to_vec();
+        for key in &keys[..3] {
+            approve(&mut tx, &other, key);
+        }
+        assert!(check_validator_set_tx(&tx, 1, &current, None).is_err());
+    }
+
+    #[test]
+    fn validator_without_pop() {
+        let (keys, current, mut tx) = setup();
+        // the pop of another key
+        tx.validators[4].pop = keys[0].prove_possession().to_string();
+        for key in &keys[..3] {
+            approve(&mut tx, &current, key);
+        }
+        assert!(check_validator_set_tx(&tx, 1, &current, None).is_err());
+    }
+
+    #[test]
+    fn approvals_replayed_after_the_set_changed_back() {
+        let (keys, current, mut tx) = setup();
+        for key in &keys[..3] {
+            approve(&mut tx, &current, key);
+        }
+        assert!(check_validator_set_tx(&tx, 1, &current, None).is_ok());
+        // the set changed to the new one and back to the current one
+        let mut back_tx = tx.clone();
+        back_tx.validators = current.clone();
+        back_tx.nonce = 1;
+        let last = (21, back_tx);
+        assert!(check_validator_set_tx(&tx, 30, &current, Some(&last)).is_err());
+        let mut tx = ValidatorSetTx {
+            nonce: 2,
+            approvals: vec![],
+            ..tx
+        };
+        for key in &keys[..3] {
+            approve(&mut tx, &current, key);
+        }
+        assert!(check_validator_set_tx(&tx, 30, &current, Some(&last)).is_ok());
+        // not effective after the last set
+        assert!(check_validator_set_tx(&tx, 5, &current, Some(&last)).is_err());
+    }
+
+    #[test]
+    fn approval_msg_of_different_sets() {
+        let (_, current, tx) = setup();
+        let mut other = tx.clone();
+        other.validators[0].public_key.push('0');
+        other.validators[0].pop.remove(0);
+        assert_ne!(tx.approval_msg(&current), other.approval_msg(&current));
+    }
+}
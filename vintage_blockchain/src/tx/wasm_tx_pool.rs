@@ -1,7 +1,11 @@
-use std::collections::HashMap;
+use crate::db::BlockChainDb;
+use crate::tx::TxPool;
+use anyhow::anyhow;
+use indexmap::IndexMap;
+use vintage_metrics::POOL_TXS;
 use vintage_msg::{WasmId, WasmInfo, WasmTx};
 
-pub fn get_wasm_txs_from_pool(pool: &HashMap<WasmId, WasmInfo>) -> Vec<WasmTx> {
+pub fn get_wasm_txs_from_pool(pool: &IndexMap<WasmId, WasmInfo>) -> Vec<WasmTx> {
     let mut wasm_txs = Vec::new();
     for (wasm_id, wasm_info) in pool {
         wasm_txs.push(WasmTx {
@@ -11,3 +15,27 @@ pub fn get_wasm_txs_from_pool(pool: &HashMap<WasmId, WasmInfo>) -> Vec<WasmTx> {
     }
     wasm_txs
 }
+
+pub async fn put_wasm_tx_to_pool(
+    blockchain_db: &BlockChainDb,
+    tx_pool: &TxPool,
+    key: WasmId,
+    info: WasmInfo,
+) -> anyhow::Result<()> {
+    {
+        if tx_pool.wasm_txs_guard().contains_key(&key) {
+            return Err(anyhow!(
+                "wasm tx {} {} already exists in pool",
+                key.proto,
+                key.wasm_hash
+            ));
+        }
+    }
+    blockchain_db.check_wasm_tx_not_exists(key.clone()).await?;
+    {
+        let mut pool = tx_pool.wasm_txs_guard();
+        pool.insert(key, info);
+        POOL_TXS.with_label_values(&["wasm"]).set(pool.len() as i64);
+    }
+    Ok(())
+}
@@ -1,10 +1,12 @@
 use crate::network::BlockChainNetworkClient;
 use crate::proxy::MsgToProxySender;
 use crate::wasm_db::WasmDb;
+use anyhow::anyhow;
 use async_trait::async_trait;
 use std::sync::Arc;
+use vintage_metrics::{WASM_DOWNLOADED, WASM_DOWNLOADED_BYTES, WASM_DOWNLOADS_PENDING};
 use vintage_msg::WasmHash;
-use vintage_utils::Service;
+use vintage_utils::{CalcHash, Service};
 
 pub(crate) struct DownloadWasmTask {
     wasm_db: WasmDb,
@@ -35,9 +37,11 @@ impl Service for DownloadWasmTask {
     type Output = ();
 
     async fn service(self, _input: Self::Input) -> Self::Output {
+        WASM_DOWNLOADS_PENDING.inc();
         if let Err(err) = self.service_impl().await {
-            log::error!("DownloadWasmTask err: {:?}", err);
+            tracing::error!("DownloadWasmTask err: {:?}", err);
         }
+        WASM_DOWNLOADS_PENDING.dec();
     }
 }
 
@@ -49,16 +53,27 @@ impl DownloadWasmTask {
             .await?;
         let wasm_binary = self
             .client
-            .request_wasm(self.wasm_hash.clone(), node_id)
+            .request_wasm(self.wasm_hash.clone(), node_id.clone())
             .await?;
+        let calc_hash = wasm_binary.calc_hash();
+        if calc_hash != self.wasm_hash {
+            return Err(anyhow!(
+                "wasm binary from node {}, hash {} != {}",
+                node_id,
+                calc_hash,
+                self.wasm_hash
+            ));
+        }
         self.wasm_db
             .finish_download_wasm_task(self.wasm_hash.clone(), wasm_binary.clone())
             .await?;
-        log::info!(
+        tracing::info!(
             "wasm file downloaded, hash: {}, size: {}B, saved in db",
             self.wasm_hash,
             wasm_binary.len()
         );
+        WASM_DOWNLOADED.inc();
+        WASM_DOWNLOADED_BYTES.inc_by(wasm_binary.len() as u64);
 
         self.proxy_msg_sender
             .send_wasm_binary(self.wasm_hash, wasm_binary);
@@ -45,7 +45,7 @@ impl Service for DownloadWasmTasks {
                 }
             }
             Err(err) => {
-                log::error!("get_download_wasm_tasks err: {:?}", err)
+                tracing::error!("get_download_wasm_tasks err: {:?}", err)
             }
         }
     }
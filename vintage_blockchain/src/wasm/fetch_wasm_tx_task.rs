@@ -0,0 +1,90 @@
+use crate::db::BlockChainDb;
+use crate::network::BlockChainNetworkClient;
+use crate::proxy::MsgToProxySender;
+use crate::tx::{put_wasm_tx_to_pool, TxPool};
+use crate::wasm_db::WasmDb;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::sync::Arc;
+use vintage_msg::{NodeId, WasmTx};
+use vintage_utils::{CalcHash, Service};
+
+// a gossiped wasm tx enters the pool once its binary is in the db,
+// fetched from the node that sent the tx and checked against the hash
+pub(crate) struct FetchWasmTxTask {
+    blockchain_db: BlockChainDb,
+    wasm_db: WasmDb,
+    tx_pool: Arc<TxPool>,
+    proxy_msg_sender: MsgToProxySender,
+    client: Arc<BlockChainNetworkClient>,
+    node_id: NodeId,
+    wasm_tx: WasmTx,
+}
+
+impl FetchWasmTxTask {
+    pub fn new(
+        blockchain_db: BlockChainDb,
+        wasm_db: WasmDb,
+        tx_pool: Arc<TxPool>,
+        proxy_msg_sender: MsgToProxySender,
+        client: Arc<BlockChainNetworkClient>,
+        node_id: NodeId,
+        wasm_tx: WasmTx,
+    ) -> Self {
+        Self {
+            blockchain_db,
+            wasm_db,
+            tx_pool,
+            proxy_msg_sender,
+            client,
+            node_id,
+            wasm_tx,
+        }
+    }
+}
+
+#[async_trait]
+impl Service for FetchWasmTxTask {
+    type Input = ();
+    type Output = ();
+
+    async fn service(self, _input: Self::Input) -> Self::Output {
+        if let Err(err) = self.service_impl().await {
+            tracing::error!("FetchWasmTxTask err: {:?}", err);
+        }
+    }
+}
+
+impl FetchWasmTxTask {
+    async fn service_impl(self) -> anyhow::Result<()> {
+        let WasmTx { wasm_id, wasm_info } = self.wasm_tx;
+        let wasm_hash = wasm_id.wasm_hash.clone();
+        let wasm_binary = self
+            .client
+            .request_wasm(wasm_hash.clone(), self.node_id.clone())
+            .await?;
+        let calc_hash = wasm_binary.calc_hash();
+        if calc_hash != wasm_hash {
+            return Err(anyhow!(
+                "wasm binary from node {}, hash {} != {}",
+                self.node_id,
+                calc_hash,
+                wasm_hash
+            ));
+        }
+        if self
+            .wasm_db
+            .try_insert_wasm_binary(wasm_hash.clone(), wasm_binary.clone())
+            .await?
+        {
+            tracing::info!(
+                "wasm file fetched, hash: {}, size: {}B, saved in db",
+                wasm_hash,
+                wasm_binary.len()
+            );
+            self.proxy_msg_sender
+                .send_wasm_binary(wasm_hash, wasm_binary);
+        }
+        put_wasm_tx_to_pool(&self.blockchain_db, &self.tx_pool, wasm_id, wasm_info).await
+    }
+}
@@ -1,5 +1,7 @@
 mod download_wasm_task;
 mod download_wasm_tasks;
+mod fetch_wasm_tx_task;
 
 pub(crate) use self::download_wasm_task::*;
 pub use self::download_wasm_tasks::*;
+pub(crate) use self::fetch_wasm_tx_task::*;
@@ -1,6 +1,5 @@
-use anyhow::anyhow;
 use redb::ReadableTable;
-use vintage_msg::WasmHash;
+use vintage_msg::{ApiError, WasmHash};
 use vintage_utils::{define_redb_table, RedbBytes, RedbBytes32};
 
 define_redb_table! {
@@ -18,7 +17,10 @@ where
     pub fn get_wasm_binary(&self, wasm_hash: &WasmHash) -> anyhow::Result<Vec<u8>> {
         match self.get(wasm_hash.as_bytes())? {
             Some(access) => Ok(access.value().into()),
-            None => Err(anyhow!("wasm file {} not found", wasm_hash)),
+            None => Err(ApiError::not_found(format!(
+                "wasm file {} not found",
+                wasm_hash
+            ))),
         }
     }
 }
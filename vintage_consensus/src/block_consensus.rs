@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use overlord::types::Hash;
 use overlord::Codec;
 use std::error::Error;
-use vintage_msg::BlockHeight;
+use vintage_msg::{BlockHeight, BlockProof, ValidatorNode};
 
 #[async_trait]
 pub trait BlockConsensus<T: Codec> {
@@ -17,10 +17,17 @@ pub trait BlockConsensus<T: Codec> {
         hash: Hash,
     ) -> Result<(), Box<dyn Error + Send>>;
 
+    // the block hash is in the proof
     async fn commit_block(
         &self,
         height: u64,
         block: T,
-        hash: Hash,
+        proof: BlockProof,
     ) -> Result<(), Box<dyn Error + Send>>;
+
+    // the on-chain validator set effective at the height, None if it was never changed
+    async fn get_validator_set(
+        &self,
+        height: u64,
+    ) -> Result<Option<Vec<ValidatorNode>>, Box<dyn Error + Send>>;
 }
@@ -1,120 +1,225 @@
 #![allow(clippy::mutable_key_type)]
 
+use crate::crypto::BlsCrypto;
+use crate::proof::block_proof_of;
+use crate::wal::RedbWal;
 use crate::BlockConsensus;
 use anyhow::anyhow;
 use async_trait::async_trait;
-use bytes::{Bytes, BytesMut};
+use bytes::Bytes;
 use creep::Context;
-use hasher::{Hasher, HasherKeccak};
-use lazy_static::lazy_static;
 use overlord::error::ConsensusError;
-use overlord::types::{Commit, Hash, Node, OverlordMsg, Status, ViewChangeReason};
-use overlord::{Consensus, Crypto, DurationConfig, Overlord, OverlordHandler, Wal};
+use overlord::types::{
+    Address, Commit, Hash, Node, OverlordMsg, Proposal, Status, ViewChangeReason,
+};
+use overlord::{Consensus, DurationConfig, Overlord, OverlordHandler};
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
-use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use tokio::sync::mpsc;
+use vintage_metrics::{CONSENSUS_ERRORS, CONSENSUS_VIEW_CHANGES};
 use vintage_msg::MsgToNetwork;
-use vintage_msg::{Block, ConsensusMsgChannels, OverlordMsgBlock};
+use vintage_msg::{
+    ArcConsensusRound, ArcConsensusRoundRecords, Block, BlockHash, BlockHeight,
+    ConsensusMsgChannels, ConsensusRound, ConsensusRoundRecord, NodeId, OverlordMsgBlock,
+    ValidatorNode,
+};
 use vintage_network::config::NodeConfig;
+use vintage_utils::{current_timestamp, ArcQuorum, BlsPrivateKey, BlsPublicKey, CalcHash, Quorum};
 
-lazy_static! {
-    static ref HASHER_INST: HasherKeccak = HasherKeccak::new();
-}
+const MAX_ROUND_RECORDS: usize = 256;
+const MAX_ERRORS_PER_ROUND: usize = 16;
 
-struct MockWal {
-    inner: Mutex<Option<Bytes>>,
+struct ConsensusEngine<BC> {
+    block_consensus: BC,
+    crypto: Arc<BlsCrypto>,
+    // from the config, until the validator set is changed on-chain
+    genesis_list: Vec<Node>,
+    // the validator set last sent to network, and the height it was read at
+    notified_validators: Mutex<(BlockHeight, Option<Vec<ValidatorNode>>)>,
+    node_id: NodeId,
+    // the quorum of the network votes, this node left out, shared with block sync
+    quorum: ArcQuorum<NodeId>,
+    // shared with the rpc server
+    consensus_round: ArcConsensusRound,
+    round_records: ArcConsensusRoundRecords,
+    // the height the consensus is at, and when its first round started
+    height_started: Mutex<(BlockHeight, Instant)>,
+    outbound: mpsc::Sender<MsgToNetwork>,
+    config: NodeConfig,
 }
 
-impl MockWal {
-    fn new() -> MockWal {
-        MockWal {
-            inner: Mutex::new(None),
+impl<BC> ConsensusEngine<BC> {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        block_consensus: BC,
+        crypto: Arc<BlsCrypto>,
+        genesis_list: Vec<Node>,
+        node_id: NodeId,
+        quorum: ArcQuorum<NodeId>,
+        consensus_round: ArcConsensusRound,
+        round_records: ArcConsensusRoundRecords,
+        outbound: mpsc::Sender<MsgToNetwork>,
+        config: NodeConfig,
+    ) -> Self {
+        Self {
+            block_consensus,
+            crypto,
+            genesis_list,
+            notified_validators: Mutex::new((0, None)),
+            node_id,
+            quorum,
+            consensus_round,
+            round_records,
+            height_started: Mutex::new((0, Instant::now())),
+            outbound,
+            config,
         }
     }
-}
 
-#[async_trait]
-impl Wal for MockWal {
-    async fn save(&self, info: Bytes) -> Result<(), Box<dyn Error + Send>> {
-        *self.inner.lock().unwrap() = Some(info);
-        Ok(())
+    // starts the record of the round, the consensus may be set to the same round again
+    fn set_consensus_round(&self, height: BlockHeight, round: u64) {
+        *self.consensus_round.write().unwrap() = ConsensusRound { height, round };
+        {
+            let mut height_started = self.height_started.lock().unwrap();
+            if height_started.0 != height {
+                *height_started = (height, Instant::now());
+            }
+        }
+        let mut records = self.round_records.write().unwrap();
+        if records
+            .back()
+            .is_some_and(|record| record.height == height && record.round == round)
+        {
+            return;
+        }
+        push_round_record(&mut records, height, round);
     }
 
-    async fn load(&self) -> Result<Option<Bytes>, Box<dyn Error + Send>> {
-        Ok(self.inner.lock().unwrap().as_ref().cloned())
+    fn update_round_record(
+        &self,
+        height: BlockHeight,
+        round: u64,
+        update: impl FnOnce(&mut ConsensusRoundRecord),
+    ) {
+        let mut records = self.round_records.write().unwrap();
+        match records
+            .iter_mut()
+            .rev()
+            .find(|record| record.height == height && record.round == round)
+        {
+            Some(record) => update(record),
+            None => update(push_round_record(&mut records, height, round)),
+        }
     }
-}
-
-struct MockCrypto {
-    name: Bytes,
-}
 
-impl MockCrypto {
-    fn new(name: Bytes) -> Self {
-        MockCrypto { name }
+    fn record_commit(&self, height: BlockHeight, round: u64, proposer: NodeId) {
+        let time_to_commit_ms = {
+            let height_started = self.height_started.lock().unwrap();
+            if height_started.0 == height {
+                Some(height_started.1.elapsed().as_millis() as u64)
+            } else {
+                None
+            }
+        };
+        tracing::info!(
+            target: "consensus_event",
+            "commit height={} round={} proposer={} time_to_commit_ms={:?}",
+            height,
+            round,
+            proposer,
+            time_to_commit_ms
+        );
+        self.update_round_record(height, round, |record| {
+            record.proposer = Some(proposer);
+            record.time_to_commit_ms = time_to_commit_ms;
+        });
     }
 }
 
-impl Crypto for MockCrypto {
-    fn hash(&self, block: Bytes) -> Bytes {
-        //log::info!("==Crypto block: {:?}", block);
-        let result = hash(&block);
-        //log::info!("==Crypto hash: {:?}", result);
-        result
-    }
-
-    fn sign(&self, _hash: Bytes) -> Result<Bytes, Box<dyn Error + Send>> {
-        Ok(self.name.clone())
-    }
-
-    fn aggregate_signatures(
-        &self,
-        _signatures: Vec<Bytes>,
-        _speaker: Vec<Bytes>,
-    ) -> Result<Bytes, Box<dyn Error + Send>> {
-        Ok(Bytes::new())
+fn push_round_record(
+    records: &mut VecDeque<ConsensusRoundRecord>,
+    height: BlockHeight,
+    round: u64,
+) -> &mut ConsensusRoundRecord {
+    if records.len() >= MAX_ROUND_RECORDS {
+        records.pop_front();
     }
+    records.push_back(ConsensusRoundRecord {
+        height,
+        round,
+        start_time: current_timestamp(),
+        proposer: None,
+        view_change_reason: None,
+        time_to_commit_ms: None,
+        errors: Vec::new(),
+    });
+    records.back_mut().unwrap()
+}
 
-    fn verify_signature(
-        &self,
-        _signature: Bytes,
-        _hash: Bytes,
-        _voter: Bytes,
-    ) -> Result<(), Box<dyn Error + Send>> {
-        Ok(())
+impl<BC> ConsensusEngine<BC>
+where
+    BC: BlockConsensus<Block> + Send + Sync,
+{
+    async fn authority_list(&self, height: BlockHeight) -> anyhow::Result<Vec<Node>> {
+        let validators = match self
+            .block_consensus
+            .get_validator_set(height)
+            .await
+            .map_err(|err| anyhow!("get_validator_set err: {:?}", err))?
+        {
+            Some(validators) => validators,
+            None => return Ok(self.genesis_list.clone()),
+        };
+
+        let mut nodes = Vec::with_capacity(validators.len());
+        for validator in &validators {
+            let public_key = BlsPublicKey::from_hex(&validator.public_key).map_err(|err| {
+                anyhow!("public key of validator {} err: {}", validator.name, err)
+            })?;
+            let address = node_id_to_bytes(&public_key);
+            self.crypto.insert_public_key(address.clone(), public_key);
+            nodes.push(Node {
+                address,
+                propose_weight: validator.propose_weight,
+                vote_weight: validator.vote_weight,
+            });
+        }
+        self.update_validators(height, validators, &nodes).await;
+        Ok(nodes)
     }
 
-    fn verify_aggregated_signature(
+    async fn update_validators(
         &self,
-        _aggregated_signature: Bytes,
-        _hash: Bytes,
-        _voters: Vec<Bytes>,
-    ) -> Result<(), Box<dyn Error + Send>> {
-        Ok(())
-    }
-}
-
-struct ConsensusEngine<BC> {
-    block_consensus: BC,
-    peer_list: Vec<Node>,
-    outbound: mpsc::Sender<MsgToNetwork>,
-    config: NodeConfig,
-}
-
-impl<BC> ConsensusEngine<BC> {
-    fn new(
-        block_consensus: BC,
-        peer_list: Vec<Node>,
-        outbound: mpsc::Sender<MsgToNetwork>,
-        config: NodeConfig,
-    ) -> Self {
-        Self {
-            block_consensus,
-            peer_list,
-            outbound,
-            config,
+        height: BlockHeight,
+        validators: Vec<ValidatorNode>,
+        nodes: &[Node],
+    ) {
+        {
+            let mut notified = self.notified_validators.lock().unwrap();
+            // the authority list of an old height is still queried while the messages arrive
+            if height < notified.0 {
+                return;
+            }
+            notified.0 = height;
+            if notified.1.as_ref() == Some(&validators) {
+                return;
+            }
+            notified.1 = Some(validators.clone());
         }
+        let quorum = build_peer_quorum(nodes, &self.node_id);
+        tracing::info!(
+            "validator set changed at height {}, peer quorum: {}/{}",
+            height,
+            quorum.threshold(),
+            quorum.total_weight()
+        );
+        *self.quorum.write().unwrap() = quorum;
+        let _result = self
+            .outbound
+            .send(MsgToNetwork::UpdateValidators(validators))
+            .await;
     }
 }
 
@@ -123,29 +228,34 @@ impl<BC> Consensus<Block> for ConsensusEngine<BC>
 where
     BC: BlockConsensus<Block> + Send + Sync,
 {
+    #[tracing::instrument(name = "consensus", skip_all, fields(height = height))]
     async fn get_block(
         &self,
         _ctx: Context,
         height: u64,
     ) -> Result<(Block, Hash), Box<dyn Error + Send>> {
-        //log::info!("+++++++get_block++++++++\n");
         let result = self.block_consensus.new_block(height).await;
         match result.as_ref() {
-            Ok((block, _hash)) => {
-                // Use block here
-                log::info!(
-                    "\n\n==========================\nget_block Block: {:?}\n==========================\n",
-                    block
+            Ok((block, hash)) => {
+                tracing::info!(
+                    height,
+                    act_txs = block.body.act_txs.len(),
+                    ue_txs = block.body.ue_txs.len(),
+                    wasm_txs = block.body.wasm_txs.len(),
+                    hash = %display_hash(hash),
+                    "new block proposed"
                 );
+                tracing::trace!(?block, "proposed block");
             }
             Err(e) => {
                 // Handle the error
-                log::info!("==get_block Error: {}", e);
+                tracing::info!("==get_block Error: {}", e);
             }
         }
         result
     }
 
+    #[tracing::instrument(name = "consensus", skip_all, fields(height = height))]
     async fn check_block(
         &self,
         _ctx: Context,
@@ -153,42 +263,51 @@ where
         hash: Hash,
         speech: Block,
     ) -> Result<(), Box<dyn Error + Send>> {
-        //log::info!("++++++++++check_block+++++++++++");
         let result = self.block_consensus.check_block(height, speech, hash).await;
         match result.as_ref() {
-            Err(_e) => log::info!("check_block error"),
-            _ => log::info!("check_block good"),
+            Err(_e) => tracing::info!("check_block error"),
+            _ => tracing::info!("check_block good"),
         }
         result
     }
 
+    #[tracing::instrument(name = "consensus", skip_all, fields(height = height))]
     async fn commit(
         &self,
         _ctx: Context,
         height: u64,
         commit: Commit<Block>,
     ) -> Result<Status, Box<dyn Error + Send>> {
-        log::info!(
-            "\n\n====================\nblock commit height: {}\n===================\n",
-            height
+        tracing::info!(
+            height,
+            round = commit.proof.round,
+            act_txs = commit.content.body.act_txs.len(),
+            ue_txs = commit.content.body.ue_txs.len(),
+            wasm_txs = commit.content.body.wasm_txs.len(),
+            hash = %display_hash(&commit.proof.block_hash),
+            "block commit"
         );
+        let proposer = commit.content.header.proposer.clone();
+        let round = commit.proof.round;
         self.block_consensus
-            .commit_block(height, commit.content, commit.proof.block_hash)
+            .commit_block(height, commit.content, block_proof_of(commit.proof)?)
             .await?;
+        self.record_commit(height, round, proposer);
+        self.set_consensus_round(height + 1, 0);
         Ok(Status {
             height: height + 1,
             interval: Some(self.config.block_interval),
             timer_config: None,
-            authority_list: self.peer_list.clone(),
+            authority_list: self.authority_list(height + 1).await?,
         })
     }
 
     async fn get_authority_list(
         &self,
         _ctx: Context,
-        _height: u64,
+        height: u64,
     ) -> Result<Vec<Node>, Box<dyn Error + Send>> {
-        Ok(self.peer_list.clone())
+        Ok(self.authority_list(height).await?)
     }
 
     async fn broadcast_to_other(
@@ -196,7 +315,6 @@ where
         _ctx: Context,
         words: OverlordMsgBlock,
     ) -> Result<(), Box<dyn Error + Send>> {
-        //log::info!("==broadcast_to_other");
         let _result = self
             .outbound
             .send(MsgToNetwork::ConsensusBroadcast(words))
@@ -218,17 +336,37 @@ where
         Ok(())
     }
 
-    fn report_error(&self, _ctx: Context, _err: ConsensusError) {
-        log::info!("++++++++++report_error++++++: {}", _err);
+    fn report_error(&self, _ctx: Context, err: ConsensusError) {
+        CONSENSUS_ERRORS.inc();
+        let ConsensusRound { height, round } = self.consensus_round.read().unwrap().clone();
+        tracing::warn!(
+            target: "consensus_event",
+            "error height={} round={} err=\"{}\"",
+            height,
+            round,
+            err
+        );
+        self.update_round_record(height, round, |record| {
+            if record.errors.len() < MAX_ERRORS_PER_ROUND {
+                record.errors.push(err.to_string());
+            }
+        });
     }
 
-    fn report_view_change(
-        &self,
-        _ctx: Context,
-        _height: u64,
-        _round: u64,
-        _reason: ViewChangeReason,
-    ) {
+    // the round reported is the one ending, the consensus goes on with the next round
+    fn report_view_change(&self, _ctx: Context, height: u64, round: u64, reason: ViewChangeReason) {
+        CONSENSUS_VIEW_CHANGES.inc();
+        tracing::warn!(
+            target: "consensus_event",
+            "view_change height={} round={} reason=\"{}\"",
+            height,
+            round,
+            reason
+        );
+        self.update_round_record(height, round, |record| {
+            record.view_change_reason = Some(reason.to_string());
+        });
+        self.set_consensus_round(height, round + 1);
     }
 }
 
@@ -236,11 +374,12 @@ pub struct Validator<BC>
 where
     BC: BlockConsensus<Block> + Send + Sync,
 {
-    overlord: Arc<Overlord<Block, ConsensusEngine<BC>, MockCrypto, MockWal>>,
+    overlord: Arc<Overlord<Block, ConsensusEngine<BC>, BlsCrypto, RedbWal>>,
     handler: OverlordHandler<Block>,
-    _consensus_engine: Arc<ConsensusEngine<BC>>,
+    consensus_engine: Arc<ConsensusEngine<BC>>,
     inbound: tokio::sync::Mutex<mpsc::Receiver<OverlordMsgBlock>>,
     config: NodeConfig,
+    node_list: Vec<Node>,
     block_synced_receiver: tokio::sync::Mutex<mpsc::Receiver<u64>>,
 }
 
@@ -250,6 +389,10 @@ where
 {
     pub async fn create(
         config: &NodeConfig,
+        private_key: BlsPrivateKey,
+        quorum: ArcQuorum<NodeId>,
+        consensus_round: ArcConsensusRound,
+        round_records: ArcConsensusRoundRecords,
         consensus_chn: ConsensusMsgChannels,
         block_consensus: BC,
     ) -> anyhow::Result<Self> {
@@ -258,26 +401,31 @@ where
             .await
             .map_err(|err| anyhow!("get_block_height err: {:?}", err))?;
 
-        log::info!(
+        tracing::info!(
             "Validator Created. start with block_height: {}",
             block_height + 1
         );
 
-        let name = socket_addr_to_bytes(&config.listen_addr);
-        let node_list = build_node_list(config);
-        let crypto = MockCrypto::new(name.clone());
+        let public_key = private_key.public_key();
+        let name = node_id_to_bytes(&public_key);
+        let genesis_list = build_node_list(config, &public_key)?;
+        let public_keys = build_public_keys(config, &public_key)?;
+        let crypto = Arc::new(BlsCrypto::new(private_key, public_keys));
+        let wal = RedbWal::create(&config.wal_path)?;
         let consensus_engine = Arc::new(ConsensusEngine::<BC>::new(
             block_consensus,
-            node_list.clone(),
+            crypto.clone(),
+            genesis_list,
+            public_key.calc_hash(),
+            quorum,
+            consensus_round,
+            round_records,
             consensus_chn.network_msg_sender,
             config.clone(),
         ));
-        let overlord = Overlord::new(
-            name,
-            Arc::clone(&consensus_engine),
-            Arc::new(crypto),
-            Arc::new(MockWal::new()),
-        );
+        consensus_engine.set_consensus_round(block_height + 1, 0);
+        let node_list = consensus_engine.authority_list(block_height + 1).await?;
+        let overlord = Overlord::new(name, Arc::clone(&consensus_engine), crypto, Arc::new(wal));
         let overlord_handler = overlord.get_handler();
 
         overlord_handler
@@ -287,7 +435,7 @@ where
                     height: block_height + 1,
                     interval: Some(config.block_interval),
                     timer_config: None,
-                    authority_list: node_list,
+                    authority_list: node_list.clone(),
                 }),
             )
             .unwrap();
@@ -295,22 +443,23 @@ where
         Ok(Self {
             overlord: Arc::new(overlord),
             handler: overlord_handler,
-            _consensus_engine: consensus_engine,
+            consensus_engine,
             inbound: tokio::sync::Mutex::new(consensus_chn.msg_receiver),
             config: config.clone(),
+            node_list,
             block_synced_receiver: tokio::sync::Mutex::new(consensus_chn.block_synced_receiver),
         })
     }
 
     pub async fn run(self: Arc<Self>, config: NodeConfig) -> Result<(), Box<dyn Error + Send>> {
-        log::info!("==Validator run.");
+        tracing::info!("==Validator run.");
         let interval = config.block_interval;
         let timer_config = timer_config();
-        let node_list = build_node_list(&config);
+        let node_list = self.node_list.clone();
         let handler: OverlordHandler<Block> = self.handler.clone();
         let s: Arc<Validator<BC>> = self.clone();
         let spawned_task = tokio::spawn(async move {
-            log::info!("Validator Started.");
+            tracing::info!("Validator Started.");
             loop {
                 let msg = {
                     let mut receiver = s.inbound.lock().await;
@@ -324,6 +473,10 @@ where
                                 .unwrap();
                         }
                         OverlordMsg::SignedProposal(proposal) => {
+                            if let Err(err) = check_block_proposer(&proposal.proposal) {
+                                tracing::warn!("proposal dropped: {}", err);
+                                continue;
+                            }
                             handler
                                 .send_msg(Context::new(), OverlordMsg::SignedProposal(proposal))
                                 .unwrap();
@@ -341,7 +494,7 @@ where
                         _ => {}
                     },
                     None => {
-                        eprintln!("receive nothing");
+                        tracing::error!("receive nothing");
                         // Depending on the error type, you might want to break the loop here
                         // break;
                     }
@@ -351,7 +504,7 @@ where
 
         let s: Arc<Validator<BC>> = self.clone();
         let block_sync_task = tokio::spawn(async move {
-            log::info!("===Handling Sync Block Completed Started.");
+            tracing::info!("===Handling Sync Block Completed Started.");
             loop {
                 let msg = {
                     let mut receiver = s.block_synced_receiver.lock().await;
@@ -359,11 +512,11 @@ where
                 };
                 match msg {
                     Some(msg) => {
-                        log::info!("====Sync Block receive new height: {}", msg);
-                        s.set_height(msg)
+                        tracing::info!("====Sync Block receive new height: {}", msg);
+                        s.set_height(msg).await
                     }
                     None => {
-                        eprintln!("receive nothing");
+                        tracing::error!("receive nothing");
                     }
                 }
             }
@@ -380,9 +533,21 @@ where
         Ok(())
     }
 
-    pub fn set_height(&self, block_height: u64) {
+    pub async fn set_height(&self, block_height: u64) {
         let overlord_handler = self.overlord.get_handler();
-        let node_list = build_node_list(&self.config);
+        let node_list = match self.consensus_engine.authority_list(block_height + 1).await {
+            Ok(node_list) => node_list,
+            Err(err) => {
+                tracing::error!(
+                    "authority_list of height {} err: {:?}",
+                    block_height + 1,
+                    err
+                );
+                return;
+            }
+        };
+        self.consensus_engine
+            .set_consensus_round(block_height + 1, 0);
         overlord_handler
             .send_msg(
                 Context::new(),
@@ -397,26 +562,39 @@ where
     }
 }
 
-fn hash(bytes: &Bytes) -> Bytes {
-    let mut out = [0u8; 32];
-    out.copy_from_slice(&HASHER_INST.digest(bytes));
-    BytesMut::from(&out[..]).freeze()
-}
-
 pub fn timer_config() -> Option<DurationConfig> {
     Some(DurationConfig::new(20, 20, 20, 10))
 }
 
-fn socket_addr_to_bytes(addr: &SocketAddr) -> Bytes {
-    Bytes::from(addr.to_string())
+// the proposer of the block draws its randomness, it must be the leader overlord checks the proposal
+// against, or the leader of the round the block is locked in
+fn check_block_proposer(proposal: &Proposal<Block>) -> anyhow::Result<()> {
+    let leader = match &proposal.lock {
+        Some(polc) => &polc.lock_votes.leader,
+        None => &proposal.proposer,
+    };
+    let proposer = &proposal.content.header.proposer;
+    if Bytes::from(proposer) != *leader {
+        return Err(anyhow!(
+            "block {} round {} proposer {} is not the leader",
+            proposal.height,
+            proposal.round,
+            proposer
+        ));
+    }
+    Ok(())
+}
+
+fn node_id_to_bytes(public_key: &BlsPublicKey) -> Bytes {
+    Bytes::from(&public_key.calc_hash())
 }
 
-fn build_node_list(config: &NodeConfig) -> Vec<Node> {
+fn build_node_list(config: &NodeConfig, public_key: &BlsPublicKey) -> anyhow::Result<Vec<Node>> {
     let mut nodes = Vec::new();
 
     // Add the current node
     nodes.push(Node {
-        address: socket_addr_to_bytes(&config.listen_addr),
+        address: node_id_to_bytes(public_key),
         propose_weight: config.propose_weight,
         vote_weight: config.vote_weight,
     });
@@ -424,11 +602,43 @@ fn build_node_list(config: &NodeConfig) -> Vec<Node> {
     // Add peer nodes
     for peer in &config.peers {
         nodes.push(Node {
-            address: socket_addr_to_bytes(&peer.address),
+            address: Bytes::from(&peer.node_id()?),
             propose_weight: peer.propose_weight,
             vote_weight: peer.vote_weight,
         });
     }
-    log::info!("build_node_list: {:?}", nodes);
-    nodes
+    tracing::info!("build_node_list: {:?}", nodes);
+    Ok(nodes)
+}
+
+fn build_peer_quorum(nodes: &[Node], local_node_id: &NodeId) -> Quorum<NodeId> {
+    Quorum::new(nodes.iter().filter_map(|node| {
+        NodeId::try_from(&node.address)
+            .ok()
+            .filter(|node_id| node_id != local_node_id)
+            .map(|node_id| (node_id, node.vote_weight))
+    }))
+}
+
+fn build_public_keys(
+    config: &NodeConfig,
+    public_key: &BlsPublicKey,
+) -> anyhow::Result<HashMap<Address, BlsPublicKey>> {
+    let mut public_keys = HashMap::new();
+
+    // Add the current node
+    public_keys.insert(node_id_to_bytes(public_key), *public_key);
+
+    // Add peer nodes
+    for peer in &config.peers {
+        let public_key = peer.public_key()?;
+        public_keys.insert(node_id_to_bytes(&public_key), public_key);
+    }
+    Ok(public_keys)
+}
+
+fn display_hash(hash: &Hash) -> String {
+    BlockHash::try_from(hash)
+        .map(|hash| hash.to_string())
+        .unwrap_or_default()
 }
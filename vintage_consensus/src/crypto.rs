@@ -0,0 +1,100 @@
+use anyhow::anyhow;
+use bytes::{Bytes, BytesMut};
+use hasher::{Hasher, HasherKeccak};
+use lazy_static::lazy_static;
+use overlord::types::{Address, Hash, Signature};
+use overlord::Crypto;
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::RwLock;
+use vintage_utils::{BlsPrivateKey, BlsPublicKey, BlsSignature};
+
+lazy_static! {
+    static ref HASHER_INST: HasherKeccak = HasherKeccak::new();
+}
+
+pub(crate) struct BlsCrypto {
+    private_key: BlsPrivateKey,
+    // keys of removed validators are kept, messages of the old heights may still arrive
+    public_keys: RwLock<HashMap<Address, BlsPublicKey>>,
+}
+
+impl BlsCrypto {
+    pub fn new(private_key: BlsPrivateKey, public_keys: HashMap<Address, BlsPublicKey>) -> Self {
+        Self {
+            private_key,
+            public_keys: RwLock::new(public_keys),
+        }
+    }
+
+    pub fn insert_public_key(&self, address: Address, public_key: BlsPublicKey) {
+        self.public_keys
+            .write()
+            .unwrap()
+            .insert(address, public_key);
+    }
+
+    fn public_key(&self, address: &Address) -> anyhow::Result<BlsPublicKey> {
+        self.public_keys
+            .read()
+            .unwrap()
+            .get(address)
+            .copied()
+            .ok_or_else(|| anyhow!("public key of {:?} not found", address))
+    }
+}
+
+impl Crypto for BlsCrypto {
+    fn hash(&self, msg: Bytes) -> Hash {
+        hash(&msg)
+    }
+
+    fn sign(&self, hash: Hash) -> Result<Signature, Box<dyn Error + Send>> {
+        let signature = self.private_key.sign(&hash);
+        Ok(Bytes::copy_from_slice(&signature.to_bytes()))
+    }
+
+    fn aggregate_signatures(
+        &self,
+        signatures: Vec<Signature>,
+        _voters: Vec<Address>,
+    ) -> Result<Signature, Box<dyn Error + Send>> {
+        let mut bls_signatures = Vec::with_capacity(signatures.len());
+        for signature in signatures {
+            bls_signatures.push(BlsSignature::from_bytes(&signature)?);
+        }
+        let aggregated = BlsSignature::aggregate(&bls_signatures)?;
+        Ok(Bytes::copy_from_slice(&aggregated.to_bytes()))
+    }
+
+    fn verify_signature(
+        &self,
+        signature: Signature,
+        hash: Hash,
+        voter: Address,
+    ) -> Result<(), Box<dyn Error + Send>> {
+        let public_key = self.public_key(&voter)?;
+        BlsSignature::from_bytes(&signature)?.verify(&hash, &public_key)?;
+        Ok(())
+    }
+
+    fn verify_aggregated_signature(
+        &self,
+        aggregated_signature: Signature,
+        hash: Hash,
+        voters: Vec<Address>,
+    ) -> Result<(), Box<dyn Error + Send>> {
+        let mut public_keys = Vec::with_capacity(voters.len());
+        for voter in &voters {
+            public_keys.push(self.public_key(voter)?);
+        }
+        BlsSignature::from_bytes(&aggregated_signature)?.verify_aggregated(&hash, &public_keys)?;
+        Ok(())
+    }
+}
+
+pub(crate) fn hash(bytes: &Bytes) -> Bytes {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&HASHER_INST.digest(bytes));
+    BytesMut::from(&out[..]).freeze()
+}
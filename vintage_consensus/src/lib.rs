@@ -1,6 +1,10 @@
 mod block_consensus;
 mod consensus;
+mod crypto;
+mod proof;
+mod wal;
 
 pub use self::block_consensus::*;
 pub use self::consensus::*;
+pub use self::proof::*;
 pub use overlord::types::OverlordMsg;
@@ -0,0 +1,88 @@
+use crate::crypto::hash;
+use anyhow::anyhow;
+use bytes::Bytes;
+use overlord::types::{Proof, Vote, VoteType};
+use vintage_msg::{BlockHash, BlockProof, NodeId, ValidatorNode};
+use vintage_utils::{BlsPublicKey, BlsSignature, CalcHash, Quorum};
+
+pub(crate) fn block_proof_of(proof: Proof) -> anyhow::Result<BlockProof> {
+    let block_hash: BlockHash = (&proof.block_hash).try_into()?;
+    Ok(BlockProof {
+        height: proof.height,
+        round: proof.round,
+        block_hash,
+        signature: proof.signature.signature.to_vec(),
+        voter_bitmap: proof.signature.address_bitmap.to_vec(),
+    })
+}
+
+// the validators of the bitmap must weigh more than 2/3 of the validator set at the height,
+// and the signature must be their aggregated signature over the precommit vote, as overlord signs it
+pub fn verify_block_proof(proof: &BlockProof, validators: &[ValidatorNode]) -> anyhow::Result<()> {
+    let authority = authority_of(validators)?;
+    let mut voters = Vec::new();
+    let mut public_keys = Vec::new();
+    for (index, (node_id, public_key, _)) in authority.iter().enumerate() {
+        if bit_is_set(&proof.voter_bitmap, index) {
+            voters.push(node_id.clone());
+            public_keys.push(*public_key);
+        }
+    }
+    let quorum = Quorum::new(
+        authority
+            .iter()
+            .map(|(node_id, _, vote_weight)| (node_id.clone(), *vote_weight)),
+    );
+    if !quorum.is_reached(&voters) {
+        return Err(anyhow!(
+            "block {} proof weight {} below threshold {}",
+            proof.height,
+            quorum.weight_of(&voters),
+            quorum.threshold()
+        ));
+    }
+
+    let vote = Vote {
+        height: proof.height,
+        round: proof.round,
+        vote_type: VoteType::Precommit,
+        block_hash: Bytes::from(&proof.block_hash),
+    };
+    let vote_hash = hash(&Bytes::from(rlp::encode(&vote).to_vec()));
+    BlsSignature::from_bytes(&proof.signature)?
+        .verify_aggregated(&vote_hash, &public_keys)
+        .map_err(|err| anyhow!("block {} proof signature err: {}", proof.height, err))
+}
+
+// the validators in the bitmap of the proof, the signature is not verified
+pub fn block_proof_voters(
+    proof: &BlockProof,
+    validators: &[ValidatorNode],
+) -> anyhow::Result<Vec<NodeId>> {
+    Ok(authority_of(validators)?
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| bit_is_set(&proof.voter_bitmap, *index))
+        .map(|(_, (node_id, _, _))| node_id)
+        .collect())
+}
+
+// the authority list of overlord is sorted by address, that is the node id
+fn authority_of(validators: &[ValidatorNode]) -> anyhow::Result<Vec<(NodeId, BlsPublicKey, u32)>> {
+    let mut authority = Vec::with_capacity(validators.len());
+    for validator in validators {
+        let public_key = BlsPublicKey::from_hex(&validator.public_key)
+            .map_err(|err| anyhow!("public key of validator {} err: {}", validator.name, err))?;
+        authority.push((public_key.calc_hash(), public_key, validator.vote_weight));
+    }
+    authority
+        .sort_by(|(node_id1, _, _), (node_id2, _, _)| node_id1.as_bytes().cmp(node_id2.as_bytes()));
+    Ok(authority)
+}
+
+// the bitmap is read from the most significant bit, as bit-vec does
+fn bit_is_set(bitmap: &[u8], index: usize) -> bool {
+    bitmap
+        .get(index / 8)
+        .is_some_and(|byte| byte & (0x80 >> (index % 8)) != 0)
+}
@@ -0,0 +1,355 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use overlord::Wal;
+use redb::{Database, Durability};
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::task::spawn_blocking;
+use vintage_utils::{define_redb_table, RedbBytes};
+
+define_redb_table! {
+    pub(crate) (WalTable, WalTableR, WalTableW) = ((), RedbBytes, "overlord_wal")
+}
+
+// the lock and vote state of overlord, written with immediate durability (fsync on commit)
+// so that a restarted validator can not vote against what it has voted before the crash
+pub(crate) struct RedbWal {
+    database: Arc<Database>,
+}
+
+impl RedbWal {
+    pub fn create(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let database = Database::create(path)?;
+        let db_write = database.begin_write()?;
+        WalTableW::open_table(&db_write)?;
+        db_write.commit()?;
+        Ok(Self {
+            database: Arc::new(database),
+        })
+    }
+
+    fn save_inner(database: &Database, info: &[u8]) -> anyhow::Result<()> {
+        let mut db_write = database.begin_write()?;
+        db_write.set_durability(Durability::Immediate);
+        {
+            let mut table = WalTableW::open_table(&db_write)?;
+            table.insert((), info)?;
+        }
+        db_write.commit()?;
+        Ok(())
+    }
+
+    fn load_inner(database: &Database) -> anyhow::Result<Option<Bytes>> {
+        let db_read = database.begin_read()?;
+        let table = WalTableR::open_table(&db_read)?;
+        let info = table
+            .get(())?
+            .map(|access| Bytes::copy_from_slice(access.value()));
+        Ok(info)
+    }
+}
+
+#[async_trait]
+impl Wal for RedbWal {
+    async fn save(&self, info: Bytes) -> Result<(), Box<dyn Error + Send>> {
+        let database = self.database.clone();
+        spawn_blocking(move || Self::save_inner(&database, &info))
+            .await
+            .map_err(anyhow::Error::from)??;
+        Ok(())
+    }
+
+    async fn load(&self) -> Result<Option<Bytes>, Box<dyn Error + Send>> {
+        let database = self.database.clone();
+        let info = spawn_blocking(move || Self::load_inner(&database))
+            .await
+            .map_err(anyhow::Error::from)??;
+        Ok(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::BlsCrypto;
+    use creep::Context;
+    use overlord::error::ConsensusError;
+    use overlord::types::{Address, Commit, Hash, Node, OverlordMsg, Status, ViewChangeReason};
+    use overlord::{Consensus, Overlord};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio::task::JoinHandle;
+    use vintage_msg::{Block, BlockBody, BlockHash, BlockHeader, NodeId};
+    use vintage_utils::{BlsPrivateKey, CalcHash, Hashed};
+
+    fn wal_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("vintage_wal_{}_{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    async fn load(wal: &RedbWal) -> Option<Bytes> {
+        wal.load().await.map_err(|err| err.to_string()).unwrap()
+    }
+
+    async fn save(wal: &RedbWal, info: &'static [u8]) {
+        wal.save(Bytes::from_static(info))
+            .await
+            .map_err(|err| err.to_string())
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn save_drop_reopen_load() {
+        let path = wal_path("reopen");
+        {
+            let wal = RedbWal::create(&path).unwrap();
+            assert_eq!(load(&wal).await, None);
+            save(&wal, b"height 1 round 0").await;
+        }
+        {
+            let wal = RedbWal::create(&path).unwrap();
+            assert_eq!(
+                load(&wal).await,
+                Some(Bytes::from_static(b"height 1 round 0"))
+            );
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    // the validator is stopped after locking on a block in the middle of a height,
+    // after the restart overlord must find the lock instead of the state of an earlier step
+    #[tokio::test]
+    async fn restart_mid_height_keeps_last_lock() {
+        let path = wal_path("restart");
+        {
+            let wal = RedbWal::create(&path).unwrap();
+            save(&wal, b"height 5 round 0 step propose").await;
+            save(&wal, b"height 5 round 0 step prevote").await;
+            save(&wal, b"height 5 round 1 step precommit lock block_a").await;
+            // killed before the height is committed
+        }
+        {
+            let wal = RedbWal::create(&path).unwrap();
+            assert_eq!(
+                load(&wal).await,
+                Some(Bytes::from_static(
+                    b"height 5 round 1 step precommit lock block_a"
+                ))
+            );
+            // the height is finished after the restart
+            save(&wal, b"height 6 round 0 step propose").await;
+        }
+        {
+            let wal = RedbWal::create(&path).unwrap();
+            assert_eq!(
+                load(&wal).await,
+                Some(Bytes::from_static(b"height 6 round 0 step propose"))
+            );
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////
+    // the engine
+
+    const INTERVAL: u64 = 100;
+
+    // a single validator, its blocks carry the run in the timestamp
+    struct TestConsensus {
+        run: u64,
+        node: Node,
+        // the engine is stuck in the commit as if killed, the block is locked by then
+        stuck_in_commit: bool,
+        commits: mpsc::UnboundedSender<(u64, Block, Hash)>,
+    }
+
+    #[async_trait]
+    impl Consensus<Block> for TestConsensus {
+        async fn get_block(
+            &self,
+            _ctx: Context,
+            height: u64,
+        ) -> Result<(Block, Hash), Box<dyn Error + Send>> {
+            let header = BlockHeader {
+                height,
+                prev_hash: BlockHash::zero_hash(),
+                proposer: NodeId::zero_hash(),
+                timestamp: self.run,
+                total_act_txs: 0,
+                tx_root: Hashed::zero_hash(),
+                state_root: Hashed::zero_hash(),
+                randomness: Hashed::zero_hash(),
+                randomness_proof: Vec::new(),
+            };
+            let hash = Bytes::from(&header.calc_hash());
+            let block = Block {
+                header,
+                body: BlockBody::default(),
+            };
+            Ok((block, hash))
+        }
+
+        async fn check_block(
+            &self,
+            _ctx: Context,
+            _height: u64,
+            _hash: Hash,
+            _block: Block,
+        ) -> Result<(), Box<dyn Error + Send>> {
+            Ok(())
+        }
+
+        async fn commit(
+            &self,
+            _ctx: Context,
+            height: u64,
+            commit: Commit<Block>,
+        ) -> Result<Status, Box<dyn Error + Send>> {
+            let _ = self
+                .commits
+                .send((height, commit.content, commit.proof.block_hash));
+            if self.stuck_in_commit {
+                std::future::pending::<()>().await;
+            }
+            Ok(status(height + 1, &self.node))
+        }
+
+        async fn get_authority_list(
+            &self,
+            _ctx: Context,
+            _height: u64,
+        ) -> Result<Vec<Node>, Box<dyn Error + Send>> {
+            Ok(vec![self.node.clone()])
+        }
+
+        async fn broadcast_to_other(
+            &self,
+            _ctx: Context,
+            _msg: OverlordMsg<Block>,
+        ) -> Result<(), Box<dyn Error + Send>> {
+            Ok(())
+        }
+
+        async fn transmit_to_relayer(
+            &self,
+            _ctx: Context,
+            _addr: Address,
+            _msg: OverlordMsg<Block>,
+        ) -> Result<(), Box<dyn Error + Send>> {
+            Ok(())
+        }
+
+        fn report_error(&self, _ctx: Context, _error: ConsensusError) {}
+
+        fn report_view_change(
+            &self,
+            _ctx: Context,
+            _height: u64,
+            _round: u64,
+            _reason: ViewChangeReason,
+        ) {
+        }
+    }
+
+    fn status(height: u64, node: &Node) -> Status {
+        Status {
+            height,
+            interval: Some(INTERVAL),
+            timer_config: None,
+            authority_list: vec![node.clone()],
+        }
+    }
+
+    // the db of the killed engine is closed once its tasks are dropped
+    async fn reopen_wal(path: &PathBuf) -> RedbWal {
+        for _ in 0..50 {
+            if let Ok(wal) = RedbWal::create(path) {
+                return wal;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("wal not reopened");
+    }
+
+    // started as the validator does, at the height after the last block
+    async fn start_engine(
+        path: &PathBuf,
+        run: u64,
+        stuck_in_commit: bool,
+    ) -> (JoinHandle<()>, mpsc::UnboundedReceiver<(u64, Block, Hash)>) {
+        let private_key = BlsPrivateKey::from_bytes(&[1; 32]).unwrap();
+        let public_key = private_key.public_key();
+        let address = Bytes::from(&public_key.calc_hash());
+        let node = Node {
+            address: address.clone(),
+            propose_weight: 1,
+            vote_weight: 1,
+        };
+        let crypto = BlsCrypto::new(private_key, HashMap::from([(address.clone(), public_key)]));
+        let (commit_sender, commit_receiver) = mpsc::unbounded_channel();
+        let consensus = TestConsensus {
+            run,
+            node: node.clone(),
+            stuck_in_commit,
+            commits: commit_sender,
+        };
+        let wal = reopen_wal(path).await;
+        let overlord = Overlord::new(
+            address,
+            Arc::new(consensus),
+            Arc::new(crypto),
+            Arc::new(wal),
+        );
+        overlord
+            .get_handler()
+            .send_msg(Context::new(), OverlordMsg::RichStatus(status(1, &node)))
+            .unwrap();
+        let engine = tokio::spawn(async move {
+            overlord.run(0, INTERVAL, vec![node], None).await.unwrap();
+        });
+        (engine, commit_receiver)
+    }
+
+    async fn next_commit(
+        commits: &mut mpsc::UnboundedReceiver<(u64, Block, Hash)>,
+    ) -> (u64, Block, Hash) {
+        tokio::time::timeout(Duration::from_secs(30), commits.recv())
+            .await
+            .expect("no commit in time")
+            .expect("engine stopped")
+    }
+
+    // the engine is killed after the precommit lock of height 1, before the block is committed,
+    // the restarted engine must commit the locked block at height 1 instead of a new proposal
+    #[tokio::test(flavor = "multi_thread")]
+    async fn engine_restarted_after_precommit_keeps_the_lock() {
+        let path = wal_path("engine");
+
+        let (engine, mut commits) = start_engine(&path, 1, true).await;
+        let (height, locked_block, locked_hash) = next_commit(&mut commits).await;
+        assert_eq!(height, 1);
+        assert_eq!(locked_block.header.timestamp, 1);
+        engine.abort();
+        let _ = engine.await;
+
+        // a block proposed by the second run would carry its timestamp
+        let (engine, mut commits) = start_engine(&path, 2, false).await;
+        let (height, block, hash) = next_commit(&mut commits).await;
+        assert_eq!(height, 1);
+        assert_eq!(block, locked_block);
+        assert_eq!(hash, locked_hash);
+        // and it goes on from there
+        let (height, block, _) = next_commit(&mut commits).await;
+        assert_eq!(height, 2);
+        assert_eq!(block.header.timestamp, 2);
+        engine.abort();
+        let _ = engine.await;
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
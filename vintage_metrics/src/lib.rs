@@ -0,0 +1,26 @@
+mod metrics;
+mod service;
+
+pub use self::metrics::*;
+pub use self::service::*;
+
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use vintage_utils::ServiceStarter;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MetricsConfig {
+    pub listen_addr: SocketAddr,
+}
+
+pub enum Metrics {}
+
+impl Metrics {
+    pub async fn create(config: MetricsConfig) -> anyhow::Result<ServiceStarter<MetricsService>> {
+        tracing::info!("metrics listen on: {}", config.listen_addr);
+        let listener = TcpListener::bind(config.listen_addr).await?;
+
+        Ok(ServiceStarter::new_with_input(MetricsService, listener))
+    }
+}
@@ -0,0 +1,100 @@
+use lazy_static::lazy_static;
+use prometheus::{
+    exponential_buckets, register_histogram, register_histogram_vec, register_int_counter,
+    register_int_counter_vec, register_int_gauge, register_int_gauge_vec, Histogram, HistogramVec,
+    IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+};
+
+lazy_static! {
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    // blockchain
+
+    pub static ref BLOCK_HEIGHT: IntGauge =
+        register_int_gauge!("vintage_block_height", "height of the last committed block")
+            .unwrap();
+    pub static ref BLOCK_COMMIT_SECONDS: Histogram = register_histogram!(
+        "vintage_block_commit_seconds",
+        "time to commit a block to the db",
+        exponential_buckets(0.001, 2.0, 14).unwrap()
+    )
+    .unwrap();
+    pub static ref BLOCK_TXS: HistogramVec = register_histogram_vec!(
+        "vintage_block_txs",
+        "txs per committed block",
+        &["kind"],
+        vec![0.0, 1.0, 10.0, 50.0, 100.0, 500.0, 1000.0, 5000.0]
+    )
+    .unwrap();
+    pub static ref POOL_TXS: IntGaugeVec =
+        register_int_gauge_vec!("vintage_pool_txs", "txs waiting in the pool", &["pool"]).unwrap();
+    pub static ref POOL_WAIT_SECONDS: HistogramVec = register_histogram_vec!(
+        "vintage_pool_wait_seconds",
+        "time from entering the pool to being included in a block",
+        &["pool"],
+        exponential_buckets(1.0, 2.0, 12).unwrap()
+    )
+    .unwrap();
+    pub static ref SYNC_LAG_BLOCKS: IntGauge = register_int_gauge!(
+        "vintage_sync_lag_blocks",
+        "blocks behind the peers, found by the last block sync batch"
+    )
+    .unwrap();
+    pub static ref SYNC_IMPORTED_BLOCKS: IntCounter = register_int_counter!(
+        "vintage_sync_imported_blocks_total",
+        "blocks imported by block sync"
+    )
+    .unwrap();
+    pub static ref FORK_ALERTS: IntCounterVec = register_int_counter_vec!(
+        "vintage_fork_alerts_total",
+        "peers found on a chain conflicting with the local one, by alert kind",
+        &["kind"]
+    )
+    .unwrap();
+    pub static ref WASM_DOWNLOADS_PENDING: IntGauge = register_int_gauge!(
+        "vintage_wasm_downloads_pending",
+        "wasm binaries being downloaded"
+    )
+    .unwrap();
+    pub static ref WASM_DOWNLOADED: IntCounter = register_int_counter!(
+        "vintage_wasm_downloaded_total",
+        "wasm binaries downloaded"
+    )
+    .unwrap();
+    pub static ref WASM_DOWNLOADED_BYTES: IntCounter = register_int_counter!(
+        "vintage_wasm_downloaded_bytes_total",
+        "bytes of the wasm binaries downloaded"
+    )
+    .unwrap();
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    // consensus
+
+    pub static ref CONSENSUS_VIEW_CHANGES: IntCounter = register_int_counter!(
+        "vintage_consensus_view_changes_total",
+        "view changes reported by the consensus"
+    )
+    .unwrap();
+    pub static ref CONSENSUS_ERRORS: IntCounter = register_int_counter!(
+        "vintage_consensus_errors_total",
+        "errors reported by the consensus"
+    )
+    .unwrap();
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    // network
+
+    pub static ref NETWORK_CONNECTED_PEERS: IntGauge =
+        register_int_gauge!("vintage_network_connected_peers", "peers connected").unwrap();
+    pub static ref NETWORK_RECEIVED_BYTES: IntCounterVec = register_int_counter_vec!(
+        "vintage_network_received_bytes_total",
+        "bytes received, by message type",
+        &["msg_type"]
+    )
+    .unwrap();
+    pub static ref NETWORK_SENT_BYTES: IntCounterVec = register_int_counter_vec!(
+        "vintage_network_sent_bytes_total",
+        "bytes sent, by message type",
+        &["msg_type"]
+    )
+    .unwrap();
+}
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use prometheus::{Encoder, TextEncoder};
+use tokio::net::TcpListener;
+use vintage_utils::Service;
+
+pub struct MetricsService;
+
+#[async_trait]
+impl Service for MetricsService {
+    type Input = TcpListener;
+    type Output = anyhow::Result<()>;
+
+    async fn service(self, listener: Self::Input) -> Self::Output {
+        let router = Router::new().route("/metrics", get(get_metrics));
+        axum::serve(listener, router).await?;
+        Ok(())
+    }
+}
+
+async fn get_metrics() -> impl IntoResponse {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    match encoder.encode(&prometheus::gather(), &mut buffer) {
+        Ok(()) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, encoder.format_type().to_string())],
+            buffer,
+        ),
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            [(header::CONTENT_TYPE, "text/plain".to_string())],
+            err.to_string().into_bytes(),
+        ),
+    }
+}
@@ -1,8 +1,94 @@
-use crate::{BlockHeight, Entity, Model, Proto};
+use crate::{
+    BlockHash, BlockHeight, BlockWithHash, Entity, EntityHash, EntityId, EntityProof,
+    EntityVersion, Model, Proto, TxInBlock, TxPoolContents, TxProof, TxReceipt, ValidatorNode,
+    WasmHash, WasmId,
+};
 use async_trait::async_trait;
+use std::fmt::{Display, Formatter};
+use vintage_utils::Hashed;
 
 #[async_trait]
 pub trait BlockChainApi {
     async fn get_block_height(&self) -> anyhow::Result<BlockHeight>;
     async fn check_entities(&self, proto: Proto, model: Model, entities: Vec<Entity>) -> bool;
+    async fn get_entity(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+    ) -> anyhow::Result<EntityHash>;
+    // the hash of the entity after the block at the height is committed
+    async fn get_entity_at(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+        height: BlockHeight,
+    ) -> anyhow::Result<EntityHash>;
+    // the versions at and after the height, at most 100
+    async fn entity_history(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+        begin_height: BlockHeight,
+    ) -> anyhow::Result<Vec<EntityVersion>>;
+    // returns the number of versions removed, the last version before the height is kept
+    async fn prune_entity_history(&self, before_height: BlockHeight) -> anyhow::Result<usize>;
+    // proof against the state root of the last block
+    async fn get_entity_proof(
+        &self,
+        proto: Proto,
+        model: Model,
+        entity_id: EntityId,
+    ) -> anyhow::Result<EntityProof>;
+    async fn get_tx_proof(&self, height: BlockHeight, tx_id: Hashed) -> anyhow::Result<TxProof>;
+    async fn get_block(&self, height: BlockHeight) -> anyhow::Result<BlockWithHash>;
+    async fn get_block_by_hash(&self, hash: BlockHash) -> anyhow::Result<BlockWithHash>;
+    async fn get_tx(&self, tx_id: Hashed) -> anyhow::Result<TxInBlock>;
+    // at most 100 blocks, from begin_height to end_height inclusive
+    async fn get_txs(
+        &self,
+        begin_height: BlockHeight,
+        end_height: BlockHeight,
+    ) -> anyhow::Result<Vec<TxInBlock>>;
+    async fn get_wasm_binary(&self, wasm_hash: WasmHash) -> anyhow::Result<Vec<u8>>;
+    // the wasm upgrades scheduled at and after the height, at most 100 heights
+    async fn get_upgrade_schedule(
+        &self,
+        begin_height: BlockHeight,
+    ) -> anyhow::Result<Vec<(BlockHeight, Vec<WasmId>)>>;
+    // at most 1000 txs of each kind
+    async fn get_pool_contents(&self) -> anyhow::Result<TxPoolContents>;
+    // the status of an act tx or an update entity tx submitted to the node
+    async fn get_tx_receipt(&self, tx_id: Hashed) -> anyhow::Result<TxReceipt>;
+    // the validator set of the block at the height
+    async fn get_validators(&self, height: BlockHeight) -> anyhow::Result<Vec<ValidatorNode>>;
 }
+
+// the errors the callers of the api can act on, the other errors are internal
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound(String),
+    InvalidArg(String),
+}
+
+impl ApiError {
+    pub fn not_found(msg: impl ToString) -> anyhow::Error {
+        anyhow::Error::new(Self::NotFound(msg.to_string()))
+    }
+
+    pub fn invalid_arg(msg: impl ToString) -> anyhow::Error {
+        anyhow::Error::new(Self::InvalidArg(msg.to_string()))
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::NotFound(msg) | ApiError::InvalidArg(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
@@ -1,8 +1,13 @@
-use crate::{ActTx, Block, BlockEvent, UpdateEntityTx, UploadWasm, WasmHash};
+use crate::{
+    ActTx, Block, BlockEvent, BlockHeight, Proto, TxReceipt, UpdateEntityTx, UploadWasm,
+    ValidatorNode, ValidatorSetTx, WasmHash,
+};
 use bytes::Bytes;
 use overlord::types::OverlordMsg;
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use vintage_utils::{Hashed, Timestamp};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 // blockchain
@@ -16,6 +21,7 @@ pub enum MsgToBlockChain {
     UpdateEntityTx(UpdateEntityTx),
     // from admin
     UploadWasm(UploadWasm),
+    ValidatorSetTx(ValidatorSetTx),
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -24,6 +30,7 @@ pub enum MsgToBlockChain {
 pub enum MsgToProxy {
     BlockEvent(BlockEvent),
     WasmBinary(WasmHash, Vec<u8>),
+    TxStatus(Proto, TxReceipt),
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -31,10 +38,36 @@ pub enum MsgToProxy {
 
 pub type OverlordMsgBlock = OverlordMsg<Block>;
 
+// the height and round the consensus is at, updated on commit and on view change
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConsensusRound {
+    pub height: BlockHeight,
+    pub round: u64,
+}
+
+pub type ArcConsensusRound = Arc<RwLock<ConsensusRound>>;
+
+// a round of the consensus, the reason is set if the round ends with a view change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusRoundRecord {
+    pub height: BlockHeight,
+    pub round: u64,
+    pub start_time: Timestamp,
+    pub proposer: Option<NodeId>,
+    pub view_change_reason: Option<String>,
+    // from the first round of the height
+    pub time_to_commit_ms: Option<u64>,
+    pub errors: Vec<String>,
+}
+
+// the recent rounds, oldest first
+pub type ArcConsensusRoundRecords = Arc<RwLock<VecDeque<ConsensusRoundRecord>>>;
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 // network
 
-pub type NodeId = SocketAddr;
+// sha256 of the bls public key of the node, the address of the node is only used for routing
+pub type NodeId = Hashed;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NetworkMsgHandler {
@@ -51,4 +84,6 @@ pub enum MsgToNetwork {
     Response(NodeId, NetworkRequestId, Vec<u8>),
     ConsensusBroadcast(OverlordMsgBlock),
     ConsensusMsgRelay(Bytes, OverlordMsgBlock),
+    // connect to the new validators and drop the removed ones
+    UpdateValidators(Vec<ValidatorNode>),
 }
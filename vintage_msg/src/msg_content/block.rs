@@ -1,20 +1,160 @@
-use crate::{ActTx, UpdateEntityTx, WasmTx};
+use crate::{ActTx, NodeId, UpdateEntityTx, ValidatorSetTx, WasmTx};
+use anyhow::anyhow;
 use bytes::Bytes;
+use digest::Digest;
 use overlord::Codec;
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::error::Error;
-use vintage_utils::Hashed;
+use vintage_utils::{BlsPublicKey, BlsSignature, CalcHash, Hashed};
 
 pub type BlockHeight = u64;
 pub type BlockTimestamp = u64;
 pub type BlockHash = Hashed;
 
+// the block hash is the hash of the header
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct Block {
+pub struct BlockHeader {
+    pub height: BlockHeight,
+    pub prev_hash: BlockHash,
+    pub proposer: NodeId,
     pub timestamp: BlockTimestamp,
+    pub total_act_txs: u64, // 全区块链的act总数，不是当前块的act数量
+    // merkle root of the act tx ids, ue tx ids, wasm txs and validator set tx, in that order
+    pub tx_root: Hashed,
+    // root of the entity state tree after the block
+    pub state_root: Hashed,
+    // hash of the randomness proof
+    pub randomness: Hashed,
+    // bls signature of the proposer over the randomness of the previous block,
+    // unique for the key and the message, so the proposer can not choose the randomness
+    pub randomness_proof: Vec<u8>,
+}
+
+impl BlockHeader {
+    pub fn randomness_msg(height: BlockHeight, prev_randomness: &Hashed) -> Vec<u8> {
+        let mut msg = b"vintage-randomness".to_vec();
+        msg.extend_from_slice(&height.to_be_bytes());
+        msg.extend_from_slice(prev_randomness.as_ref());
+        msg
+    }
+
+    pub fn verify_randomness(
+        &self,
+        prev_randomness: &Hashed,
+        proposer_key: &BlsPublicKey,
+    ) -> anyhow::Result<()> {
+        if proposer_key.calc_hash() != self.proposer {
+            return Err(anyhow!("block {} proposer key mismatch", self.height));
+        }
+        if self.randomness_proof.calc_hash() != self.randomness {
+            return Err(anyhow!("block {} randomness mismatch", self.height));
+        }
+        BlsSignature::from_bytes(&self.randomness_proof)?.verify(
+            &Self::randomness_msg(self.height, prev_randomness),
+            proposer_key,
+        )
+    }
+}
+
+impl CalcHash for BlockHeader {
+    fn calc_hash(&self) -> Hashed {
+        let mut hasher = Sha256::new();
+        hasher.update(self.height.to_be_bytes());
+        hasher.update(&self.prev_hash);
+        hasher.update(&self.proposer);
+        hasher.update(self.timestamp.to_be_bytes());
+        hasher.update(self.total_act_txs.to_be_bytes());
+        hasher.update(&self.tx_root);
+        hasher.update(&self.state_root);
+        hasher.update(&self.randomness);
+        hasher.update(&self.randomness_proof);
+        hasher.into()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockBody {
     pub act_txs: Vec<ActTx>,
     pub ue_txs: Vec<UpdateEntityTx>,
     pub wasm_txs: Vec<WasmTx>,
+    pub validator_set_tx: Option<ValidatorSetTx>,
+}
+
+impl BlockBody {
+    // items of the tx merkle tree
+    pub fn tx_items(&self) -> Vec<Hashed> {
+        let mut items =
+            Vec::with_capacity(self.act_txs.len() + self.ue_txs.len() + self.wasm_txs.len() + 1);
+        items.extend(self.act_txs.iter().map(|act_tx| act_tx.calc_hash()));
+        items.extend(self.ue_txs.iter().map(|ue_tx| ue_tx.calc_hash()));
+        items.extend(self.wasm_txs.iter().map(|wasm_tx| wasm_tx.calc_hash()));
+        if let Some(validator_set_tx) = &self.validator_set_tx {
+            items.push(validator_set_tx.calc_hash());
+        }
+        items
+    }
+
+    // in the order of the tx merkle tree items
+    pub fn into_txs(self) -> Vec<(Hashed, BlockTx)> {
+        let mut txs = Vec::new();
+        for act_tx in self.act_txs {
+            txs.push((act_tx.calc_hash(), BlockTx::Act(act_tx)));
+        }
+        for ue_tx in self.ue_txs {
+            txs.push((ue_tx.calc_hash(), BlockTx::UpdateEntity(ue_tx)));
+        }
+        for wasm_tx in self.wasm_txs {
+            txs.push((wasm_tx.calc_hash(), BlockTx::Wasm(wasm_tx)));
+        }
+        if let Some(validator_set_tx) = self.validator_set_tx {
+            txs.push((
+                validator_set_tx.calc_hash(),
+                BlockTx::ValidatorSet(validator_set_tx),
+            ));
+        }
+        txs
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Block {
+    pub header: BlockHeader,
+    pub body: BlockBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockWithHash {
+    pub hash: BlockHash,
+    pub block: Block,
+}
+
+// the commit proof of the consensus, the aggregated precommit signature of the validators
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockProof {
+    pub height: BlockHeight,
+    pub round: u64,
+    pub block_hash: BlockHash,
+    pub signature: Vec<u8>,
+    // bit i is set if the i-th validator voted, the validators sorted by node id
+    pub voter_bitmap: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockTx {
+    Act(ActTx),
+    UpdateEntity(UpdateEntityTx),
+    Wasm(WasmTx),
+    ValidatorSet(ValidatorSetTx),
+}
+
+// a tx with the height of the block including it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxInBlock {
+    pub tx_id: Hashed,
+    pub height: BlockHeight,
+    pub tx: BlockTx,
 }
 
 macro_rules! impl_codec_for {
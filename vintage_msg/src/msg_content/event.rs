@@ -4,6 +4,7 @@ use vintage_utils::{Hashed, Timestamp};
 pub struct BlockEvent {
     pub height: BlockHeight,
     pub timestamp: Timestamp,
+    // the acts of the previous block, drawing their randomness from this block
     pub act_events: Vec<ActEvent>,
     pub ue_events: Vec<UpdateEntityEvent>,
     pub upgrade_wasm_ids: Vec<WasmId>,
@@ -11,6 +12,8 @@ pub struct BlockEvent {
 
 pub struct ActEvent {
     pub act_tx: ActTx,
+    // the timestamp of the block including the act
+    pub timestamp: Timestamp,
     pub act_number: u64,
     pub random: Hashed,
 }
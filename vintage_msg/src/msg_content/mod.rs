@@ -1,9 +1,11 @@
 mod admin;
 mod block;
 mod event;
+mod proof;
 mod tx;
 
 pub use self::admin::*;
 pub use self::block::*;
 pub use self::event::*;
+pub use self::proof::*;
 pub use self::tx::*;
@@ -0,0 +1,89 @@
+use crate::{BlockHash, BlockHeader, EntityHash, EntityId, Model, Proto};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use vintage_utils::{merkle_verify, CalcHash, Hashed, MerklePath, SmtProof};
+
+// The proofs carry the block header, the verifier gets the block hash from a source it trusts
+// (e.g. the other nodes) and checks the header against it, then the merkle path against the root.
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// entity
+
+pub fn entity_key(proto: &Proto, model: &Model, entity_id: &EntityId) -> String {
+    format!("{}:{}:{}", proto, model, entity_id)
+}
+
+// key and value of the entity state tree
+pub fn entity_state_key(proto: &Proto, model: &Model, entity_id: &EntityId) -> Hashed {
+    entity_key(proto, model, entity_id).as_bytes().calc_hash()
+}
+
+pub fn entity_state_value(hash: &EntityHash) -> Hashed {
+    hash.as_bytes().calc_hash()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityProof {
+    pub header: BlockHeader,
+    pub proto: Proto,
+    pub model: Model,
+    pub entity_id: EntityId,
+    pub entity_hash: EntityHash,
+    pub proof: SmtProof,
+}
+
+impl EntityProof {
+    pub fn verify(&self, block_hash: &BlockHash) -> anyhow::Result<()> {
+        check_header(&self.header, block_hash)?;
+        let key = entity_state_key(&self.proto, &self.model, &self.entity_id);
+        let value = entity_state_value(&self.entity_hash);
+        if self
+            .proof
+            .verify(&key, Some(&value), &self.header.state_root)
+        {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "entity {} {} not in state root of block {}",
+                self.model,
+                self.entity_id,
+                self.header.height
+            ))
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// tx
+
+// proves that an act tx, ue tx, wasm tx or validator set tx is included in the block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxProof {
+    pub header: BlockHeader,
+    pub tx_id: Hashed,
+    pub path: MerklePath,
+}
+
+impl TxProof {
+    pub fn verify(&self, block_hash: &BlockHash) -> anyhow::Result<()> {
+        check_header(&self.header, block_hash)?;
+        if merkle_verify(&self.tx_id, &self.path, &self.header.tx_root) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "tx {} not in tx root of block {}",
+                self.tx_id,
+                self.header.height
+            ))
+        }
+    }
+}
+
+fn check_header(header: &BlockHeader, block_hash: &BlockHash) -> anyhow::Result<()> {
+    let calc_hash = header.calc_hash();
+    if calc_hash == *block_hash {
+        Ok(())
+    } else {
+        Err(anyhow!("block hash, {} != {}", block_hash, calc_hash))
+    }
+}
@@ -1,6 +1,7 @@
 use digest::Digest;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
+use std::net::SocketAddr;
 use vintage_utils::{CalcHash, Hashed};
 
 pub type Action = String;
@@ -65,6 +66,47 @@ impl CalcHash for UpdateEntityTx {
     }
 }
 
+// a write of the entity, by the ue tx committed at the height
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityVersion {
+    pub height: u64,
+    pub ue_tx_id: Hashed,
+    pub req_id: ReqId,
+    pub hash: EntityHash,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// pool
+
+// the txs waiting in the pools of the node
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxPoolContents {
+    pub act_txs: Vec<ActTx>,
+    pub ue_txs: Vec<UpdateEntityTx>,
+    pub wasm_txs: Vec<WasmTx>,
+    pub validator_set_tx: Option<ValidatorSetTx>,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// receipt
+
+// pending in the pool, then included at the height, rejected with the reason or expired in the pool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxStatus {
+    Pending,
+    Included(u64),
+    Rejected(String),
+    Expired,
+}
+
+// the tx id is returned to the caller as tx_submitted when the tx is submitted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxReceipt {
+    pub tx_id: Hashed,
+    pub status: TxStatus,
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 // wasm
 
@@ -86,3 +128,92 @@ pub struct WasmTx {
     pub wasm_id: WasmId,
     pub wasm_info: WasmInfo,
 }
+
+impl CalcHash for WasmTx {
+    fn calc_hash(&self) -> Hashed {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.wasm_id.proto);
+        hasher.update(&self.wasm_id.wasm_hash);
+        hasher.update(self.wasm_info.block_interval.to_be_bytes());
+        hasher.into()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// validator
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorNode {
+    pub id: u16,
+    pub name: String,
+    pub address: SocketAddr,
+    pub public_key: String, // hex encoded bls public key
+    pub pop: String,        // hex encoded proof of possession of the bls key
+    pub propose_weight: u32,
+    pub vote_weight: u32,
+}
+
+// replaces the whole validator set, effective from (commit height + block_interval).
+// approved by the validators weighing more than 2/3 of the current validator set
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorSetTx {
+    pub validators: Vec<ValidatorNode>,
+    pub block_interval: u64,
+    // the number of validator set txs committed before it, so an approval is used once
+    pub nonce: u64,
+    pub approvals: Vec<ValidatorSetApproval>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorSetApproval {
+    pub public_key: String, // hex encoded bls public key of a current validator
+    pub signature: String,  // hex encoded bls signature of the approval msg
+}
+
+impl ValidatorSetTx {
+    // the change, its nonce and the current validator set, an approval is not valid after the set
+    // is changed, and not again when the set changes back
+    pub fn approval_msg(&self, current_validators: &[ValidatorNode]) -> Hashed {
+        let mut hasher = Sha256::new();
+        hasher.update(b"vintage-validator-set");
+        hash_validators(&mut hasher, &self.validators);
+        hasher.update(self.block_interval.to_be_bytes());
+        hasher.update(self.nonce.to_be_bytes());
+        hash_validators(&mut hasher, current_validators);
+        hasher.into()
+    }
+}
+
+impl CalcHash for ValidatorSetTx {
+    fn calc_hash(&self) -> Hashed {
+        let mut hasher = Sha256::new();
+        hash_validators(&mut hasher, &self.validators);
+        hasher.update(self.block_interval.to_be_bytes());
+        hasher.update(self.nonce.to_be_bytes());
+        hasher.update((self.approvals.len() as u64).to_be_bytes());
+        for approval in &self.approvals {
+            hash_str(&mut hasher, &approval.public_key);
+            hash_str(&mut hasher, &approval.signature);
+        }
+        hasher.into()
+    }
+}
+
+// the lengths are hashed with the strings and the list, so different sets never hash the same
+fn hash_validators(hasher: &mut Sha256, validators: &[ValidatorNode]) {
+    hasher.update((validators.len() as u64).to_be_bytes());
+    for validator in validators {
+        hasher.update(validator.id.to_be_bytes());
+        hash_str(hasher, &validator.name);
+        hash_str(hasher, &validator.address.to_string());
+        hash_str(hasher, &validator.public_key);
+        hash_str(hasher, &validator.pop);
+        hasher.update(validator.propose_weight.to_be_bytes());
+        hasher.update(validator.vote_weight.to_be_bytes());
+    }
+}
+
+fn hash_str(hasher: &mut Sha256, s: &str) {
+    hasher.update((s.len() as u64).to_be_bytes());
+    hasher.update(s);
+}
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use vintage_msg::{MsgToNetwork, NetworkMsgHandler, NodeId};
-use vintage_utils::SendMsg;
+use vintage_utils::{Quorum, SendMsg};
 
 pub type DynNetworkResponse = Arc<dyn NetworkResponseIO>;
 pub type DynNetworkResponseReader = Arc<dyn NetworkResponseReader>;
@@ -70,14 +70,9 @@ impl NetworkClient {
         handler: NetworkMsgHandler,
         content: Vec<u8>,
         timeout: Duration,
-        node_count: usize,
+        quorum: Quorum<NodeId>,
     ) -> anyhow::Result<(Vec<NodeId>, Vec<u8>)> {
-        let (request_id, response) = {
-            self.request_mgr
-                .lock()
-                .unwrap()
-                .request_with_vote(node_count)
-        };
+        let (request_id, response) = { self.request_mgr.lock().unwrap().request_with_vote(quorum) };
         self.network_msg_sender
             .send_msg(MsgToNetwork::RequestBroadcast(handler, request_id, content));
         let result = response.read_data(timeout).await;
@@ -1,8 +1,64 @@
 use crate::messages::NetworkMessage;
 use bytes::{Buf, BufMut, BytesMut};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
 use tokio_util::codec::{Decoder, Encoder};
+use vintage_metrics::{NETWORK_RECEIVED_BYTES, NETWORK_SENT_BYTES};
 
-pub(crate) struct BlockchainCodec;
+pub(crate) const SESSION_KEY_SIZE: usize = 32;
+// the largest messages are the wasm binaries and the block ranges
+const MAX_FRAME_SIZE: usize = 64 * 1024 * 1024;
+
+// keys derived by the handshake, one for each direction, nonces are frame counters
+pub(crate) struct SessionCipher {
+    send_cipher: ChaCha20Poly1305,
+    send_nonce: u64,
+    recv_cipher: ChaCha20Poly1305,
+    recv_nonce: u64,
+}
+
+impl SessionCipher {
+    pub fn new(send_key: &[u8; SESSION_KEY_SIZE], recv_key: &[u8; SESSION_KEY_SIZE]) -> Self {
+        Self {
+            send_cipher: ChaCha20Poly1305::new(Key::from_slice(send_key)),
+            send_nonce: 0,
+            recv_cipher: ChaCha20Poly1305::new(Key::from_slice(recv_key)),
+            recv_nonce: 0,
+        }
+    }
+
+    fn encrypt(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+        let nonce = nonce_bytes(self.send_nonce);
+        self.send_nonce += 1;
+        self.send_cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "encrypt err"))
+    }
+
+    fn decrypt(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+        let nonce = nonce_bytes(self.recv_nonce);
+        self.recv_nonce += 1;
+        self.recv_cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext)
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "decrypt err"))
+    }
+}
+
+fn nonce_bytes(counter: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&counter.to_be_bytes());
+    nonce
+}
+
+pub(crate) struct BlockchainCodec {
+    cipher: SessionCipher,
+}
+
+impl BlockchainCodec {
+    pub fn new(cipher: SessionCipher) -> Self {
+        Self { cipher }
+    }
+}
 
 impl Decoder for BlockchainCodec {
     type Item = NetworkMessage;
@@ -16,16 +72,28 @@ impl Decoder for BlockchainCodec {
         let mut length_bytes = [0u8; 4];
         length_bytes.copy_from_slice(&src[..4]);
         let length = u32::from_be_bytes(length_bytes) as usize;
+        if length > MAX_FRAME_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("frame too large: {}", length),
+            ));
+        }
 
         if src.len() < 4 + length {
             return Ok(None);
         }
 
         src.advance(4);
-        let message_bytes = src.split_to(length);
+        let encrypted_bytes = src.split_to(length);
+        let message_bytes = self.cipher.decrypt(&encrypted_bytes)?;
 
-        match bincode::deserialize(&message_bytes) {
-            Ok(message) => Ok(Some(message)),
+        match bincode::deserialize::<NetworkMessage>(&message_bytes) {
+            Ok(message) => {
+                NETWORK_RECEIVED_BYTES
+                    .with_label_values(&[message.payload.msg_type()])
+                    .inc_by(4 + length as u64);
+                Ok(Some(message))
+            }
             Err(e) => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
         }
     }
@@ -37,10 +105,99 @@ impl Encoder<NetworkMessage> for BlockchainCodec {
     fn encode(&mut self, item: NetworkMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
         let message_bytes = bincode::serialize(&item)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let encrypted_bytes = self.cipher.encrypt(&message_bytes)?;
+        if encrypted_bytes.len() > MAX_FRAME_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("frame too large: {}", encrypted_bytes.len()),
+            ));
+        }
 
-        dst.put_u32(message_bytes.len() as u32);
-        dst.extend_from_slice(&message_bytes);
+        dst.put_u32(encrypted_bytes.len() as u32);
+        dst.extend_from_slice(&encrypted_bytes);
+        NETWORK_SENT_BYTES
+            .with_label_values(&[item.payload.msg_type()])
+            .inc_by(4 + encrypted_bytes.len() as u64);
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::{NetworkBroadcast, NetworkMessageContent};
+    use vintage_msg::{NetworkMsgHandler, NodeId};
+
+    // the codecs of the two ends of a session
+    fn codec_pair() -> (BlockchainCodec, BlockchainCodec) {
+        let (key_a, key_b) = ([1; SESSION_KEY_SIZE], [2; SESSION_KEY_SIZE]);
+        (
+            BlockchainCodec::new(SessionCipher::new(&key_a, &key_b)),
+            BlockchainCodec::new(SessionCipher::new(&key_b, &key_a)),
+        )
+    }
+
+    fn message(content: &[u8]) -> NetworkMessage {
+        NetworkMessage {
+            sender: NodeId::zero_hash(),
+            receiver: None,
+            payload: NetworkMessageContent::Broadcast(NetworkBroadcast {
+                handler: NetworkMsgHandler::BlockChain,
+                broadcast_content: content.to_vec(),
+            }),
+        }
+    }
+
+    fn content_of(message: NetworkMessage) -> Vec<u8> {
+        match message.payload {
+            NetworkMessageContent::Broadcast(broadcast) => broadcast.broadcast_content,
+            _ => panic!("not a broadcast"),
+        }
+    }
+
+    fn encode(codec: &mut BlockchainCodec, content: &[u8]) -> BytesMut {
+        let mut frame = BytesMut::new();
+        codec.encode(message(content), &mut frame).unwrap();
+        frame
+    }
+
+    #[test]
+    fn frames_are_encrypted() {
+        let (mut codec_a, mut codec_b) = codec_pair();
+        let mut frame = encode(&mut codec_a, b"plaintext content");
+        assert!(!frame
+            .windows(17)
+            .any(|window| window == b"plaintext content"));
+        let message = codec_b.decode(&mut frame).unwrap().unwrap();
+        assert_eq!(content_of(message), b"plaintext content");
+        assert!(frame.is_empty());
+    }
+
+    #[test]
+    fn tampered_frame_rejected() {
+        let (mut codec_a, mut codec_b) = codec_pair();
+        let mut frame = encode(&mut codec_a, b"content");
+        let last = frame.len() - 1;
+        frame[last] ^= 1;
+        assert!(codec_b.decode(&mut frame).is_err());
+    }
+
+    #[test]
+    fn replayed_frame_rejected() {
+        let (mut codec_a, mut codec_b) = codec_pair();
+        let mut frame = encode(&mut codec_a, b"content");
+        let mut replayed = frame.clone();
+        assert!(codec_b.decode(&mut frame).unwrap().is_some());
+        // the nonce of the frame was used, the receiver expects the next one
+        assert!(codec_b.decode(&mut replayed).is_err());
+    }
+
+    #[test]
+    fn frame_of_the_other_direction_rejected() {
+        let (mut codec_a, _) = codec_pair();
+        let mut frame = encode(&mut codec_a, b"content");
+        // reflected to the sender, which decrypts with the key of the other direction
+        assert!(codec_a.decode(&mut frame).is_err());
+    }
+}
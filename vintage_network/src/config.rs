@@ -1,6 +1,9 @@
 use crate::peer_manager::PeerInfo;
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
+use vintage_msg::{NodeId, ValidatorNode};
+use vintage_utils::{BlsPrivateKey, BlsSignature, Quorum};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NodeConfig {
@@ -8,15 +11,54 @@ pub struct NodeConfig {
     pub id: u16,
     pub name: String,
     pub listen_addr: SocketAddr,
+    pub key_path: String,
+    pub wal_path: String,
     pub peers: Vec<PeerInfo>,
+    // hex encoded bls public keys of the non-validator nodes allowed to connect
+    #[serde(default)]
+    pub allowed_observers: Vec<String>,
     pub propose_weight: u32,
     pub vote_weight: u32,
 }
 
 impl NodeConfig {
-    pub fn get_number_of_node(&self) -> usize {
-        // Add peer nodes
-        let number_of_peers = self.peers.len();
-        number_of_peers + 1
+    // the quorum of the network votes, over the validators in the config but this node,
+    // which does not answer its own requests
+    pub fn peer_quorum(&self) -> anyhow::Result<Quorum<NodeId>> {
+        let mut weights = Vec::with_capacity(self.peers.len());
+        for peer in &self.peers {
+            weights.push((peer.node_id()?, peer.vote_weight));
+        }
+        Ok(Quorum::new(weights))
+    }
+
+    // the validators in the config, this node included, the keys of the peers must come with
+    // their proofs of possession, the aggregated commit signatures are not safe otherwise
+    pub fn validators(&self, private_key: &BlsPrivateKey) -> anyhow::Result<Vec<ValidatorNode>> {
+        let mut validators = vec![ValidatorNode {
+            id: self.id,
+            name: self.name.clone(),
+            address: self.listen_addr,
+            public_key: private_key.public_key().to_string(),
+            pop: private_key.prove_possession().to_string(),
+            propose_weight: self.propose_weight,
+            vote_weight: self.vote_weight,
+        }];
+        for peer in &self.peers {
+            let public_key = peer.public_key()?;
+            BlsSignature::from_hex(&peer.pop)
+                .and_then(|pop| pop.verify_possession(&public_key))
+                .map_err(|err| anyhow!("pop of peer {} err: {}", peer.name, err))?;
+            validators.push(ValidatorNode {
+                id: peer.id,
+                name: peer.name.clone(),
+                address: peer.address,
+                public_key: peer.public_key.clone(),
+                pop: peer.pop.clone(),
+                propose_weight: peer.propose_weight,
+                vote_weight: peer.vote_weight,
+            });
+        }
+        Ok(validators)
     }
 }
@@ -0,0 +1,353 @@
+use crate::codec::{SessionCipher, SESSION_KEY_SIZE};
+use anyhow::anyhow;
+use hkdf::Hkdf;
+use rand::rngs::OsRng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use vintage_msg::NodeId;
+use vintage_utils::{BlsPrivateKey, BlsPublicKey, BlsSignature, CalcHash};
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+const HANDSHAKE_PROTOCOL: &[u8] = b"vintage-handshake-v1";
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_HANDSHAKE_MSG_SIZE: usize = 1024;
+
+// Both sides send Hello then Auth, no matter which side dialed.
+// The transcript binds the ephemeral keys and the node keys of both sides, each side signs it
+// with its bls key to prove possession, the session keys are derived from the x25519 secret.
+
+#[derive(Serialize, Deserialize)]
+struct Hello {
+    ephemeral_key: [u8; 32],
+    public_key: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Auth {
+    signature: Vec<u8>,
+}
+
+pub(crate) struct PeerAuthenticator {
+    private_key: BlsPrivateKey,
+    // changed with the on-chain validator set
+    validator_nodes: RwLock<HashSet<NodeId>>,
+    observer_nodes: HashSet<NodeId>,
+}
+
+impl PeerAuthenticator {
+    pub fn new(
+        private_key: BlsPrivateKey,
+        validator_nodes: HashSet<NodeId>,
+        observer_nodes: HashSet<NodeId>,
+    ) -> Self {
+        Self {
+            private_key,
+            validator_nodes: RwLock::new(validator_nodes),
+            observer_nodes,
+        }
+    }
+
+    pub fn set_validator_nodes(&self, validator_nodes: HashSet<NodeId>) {
+        *self.validator_nodes.write().unwrap() = validator_nodes;
+    }
+
+    pub fn is_allowed(&self, node_id: &NodeId) -> bool {
+        self.observer_nodes.contains(node_id)
+            || self.validator_nodes.read().unwrap().contains(node_id)
+    }
+
+    // the node dialed, if any, must be the node proven by the handshake
+    pub async fn handshake(
+        &self,
+        socket: &mut TcpStream,
+        dialed_node_id: Option<&NodeId>,
+    ) -> anyhow::Result<(NodeId, SessionCipher)> {
+        tokio::time::timeout(
+            HANDSHAKE_TIMEOUT,
+            self.handshake_inner(socket, dialed_node_id),
+        )
+        .await
+        .map_err(|_| anyhow!("handshake timeout"))?
+    }
+
+    async fn handshake_inner(
+        &self,
+        socket: &mut TcpStream,
+        dialed_node_id: Option<&NodeId>,
+    ) -> anyhow::Result<(NodeId, SessionCipher)> {
+        let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+        let local_hello = Hello {
+            ephemeral_key: PublicKey::from(&ephemeral_secret).to_bytes(),
+            public_key: self.private_key.public_key().to_bytes().to_vec(),
+        };
+        write_msg(socket, &local_hello).await?;
+        let remote_hello: Hello = read_msg(socket).await?;
+
+        if remote_hello.public_key == local_hello.public_key {
+            return Err(anyhow!("connection with the own key of the node"));
+        }
+        let remote_public_key = BlsPublicKey::from_bytes(&remote_hello.public_key)?;
+        let node_id = remote_public_key.calc_hash();
+        if !self.is_allowed(&node_id) {
+            return Err(anyhow!("node {} is not allowed", node_id));
+        }
+        if dialed_node_id.is_some_and(|dialed_node_id| *dialed_node_id != node_id) {
+            return Err(anyhow!(
+                "node {} answered instead of the node dialed",
+                node_id
+            ));
+        }
+
+        let local_first = local_hello.ephemeral_key < remote_hello.ephemeral_key;
+        let transcript = if local_first {
+            transcript_hash(&local_hello, &remote_hello)
+        } else {
+            transcript_hash(&remote_hello, &local_hello)
+        };
+
+        // prove possession of the node key
+        let signature = self
+            .private_key
+            .sign(&auth_msg(&transcript, &local_hello.ephemeral_key));
+        write_msg(
+            socket,
+            &Auth {
+                signature: signature.to_bytes().to_vec(),
+            },
+        )
+        .await?;
+        let remote_auth: Auth = read_msg(socket).await?;
+        BlsSignature::from_bytes(&remote_auth.signature)?
+            .verify(
+                &auth_msg(&transcript, &remote_hello.ephemeral_key),
+                &remote_public_key,
+            )
+            .map_err(|err| anyhow!("auth of node {} err: {}", node_id, err))?;
+
+        // session keys
+        let shared_secret =
+            ephemeral_secret.diffie_hellman(&PublicKey::from(remote_hello.ephemeral_key));
+        let hkdf = Hkdf::<Sha256>::new(Some(&transcript), shared_secret.as_bytes());
+        let mut okm = [0u8; SESSION_KEY_SIZE * 2];
+        hkdf.expand(HANDSHAKE_PROTOCOL, &mut okm)
+            .map_err(|_| anyhow!("hkdf expand err"))?;
+        let mut first_key = [0u8; SESSION_KEY_SIZE];
+        let mut second_key = [0u8; SESSION_KEY_SIZE];
+        first_key.copy_from_slice(&okm[..SESSION_KEY_SIZE]);
+        second_key.copy_from_slice(&okm[SESSION_KEY_SIZE..]);
+        let cipher = if local_first {
+            SessionCipher::new(&first_key, &second_key)
+        } else {
+            SessionCipher::new(&second_key, &first_key)
+        };
+
+        Ok((node_id, cipher))
+    }
+}
+
+fn transcript_hash(first: &Hello, second: &Hello) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(HANDSHAKE_PROTOCOL);
+    for hello in [first, second] {
+        hasher.update(hello.ephemeral_key);
+        hasher.update(&hello.public_key);
+    }
+    hasher.finalize().into()
+}
+
+fn auth_msg(transcript: &[u8; 32], ephemeral_key: &[u8; 32]) -> Vec<u8> {
+    let mut msg = Vec::with_capacity(64);
+    msg.extend_from_slice(transcript);
+    msg.extend_from_slice(ephemeral_key);
+    msg
+}
+
+async fn write_msg<T: Serialize>(socket: &mut TcpStream, msg: &T) -> anyhow::Result<()> {
+    let bytes = bincode::serialize(msg)?;
+    socket.write_u32(bytes.len() as u32).await?;
+    socket.write_all(&bytes).await?;
+    Ok(())
+}
+
+async fn read_msg<T: DeserializeOwned>(socket: &mut TcpStream) -> anyhow::Result<T> {
+    let length = socket.read_u32().await? as usize;
+    if length > MAX_HANDSHAKE_MSG_SIZE {
+        return Err(anyhow!("handshake msg too large: {}", length));
+    }
+    let mut bytes = vec![0u8; length];
+    socket.read_exact(&mut bytes).await?;
+    Ok(bincode::deserialize(&bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::BlockchainCodec;
+    use crate::messages::{NetworkBroadcast, NetworkMessage, NetworkMessageContent};
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio_util::codec::Framed;
+    use vintage_msg::NetworkMsgHandler;
+
+    fn node() -> (BlsPrivateKey, NodeId) {
+        let private_key = BlsPrivateKey::generate();
+        let node_id = private_key.public_key().calc_hash();
+        (private_key, node_id)
+    }
+
+    fn authenticator(private_key: BlsPrivateKey, allowed: &[&NodeId]) -> PeerAuthenticator {
+        let validator_nodes = allowed.iter().map(|node_id| (*node_id).clone()).collect();
+        PeerAuthenticator::new(private_key, validator_nodes, HashSet::new())
+    }
+
+    type HandshakeResult = anyhow::Result<(NodeId, SessionCipher, TcpStream)>;
+
+    // the handshake results of the listening and the dialing node
+    async fn connect(
+        listener: PeerAuthenticator,
+        dialer: PeerAuthenticator,
+        dialed_node_id: Option<NodeId>,
+    ) -> (HandshakeResult, HandshakeResult) {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = tcp_listener.local_addr().unwrap();
+        let accepted = tokio::spawn(async move {
+            let (mut socket, _) = tcp_listener.accept().await.unwrap();
+            let (node_id, cipher) = listener.handshake(&mut socket, None).await?;
+            anyhow::Ok((node_id, cipher, socket))
+        });
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let dialed = dialer
+            .handshake(&mut socket, dialed_node_id.as_ref())
+            .await
+            .map(|(node_id, cipher)| (node_id, cipher, socket));
+        (accepted.await.unwrap(), dialed)
+    }
+
+    fn message(sender: &NodeId, content: &[u8]) -> NetworkMessage {
+        NetworkMessage {
+            sender: sender.clone(),
+            receiver: None,
+            payload: NetworkMessageContent::Broadcast(NetworkBroadcast {
+                handler: NetworkMsgHandler::BlockChain,
+                broadcast_content: content.to_vec(),
+            }),
+        }
+    }
+
+    fn content_of(message: NetworkMessage) -> Vec<u8> {
+        match message.payload {
+            NetworkMessageContent::Broadcast(broadcast) => broadcast.broadcast_content,
+            _ => panic!("not a broadcast"),
+        }
+    }
+
+    #[tokio::test]
+    async fn handshake_between_two_nodes() {
+        let (key_a, node_a) = node();
+        let (key_b, node_b) = node();
+        let (accepted, dialed) = connect(
+            authenticator(key_a, &[&node_b]),
+            authenticator(key_b, &[&node_a]),
+            Some(node_a.clone()),
+        )
+        .await;
+        let (proven_b, cipher_a, socket_a) = accepted.unwrap();
+        let (proven_a, cipher_b, socket_b) = dialed.unwrap();
+        assert_eq!(proven_b, node_b);
+        assert_eq!(proven_a, node_a);
+
+        // the session keys of both sides match, in both directions
+        let mut framed_a = Framed::new(socket_a, BlockchainCodec::new(cipher_a));
+        let mut framed_b = Framed::new(socket_b, BlockchainCodec::new(cipher_b));
+        for n in 0..3u8 {
+            framed_a.send(message(&node_a, &[n])).await.unwrap();
+            let received = framed_b.next().await.unwrap().unwrap();
+            assert_eq!(received.sender, node_a);
+            assert_eq!(content_of(received), vec![n]);
+
+            framed_b.send(message(&node_b, &[n, n])).await.unwrap();
+            let received = framed_a.next().await.unwrap().unwrap();
+            assert_eq!(content_of(received), vec![n, n]);
+        }
+    }
+
+    #[tokio::test]
+    async fn peer_not_allowed_rejected() {
+        let (key_a, node_a) = node();
+        let (key_b, _) = node();
+        // node a does not know node b
+        let (accepted, _) = connect(
+            authenticator(key_a, &[]),
+            authenticator(key_b, &[&node_a]),
+            Some(node_a.clone()),
+        )
+        .await;
+        let err = accepted.err().unwrap().to_string();
+        assert!(err.contains("is not allowed"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn other_node_than_the_one_dialed_rejected() {
+        let (key_a, node_a) = node();
+        let (key_b, node_b) = node();
+        let (_, node_c) = node();
+        let (_, dialed) = connect(
+            authenticator(key_a, &[&node_b]),
+            authenticator(key_b, &[&node_a, &node_c]),
+            Some(node_c),
+        )
+        .await;
+        let err = dialed.err().unwrap().to_string();
+        assert!(err.contains("instead of the node dialed"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn peer_without_the_private_key_rejected() {
+        let (key_a, _) = node();
+        let (key_b, node_b) = node();
+        let (key_c, _) = node();
+
+        // node c claims the key of node b, but signs the transcript with its own key
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = tcp_listener.local_addr().unwrap();
+        let impostor = tokio::spawn(async move {
+            let (mut socket, _) = tcp_listener.accept().await.unwrap();
+            let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+            let hello = Hello {
+                ephemeral_key: PublicKey::from(&ephemeral_secret).to_bytes(),
+                public_key: key_b.public_key().to_bytes().to_vec(),
+            };
+            write_msg(&mut socket, &hello).await.unwrap();
+            let remote_hello: Hello = read_msg(&mut socket).await.unwrap();
+            let transcript = if hello.ephemeral_key < remote_hello.ephemeral_key {
+                transcript_hash(&hello, &remote_hello)
+            } else {
+                transcript_hash(&remote_hello, &hello)
+            };
+            let signature = key_c.sign(&auth_msg(&transcript, &hello.ephemeral_key));
+            write_msg(
+                &mut socket,
+                &Auth {
+                    signature: signature.to_bytes().to_vec(),
+                },
+            )
+            .await
+            .unwrap();
+            let _ = read_msg::<Auth>(&mut socket).await;
+        });
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let result = authenticator(key_a, &[&node_b])
+            .handshake(&mut socket, Some(&node_b))
+            .await;
+        let err = result.err().unwrap().to_string();
+        assert!(err.contains("auth of node"), "{}", err);
+        impostor.await.unwrap();
+    }
+}
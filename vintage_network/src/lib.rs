@@ -1,36 +1,43 @@
 pub mod client;
 pub mod codec;
 pub mod config;
+mod handshake;
 pub mod messages;
-mod peer_manager;
+pub mod peer_manager;
 pub mod request;
 mod response;
 
+use crate::handshake::PeerAuthenticator;
 use crate::messages::{NetworkBroadcast, NetworkMessageContent, NetworkRequest, NetworkResponse};
 use crate::request::ArcNetworkRequestMgr;
-use bytes::Bytes;
 use codec::BlockchainCodec;
 use config::NodeConfig;
 use futures::SinkExt;
 use futures::StreamExt;
-use messages::{NetworkMessage, NetworkMessagePayload};
+use messages::NetworkMessage;
 use peer_manager::{PeerInfo, PeerManager};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{mpsc, Mutex};
 use tokio::task::JoinHandle;
 use tokio_util::codec::Framed;
+use tracing::Instrument;
+use vintage_metrics::NETWORK_CONNECTED_PEERS;
 use vintage_msg::{
-    MsgToBlockChain, MsgToNetwork, NetworkMsgChannels, NetworkMsgHandler, OverlordMsgBlock,
+    MsgToBlockChain, MsgToNetwork, NetworkMsgChannels, NetworkMsgHandler, NodeId, OverlordMsgBlock,
+    ValidatorNode,
 };
+use vintage_utils::{BlsPrivateKey, BlsPublicKey, CalcHash};
 
 pub type BoxedError = Box<dyn std::error::Error + Send + Sync>;
 
 pub struct Node {
     address: SocketAddr,
-    peers: Arc<Mutex<HashMap<SocketAddr, mpsc::Sender<NetworkMessage>>>>,
+    node_id: NodeId,
+    authenticator: Arc<PeerAuthenticator>,
+    peers: Arc<Mutex<HashMap<NodeId, mpsc::Sender<NetworkMessage>>>>,
     outgoing_messages: mpsc::Receiver<MsgToNetwork>,
     incoming_messages: mpsc::Sender<MsgToBlockChain>,
     consensus_incoming_messages: mpsc::Sender<OverlordMsgBlock>,
@@ -71,21 +78,38 @@ impl Node {
     */
     pub async fn create(
         config: &NodeConfig,
+        private_key: &BlsPrivateKey,
         channels: NetworkMsgChannels,
         request_mgr: ArcNetworkRequestMgr,
+        peer_manager: Arc<PeerManager>,
     ) -> Result<Self, anyhow::Error> {
         //let (incoming_tx, incoming_rx) = mpsc::channel(100);
         //let (outgoing_tx, outgoing_rx) = mpsc::channel(100);
         let outgoing_rx = channels.msg_receiver;
         let incoming_tx = channels.blockchain_msg_sender;
         let consensus_incoming_tx = channels.consensus_msg_sender;
-        let peer_manager = Arc::new(PeerManager::new(5));
+        let mut validator_nodes = HashSet::new();
         for peer in &config.peers {
-            peer_manager.add_peer(peer.address).await;
+            let peer_node_id = peer.node_id()?;
+            validator_nodes.insert(peer_node_id.clone());
+            peer_manager.add_peer(peer_node_id, peer.clone()).await;
         }
+        let mut observer_nodes = HashSet::new();
+        for observer in &config.allowed_observers {
+            observer_nodes.insert(BlsPublicKey::from_hex(observer)?.calc_hash());
+        }
+
+        let node_id = private_key.public_key().calc_hash();
+        tracing::info!("node id: {}", node_id);
 
         let node = Node {
             address: config.listen_addr,
+            node_id,
+            authenticator: Arc::new(PeerAuthenticator::new(
+                private_key.clone(),
+                validator_nodes,
+                observer_nodes,
+            )),
             peers: Arc::new(Mutex::new(HashMap::new())),
             incoming_messages: incoming_tx,
             outgoing_messages: outgoing_rx,
@@ -107,31 +131,39 @@ impl Node {
     }
 
     pub async fn start(&mut self) -> Result<(), BoxedError> {
-        println!("Node will start and listening on: {}", self.address);
         let listener = TcpListener::bind(self.address).await?;
-        println!("Node now listening on: {}", self.address);
+        tracing::info!(addr = %self.address, "node listening");
 
         let incoming_messages = self.incoming_messages.clone();
         let consensus_incoming_messages = self.consensus_incoming_messages.clone();
         let peers = Arc::clone(&self.peers);
-        let listen_addr = self.address;
+        let authenticator = self.authenticator.clone();
         let request_mgr = self.request_mgr.clone();
         tokio::spawn(async move {
             while let Ok((socket, addr)) = listener.accept().await {
-                println!("New connection from: {}", addr);
-                if let Err(e) = Self::handle_connection(
-                    socket,
-                    addr,
-                    listen_addr,
-                    Arc::clone(&peers),
-                    incoming_messages.clone(),
-                    consensus_incoming_messages.clone(),
-                    request_mgr.clone(),
-                )
-                .await
-                {
-                    eprintln!("Error handling connection: {}", e);
-                }
+                tracing::info!(%addr, "new connection");
+                // the handshake of a connection must not block the listener
+                let authenticator = authenticator.clone();
+                let peers = Arc::clone(&peers);
+                let incoming_messages = incoming_messages.clone();
+                let consensus_incoming_messages = consensus_incoming_messages.clone();
+                let request_mgr = request_mgr.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = Self::handle_connection(
+                        socket,
+                        addr,
+                        None,
+                        authenticator,
+                        peers,
+                        incoming_messages,
+                        consensus_incoming_messages,
+                        request_mgr,
+                    )
+                    .await
+                    {
+                        tracing::warn!(%addr, err = %e, "handle connection failed");
+                    }
+                });
             }
         });
 
@@ -177,20 +209,77 @@ impl Node {
                         self.handle_broadcast_message(message_content).await?;
                     }
                     MsgToNetwork::ConsensusMsgRelay(bytes, block) => {
-                        let addr = bytes_to_socket_addr(&bytes);
-                        match addr {
-                            Ok(recovered_addr) => {
+                        match NodeId::try_from(&bytes) {
+                            Ok(node_id) => {
                                 self.handle_message(
-                                    recovered_addr,
+                                    node_id,
                                     NetworkMessageContent::ConsensusMsgRelay(block),
                                 )
                                 .await?;
                             }
-                            Err(e) => println!("Error: {}", e),
+                            Err(e) => tracing::error!(err = %e, "relay receiver is not a node id"),
                         }
                     }
+                    MsgToNetwork::UpdateValidators(validators) => {
+                        self.update_validators(validators).await;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn update_validators(&self, validators: Vec<ValidatorNode>) {
+        let mut validator_nodes = HashSet::new();
+        let mut validator_infos = Vec::new();
+        for validator in validators {
+            let info = PeerInfo::from(validator);
+            match info.node_id() {
+                Ok(node_id) => {
+                    if node_id != self.node_id {
+                        validator_nodes.insert(node_id.clone());
+                        validator_infos.push((node_id, info));
+                    }
                 }
+                Err(err) => tracing::error!("validator {} err: {:?}", info.name, err),
+            }
+        }
+        tracing::info!("update validators: {:?}", validator_nodes);
+
+        // drop the removed validators, their read loops end on the next message
+        self.authenticator
+            .set_validator_nodes(validator_nodes.clone());
+        self.peer_manager.retain_peers(&validator_nodes).await;
+        {
+            let mut peers = self.peers.lock().await;
+            peers.retain(|node_id, _| self.authenticator.is_allowed(node_id));
+            NETWORK_CONNECTED_PEERS.set(peers.len() as i64);
+        }
+
+        // connect to the new validators
+        for (node_id, info) in validator_infos {
+            if self.peers.lock().await.contains_key(&node_id) {
+                continue;
             }
+            self.peer_manager.add_peer(node_id, info.clone()).await;
+            let authenticator = self.authenticator.clone();
+            let peers = self.peers.clone();
+            let incoming_messages = self.incoming_messages.clone();
+            let consensus_incoming_messages = self.consensus_incoming_messages.clone();
+            let request_mgr = self.request_mgr.clone();
+            tokio::spawn(async move {
+                if let Err(e) = reconnect_to_peer(
+                    authenticator,
+                    &info,
+                    peers,
+                    incoming_messages,
+                    consensus_incoming_messages,
+                    request_mgr,
+                )
+                .await
+                {
+                    tracing::warn!(addr = %info.address, err = %e, "connect to validator failed");
+                }
+            });
         }
     }
 
@@ -199,243 +288,231 @@ impl Node {
         content: NetworkMessageContent,
     ) -> Result<(), BoxedError> {
         let message = NetworkMessage {
-            sender: self.address,
+            sender: self.node_id.clone(),
             receiver: None,
-            payload: NetworkMessagePayload::Content(content),
+            payload: content,
         };
-        log::info!(
-            "Processing outgoing message:: {:.100}",
-            format!("{:?}", message)
-        );
+        tracing::debug!(msg_type = message.payload.msg_type(), "broadcast message");
+        tracing::trace!(?message, "broadcast message content");
         self.broadcast_message(message).await
     }
 
     async fn handle_message(
         &self,
-        receiver: SocketAddr,
+        receiver: NodeId,
         content: NetworkMessageContent,
     ) -> Result<(), BoxedError> {
         let message = NetworkMessage {
-            sender: self.address,
+            sender: self.node_id.clone(),
             receiver: Some(receiver),
-            payload: NetworkMessagePayload::Content(content),
+            payload: content,
         };
-        log::info!(
-            "Processing outgoing message:: {:.100}",
-            format!("{:?}", message)
+        tracing::debug!(
+            msg_type = message.payload.msg_type(),
+            receiver = ?message.receiver,
+            "send message"
         );
+        tracing::trace!(?message, "send message content");
         self.send_message(message).await
     }
 
     async fn broadcast_message(&self, message: NetworkMessage) -> Result<(), BoxedError> {
         let peers = self.peers.lock().await;
-        for (peer_addr, tx) in peers.iter() {
-            if *peer_addr != message.sender {
+        for (peer_id, tx) in peers.iter() {
+            if *peer_id != message.sender {
                 // Don't send to the original sender
                 if let Err(e) = tx.send(message.clone()).await {
-                    eprintln!("Failed to send message to peer {}: {}", peer_addr, e);
+                    tracing::warn!(peer = %peer_id, err = %e, "send message to peer failed");
                 }
             }
         }
-        // Send the message to the application layer
-        //if let Err(e) = self.outgoing_messages.send(message).await {
-        //    eprintln!("Failed to send message to application layer: {}", e);
-        //}
         Ok(())
     }
 
     async fn send_message(&self, message: NetworkMessage) -> Result<(), BoxedError> {
         let peers = self.peers.lock().await;
-        match message.receiver {
+        match &message.receiver {
             Some(receiver) => {
                 // Send only to the specified receiver
-                if let Some(tx) = peers.get(&receiver) {
+                if let Some(tx) = peers.get(receiver) {
                     if let Err(e) = tx.send(message.clone()).await {
-                        eprintln!("Failed to send message to receiver {}: {}", receiver, e);
+                        tracing::warn!(peer = %receiver, err = %e, "send message to peer failed");
                     }
                 } else {
-                    eprintln!("Receiver {} not found in peers list", receiver);
+                    tracing::warn!(peer = %receiver, "receiver not found in peers");
                 }
             }
             None => {}
         }
-        // Send the message to the application layer
-        //if let Err(e) = self.outgoing_messages.send(message).await {
-        //    eprintln!("Failed to send message to application layer: {}", e);
-        //}
         Ok(())
     }
 
+    // the node dialed must be the one proven by the handshake
+    #[allow(clippy::too_many_arguments)]
     async fn handle_connection(
-        socket: TcpStream,
+        mut socket: TcpStream,
         addr: SocketAddr,
-        listening_addr: SocketAddr,
-        peers: Arc<Mutex<HashMap<SocketAddr, mpsc::Sender<NetworkMessage>>>>,
+        dialed_node_id: Option<NodeId>,
+        authenticator: Arc<PeerAuthenticator>,
+        peers: Arc<Mutex<HashMap<NodeId, mpsc::Sender<NetworkMessage>>>>,
         incoming_messages: mpsc::Sender<MsgToBlockChain>,
         consensus_incoming_messages: mpsc::Sender<OverlordMsgBlock>,
         request_mgr: ArcNetworkRequestMgr,
     ) -> Result<(), BoxedError> {
-        let (tx, mut rx) = mpsc::channel::<NetworkMessage>(100);
+        let (peer_node_id, cipher) = authenticator
+            .handshake(&mut socket, dialed_node_id.as_ref())
+            .await?;
+        let span = tracing::info_span!("connection", peer = %peer_node_id, %addr);
+        span.in_scope(|| tracing::info!("handshake completed"));
 
+        let (tx, mut rx) = mpsc::channel::<NetworkMessage>(100);
         {
-            //let mut peers = peers.lock().await;
-            //peers.insert(addr, tx.clone());
+            let mut peers = peers.lock().await;
+            peers.insert(peer_node_id.clone(), tx);
+            NETWORK_CONNECTED_PEERS.set(peers.len() as i64);
         }
 
-        let framed = Framed::new(socket, BlockchainCodec);
+        let framed = Framed::new(socket, BlockchainCodec::new(cipher));
         let (mut sink, mut stream) = framed.split();
 
-        // Send handshake
-        let handshake = NetworkMessage {
-            sender: listening_addr,
-            receiver: Some(addr),
-            payload: NetworkMessagePayload::Handshake(listening_addr),
-        };
-        println!("Send out hand shake message to {}", addr);
-        sink.send(handshake).await?;
-
-        let mut peer_listening_addr = None;
         let incoming_messages = incoming_messages.clone();
 
-        tokio::spawn(async move {
-            log::info!("Processing incoming message from : {}", addr);
+        let read_messages = async move {
+            tracing::info!("processing incoming messages");
             while let Some(result) = stream.next().await {
+                if !authenticator.is_allowed(&peer_node_id) {
+                    tracing::info!("node is no longer allowed, disconnect");
+                    break;
+                }
                 match result {
                     Ok(message) => {
-                        if let NetworkMessagePayload::Handshake(addr) = message.payload {
-                            peer_listening_addr = Some(addr);
-                            let mut peers = peers.lock().await;
-                            peers.insert(addr, tx.clone());
-                            log::info!("Handshake received from {}", addr);
-                        } else if peer_listening_addr.is_some() {
-                            let formatted = format!("{:?}", message);
-                            log::info!(
-                                "Received network message from {},data: {:.100}",
-                                peer_listening_addr.unwrap(),
-                                formatted
-                            );
-
-                            if let NetworkMessagePayload::Content(content) = message.payload {
-                                match content {
-                                    NetworkMessageContent::Broadcast(broadcast) => {
-                                        let msg = MsgToBlockChain::Broadcast(
-                                            peer_listening_addr.unwrap(),
-                                            broadcast.broadcast_content,
-                                        );
-                                        match broadcast.handler {
-                                            NetworkMsgHandler::BlockChain => {
-                                                log::info!("Send MsgToBlockChain::Broadcast to vintage_blockchain");
-                                                if let Err(e) = incoming_messages.send(msg).await {
-                                                    eprintln!(
-                                                        "Failed to send message to application layer: {}",
-                                                        e
-                                                    );
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                    }
-                                    NetworkMessageContent::Request(request) => {
-                                        let msg = MsgToBlockChain::Request(
-                                            peer_listening_addr.unwrap(),
-                                            request.request_id,
-                                            request.request_content,
+                        tracing::debug!(msg_type = message.payload.msg_type(), "received message");
+                        tracing::trace!(?message, "received message content");
+
+                        match message.payload {
+                            NetworkMessageContent::Broadcast(broadcast) => {
+                                let msg = MsgToBlockChain::Broadcast(
+                                    peer_node_id.clone(),
+                                    broadcast.broadcast_content,
+                                );
+                                match broadcast.handler {
+                                    NetworkMsgHandler::BlockChain => {
+                                        tracing::debug!(
+                                            "Send MsgToBlockChain::Broadcast to vintage_blockchain"
                                         );
-                                        match request.handler {
-                                            NetworkMsgHandler::BlockChain => {
-                                                log::info!("Send MsgToBlockChain::Request to vintage_blockchain");
-                                                if let Err(e) = incoming_messages.send(msg).await {
-                                                    eprintln!(
-                                                        "Failed to send message to application layer: {}",
-                                                        e
-                                                    );
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                    }
-                                    NetworkMessageContent::Response(response) => {
-                                        request_mgr.lock().unwrap().on_response(
-                                            peer_listening_addr.unwrap(),
-                                            response.request_id,
-                                            response.response_content,
-                                        );
-                                    }
-                                    NetworkMessageContent::ConsensusBroadcast(consensus_msg) => {
-                                        log::info!("Send ConsensusMsg to vintage_consensus");
-                                        if let Err(err) =
-                                            consensus_incoming_messages.send(consensus_msg).await
-                                        {
-                                            eprintln!(
-                                                "Failed to send message to application layer: {}",
-                                                err
+                                        if let Err(e) = incoming_messages.send(msg).await {
+                                            tracing::error!(
+                                                "send message to application layer failed: {}",
+                                                e
                                             );
                                             break;
                                         }
                                     }
-                                    NetworkMessageContent::ConsensusMsgRelay(consensus_msg) => {
-                                        log::info!("Send ConsensusMsgRelay to vintage_consensus");
-                                        if let Err(e) =
-                                            consensus_incoming_messages.send(consensus_msg).await
-                                        {
-                                            eprintln!(
-                                                "Failed to send message to application layer: {}",
+                                }
+                            }
+                            NetworkMessageContent::Request(request) => {
+                                let msg = MsgToBlockChain::Request(
+                                    peer_node_id.clone(),
+                                    request.request_id,
+                                    request.request_content,
+                                );
+                                match request.handler {
+                                    NetworkMsgHandler::BlockChain => {
+                                        tracing::debug!(
+                                            "Send MsgToBlockChain::Request to vintage_blockchain"
+                                        );
+                                        if let Err(e) = incoming_messages.send(msg).await {
+                                            tracing::error!(
+                                                "send message to application layer failed: {}",
                                                 e
                                             );
                                             break;
                                         }
                                     }
                                 }
-                            } else {
-                                println!("Message keep at network layer, skip the sending to application layer.")
                             }
-                        } else {
-                            println!("Received message before handshake from {}", addr);
+                            NetworkMessageContent::Response(response) => {
+                                request_mgr.lock().unwrap().on_response(
+                                    peer_node_id.clone(),
+                                    response.request_id,
+                                    response.response_content,
+                                );
+                            }
+                            NetworkMessageContent::ConsensusBroadcast(consensus_msg) => {
+                                tracing::debug!("Send ConsensusMsg to vintage_consensus");
+                                if let Err(err) =
+                                    consensus_incoming_messages.send(consensus_msg).await
+                                {
+                                    tracing::error!(
+                                        "send message to application layer failed: {}",
+                                        err
+                                    );
+                                    break;
+                                }
+                            }
+                            NetworkMessageContent::ConsensusMsgRelay(consensus_msg) => {
+                                tracing::debug!("Send ConsensusMsgRelay to vintage_consensus");
+                                if let Err(e) =
+                                    consensus_incoming_messages.send(consensus_msg).await
+                                {
+                                    tracing::error!(
+                                        "send message to application layer failed: {}",
+                                        e
+                                    );
+                                    break;
+                                }
+                            }
                         }
                     }
                     Err(e) => {
-                        eprintln!("Error reading from socket {}: {}", addr, e);
+                        tracing::warn!(err = %e, "read from socket failed");
                         break;
                     }
                 }
             }
-            println!("Finished handling messages from {}", addr);
-            if let Some(peer_addr) = peer_listening_addr {
+            tracing::info!("connection closed, remove the node from peers");
+            {
                 let mut peers = peers.lock().await;
-                println!("remove addr: {} from peers", peer_addr);
-                peers.remove(&peer_addr);
+                peers.remove(&peer_node_id);
+                NETWORK_CONNECTED_PEERS.set(peers.len() as i64);
             }
-        });
+        };
+        tokio::spawn(read_messages.instrument(span.clone()));
 
-        tokio::spawn(async move {
-            while let Some(message) = rx.recv().await {
-                if let Err(e) = sink.send(message).await {
-                    eprintln!("Failed to send message to {}: {}", addr, e);
-                    break;
+        tokio::spawn(
+            async move {
+                while let Some(message) = rx.recv().await {
+                    if let Err(e) = sink.send(message).await {
+                        tracing::warn!(err = %e, "send message failed");
+                        break;
+                    }
                 }
             }
-        });
+            .instrument(span),
+        );
 
         Ok(())
     }
 
-    pub async fn connect_to_peer(&self, peer_addr: SocketAddr) -> Result<(), BoxedError> {
-        if peer_addr == self.address {
-            println!("Skipping self-connection to {}", peer_addr);
+    pub async fn connect_to_peer(&self, peer: &PeerInfo) -> Result<(), BoxedError> {
+        let node_id = peer.node_id()?;
+        if node_id == self.node_id {
+            tracing::debug!(addr = %peer.address, "skip self connection");
             return Ok(());
         }
 
-        if self.peer_manager.is_connected(&peer_addr).await {
-            println!("Already connected to {}", peer_addr);
+        if self.peer_manager.is_connected(&node_id).await {
+            tracing::debug!(addr = %peer.address, "already connected");
             return Ok(());
         }
 
-        let socket = TcpStream::connect(peer_addr).await?;
-        println!("Connected to peer: {}", peer_addr);
+        let socket = TcpStream::connect(peer.address).await?;
+        tracing::info!(addr = %peer.address, "connected to peer");
         Self::handle_connection(
             socket,
-            peer_addr,
-            self.address,
+            peer.address,
+            Some(node_id.clone()),
+            self.authenticator.clone(),
             Arc::clone(&self.peers),
             self.incoming_messages.clone(),
             self.consensus_incoming_messages.clone(),
@@ -443,21 +520,21 @@ impl Node {
         )
         .await?;
 
-        self.peer_manager.add_peer(peer_addr).await;
+        self.peer_manager.add_peer(node_id, peer.clone()).await;
         Ok(())
     }
 
-    pub async fn connect_to_peers(&self, peer_addrs: Vec<SocketAddr>) -> Result<(), BoxedError> {
-        for addr in peer_addrs {
-            if let Err(e) = self.connect_to_peer(addr).await {
-                println!("Failed to connect to {}: {}", addr, e);
+    pub async fn connect_to_peers(&self, peers: Vec<PeerInfo>) -> Result<(), BoxedError> {
+        for peer in peers {
+            if let Err(e) = self.connect_to_peer(&peer).await {
+                tracing::warn!(addr = %peer.address, err = %e, "connect to peer failed");
             }
         }
         Ok(())
     }
 
     pub async fn start_peer_management(&self) {
-        let local_address = self.address.clone();
+        let authenticator = self.authenticator.clone();
         let peer_manager = self.peer_manager.clone();
         let peers = self.peers.clone();
         let incoming_messages = self.incoming_messages.clone();
@@ -470,16 +547,16 @@ impl Node {
                     if peer.connected_once {
                         let is_healthy = false; //check_peer_health(&peer.info.address).await;
                         peer_manager
-                            .update_peer_status(peer.info.address, is_healthy)
+                            .update_peer_status(&peer.node_id, is_healthy)
                             .await;
                     }
                 }
 
                 // Reconnection attempts
                 for peer in peer_manager.get_peers_to_reconnect().await {
-                    println!("try to re-connect to: {}", peer.address);
+                    tracing::info!(addr = %peer.address, "reconnect to peer");
                     if let Err(e) = reconnect_to_peer(
-                        local_address,
+                        authenticator.clone(),
                         &peer,
                         peers.clone(),
                         incoming_messages.clone(),
@@ -488,12 +565,12 @@ impl Node {
                     )
                     .await
                     {
-                        eprintln!("Failed to reconnect to {}: {}", peer.address, e);
+                        tracing::warn!(addr = %peer.address, err = %e, "reconnect to peer failed");
                     } else {
-                        println!("re-connected to: {}", peer.address)
+                        tracing::info!(addr = %peer.address, "reconnected to peer");
                     }
                 }
-                println!("reconnect completed.");
+                tracing::debug!("reconnect completed");
                 // Remove unresponsive peers
                 peer_manager.remove_unresponsive_peers().await;
 
@@ -504,32 +581,25 @@ impl Node {
     }
 }
 
-fn bytes_to_socket_addr(bytes: &Bytes) -> Result<SocketAddr, std::io::Error> {
-    let addr_str = std::str::from_utf8(bytes)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-    addr_str
-        .parse()
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
-}
-
 async fn _check_peer_health(addr: &SocketAddr) -> bool {
     tokio::net::TcpStream::connect(addr).await.is_ok()
 }
 
 async fn reconnect_to_peer(
-    listening_addr: SocketAddr,
+    authenticator: Arc<PeerAuthenticator>,
     peer: &PeerInfo,
-    peers: Arc<Mutex<HashMap<SocketAddr, mpsc::Sender<NetworkMessage>>>>,
+    peers: Arc<Mutex<HashMap<NodeId, mpsc::Sender<NetworkMessage>>>>,
     incoming_messages: mpsc::Sender<MsgToBlockChain>,
     consensus_incoming_messages: mpsc::Sender<OverlordMsgBlock>,
     request_mgr: ArcNetworkRequestMgr,
 ) -> Result<(), BoxedError> {
     let socket = tokio::net::TcpStream::connect(peer.address).await?;
-    println!("Reconnected to peer: {}", peer.address);
+    tracing::debug!(addr = %peer.address, "connected to peer");
     Node::handle_connection(
         socket,
         peer.address,
-        listening_addr,
+        Some(peer.node_id()?),
+        authenticator,
         peers,
         incoming_messages,
         consensus_incoming_messages,
@@ -1,18 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
-use vintage_msg::{NetworkMsgHandler, NetworkRequestId, OverlordMsgBlock};
+use vintage_msg::{NetworkMsgHandler, NetworkRequestId, NodeId, OverlordMsgBlock};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct NetworkMessage {
-    pub sender: SocketAddr,
-    pub receiver: Option<SocketAddr>,
-    pub payload: NetworkMessagePayload,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub(crate) enum NetworkMessagePayload {
-    Handshake(SocketAddr),
-    Content(NetworkMessageContent),
+    pub sender: NodeId,
+    pub receiver: Option<NodeId>,
+    pub payload: NetworkMessageContent,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +17,18 @@ pub(crate) enum NetworkMessageContent {
     ConsensusMsgRelay(OverlordMsgBlock),
 }
 
+impl NetworkMessageContent {
+    pub fn msg_type(&self) -> &'static str {
+        match self {
+            Self::Broadcast(_) => "broadcast",
+            Self::Request(_) => "request",
+            Self::Response(_) => "response",
+            Self::ConsensusBroadcast(_) => "consensus_broadcast",
+            Self::ConsensusMsgRelay(_) => "consensus_msg_relay",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct NetworkBroadcast {
     pub handler: NetworkMsgHandler,
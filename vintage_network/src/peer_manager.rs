@@ -1,46 +1,81 @@
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::net::{IpAddr, SocketAddr};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::Instant;
+use vintage_msg::{NodeId, ValidatorNode};
+use vintage_utils::{BlsPublicKey, CalcHash};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct PeerAddress {
-    pub ip: IpAddr,
-    pub port: Option<u16>,
-}
-
-impl From<SocketAddr> for PeerAddress {
-    fn from(addr: SocketAddr) -> Self {
-        PeerAddress {
-            ip: addr.ip(),
-            port: Some(addr.port()),
-        }
-    }
-}
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PeerInfo {
     pub id: u16,
     pub name: String,
     pub address: SocketAddr,
+    // empty until the keys are generated, the peers of a single node are not used
+    #[serde(default)]
+    pub public_key: String,
+    // hex encoded proof of possession of the public key
+    #[serde(default)]
+    pub pop: String,
     pub propose_weight: u32,
     pub vote_weight: u32,
 }
 
+impl PeerInfo {
+    pub fn node_id(&self) -> anyhow::Result<NodeId> {
+        Ok(self.public_key()?.calc_hash())
+    }
+
+    pub fn public_key(&self) -> anyhow::Result<BlsPublicKey> {
+        if self.public_key.is_empty() {
+            return Err(anyhow!(
+                "public key of peer {} is not set, run config/gen-dev-keys.sh for the dev nodes",
+                self.name
+            ));
+        }
+        BlsPublicKey::from_hex(&self.public_key)
+            .map_err(|err| anyhow!("public key of peer {} err: {}", self.name, err))
+    }
+}
+
+impl From<ValidatorNode> for PeerInfo {
+    fn from(validator: ValidatorNode) -> Self {
+        Self {
+            id: validator.id,
+            name: validator.name,
+            address: validator.address,
+            public_key: validator.public_key,
+            pop: validator.pop,
+            propose_weight: validator.propose_weight,
+            vote_weight: validator.vote_weight,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PeerStatus {
+    pub node_id: NodeId,
     pub info: PeerInfo,
     pub connected_once: bool,
     pub last_seen: Instant,
     pub failed_attempts: u32,
 }
 
+const MAX_FAILED_ATTEMPTS: u32 = 5;
+
 pub struct PeerManager {
-    peers: Arc<Mutex<HashMap<PeerAddress, PeerStatus>>>,
+    peers: Arc<Mutex<HashMap<NodeId, PeerStatus>>>,
     max_failed_attempts: u32,
 }
 
+impl Default for PeerManager {
+    fn default() -> Self {
+        Self::new(MAX_FAILED_ATTEMPTS)
+    }
+}
+
 impl PeerManager {
     pub fn new(max_failed_attempts: u32) -> Self {
         PeerManager {
@@ -49,14 +84,16 @@ impl PeerManager {
         }
     }
 
-    pub async fn _load_peers_from_yaml(&self, yaml_content: &str) -> Result<(), serde_yaml::Error> {
+    pub async fn _load_peers_from_yaml(&self, yaml_content: &str) -> anyhow::Result<()> {
         let peer_infos: Vec<PeerInfo> = serde_yaml::from_str(yaml_content)?;
         let mut peers = self.peers.lock().await;
         for info in peer_infos {
-            println!("add peer: {}", info.address);
+            tracing::info!(addr = %info.address, "add peer");
+            let node_id = info.node_id()?;
             peers.insert(
-                info.address.into(),
+                node_id.clone(),
                 PeerStatus {
+                    node_id,
                     info: info.clone(),
                     last_seen: Instant::now(),
                     failed_attempts: 0,
@@ -77,10 +114,10 @@ impl PeerManager {
         peers.values().cloned().collect()
     }
 
-    pub async fn update_peer_status(&self, addr: SocketAddr, is_healthy: bool) {
-        println!("Update peer status: {}, {}", addr, is_healthy);
+    pub async fn update_peer_status(&self, node_id: &NodeId, is_healthy: bool) {
+        tracing::debug!(peer = %node_id, is_healthy, "update peer status");
         let mut peers = self.peers.lock().await;
-        if let Some(status) = peers.get_mut(&addr.into()) {
+        if let Some(status) = peers.get_mut(node_id) {
             if is_healthy {
                 status.last_seen = Instant::now();
                 status.failed_attempts = 0;
@@ -93,9 +130,11 @@ impl PeerManager {
     pub async fn get_peers_to_reconnect(&self) -> Vec<PeerInfo> {
         let peers = self.peers.lock().await;
         for peer in peers.values() {
-            println!(
-                "Peer: {}, {}, {}",
-                peer.info.address, peer.info.name, peer.failed_attempts
+            tracing::debug!(
+                addr = %peer.info.address,
+                name = %peer.info.name,
+                failed_attempts = peer.failed_attempts,
+                "peer status"
             );
         }
         peers
@@ -112,21 +151,15 @@ impl PeerManager {
         peers.retain(|_, status| status.failed_attempts <= self.max_failed_attempts);
     }
 
-    pub async fn add_peer(&self, addr: SocketAddr) -> bool {
+    pub async fn add_peer(&self, node_id: NodeId, info: PeerInfo) -> bool {
         let mut peers = self.peers.lock().await;
-        let peer_addr = PeerAddress::from(addr);
-        if !peers.contains_key(&peer_addr) {
+        if !peers.contains_key(&node_id) {
             peers.insert(
-                peer_addr,
+                node_id.clone(),
                 PeerStatus {
+                    node_id,
                     connected_once: true,
-                    info: PeerInfo {
-                        id: 0,
-                        name: format!("Node_{}", addr),
-                        address: addr,
-                        vote_weight: 0,
-                        propose_weight: 0,
-                    },
+                    info,
                     last_seen: Instant::now(),
                     failed_attempts: 0,
                 },
@@ -137,9 +170,13 @@ impl PeerManager {
         }
     }
 
-    pub async fn is_connected(&self, addr: &SocketAddr) -> bool {
+    pub async fn retain_peers(&self, node_ids: &HashSet<NodeId>) {
+        let mut peers = self.peers.lock().await;
+        peers.retain(|node_id, _| node_ids.contains(node_id));
+    }
+
+    pub async fn is_connected(&self, node_id: &NodeId) -> bool {
         let peers = self.peers.lock().await;
-        let peer_addr = PeerAddress::from(*addr);
-        peers.contains_key(&peer_addr)
+        peers.contains_key(node_id)
     }
 }
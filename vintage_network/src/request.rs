@@ -3,6 +3,7 @@ use crate::response::{NetworkResponseSimple, NetworkResponseWithFilter, NetworkR
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use vintage_msg::{NetworkRequestId, NodeId};
+use vintage_utils::Quorum;
 
 pub type ArcNetworkRequestMgr = Arc<Mutex<NetworkRequestMgr>>;
 
@@ -39,9 +40,9 @@ impl NetworkRequestMgr {
 
     pub(super) fn request_with_vote(
         &mut self,
-        node_count: usize,
+        quorum: Quorum<NodeId>,
     ) -> (NetworkRequestId, DynNetworkResponseReader) {
-        let response = Arc::new(NetworkResponseWithVote::new(node_count));
+        let response = Arc::new(NetworkResponseWithVote::new(quorum));
         let request_id = self.insert_request(response.clone());
         (request_id, response)
     }
@@ -50,7 +51,7 @@ impl NetworkRequestMgr {
         if let Some(response) = self.requests.get(&request_id) {
             response.write_data(node_id, data);
         } else {
-            log::debug!(
+            tracing::debug!(
                 "request {} removed, received response from {}",
                 request_id,
                 node_id
@@ -5,7 +5,7 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 use std::time::Duration;
 use vintage_msg::NodeId;
-use vintage_utils::{Activation, Data};
+use vintage_utils::{Activation, Data, Quorum};
 
 pub trait NetworkResponseIO: NetworkResponseWriter + NetworkResponseReader {}
 
@@ -103,15 +103,15 @@ where
 pub struct NetworkResponseWithVote {
     activation: Activation,
     multi_data: Mutex<HashMap<Vec<u8>, HashSet<NodeId>>>,
-    node_count: usize,
+    quorum: Quorum<NodeId>,
 }
 
 impl NetworkResponseWithVote {
-    pub fn new(node_count: usize) -> Self {
+    pub fn new(quorum: Quorum<NodeId>) -> Self {
         Self {
             activation: Activation::new(false),
             multi_data: Mutex::new(HashMap::with_capacity(1)),
-            node_count,
+            quorum,
         }
     }
 }
@@ -121,19 +121,13 @@ impl NetworkResponseIO for NetworkResponseWithVote {}
 impl NetworkResponseWriter for NetworkResponseWithVote {
     fn write_data(&self, node_id: NodeId, data: Vec<u8>) {
         let mut guard = self.multi_data.lock().unwrap();
-        match guard.entry(data) {
-            Entry::Occupied(mut entry) => {
-                let node_ids = entry.get_mut();
-                node_ids.insert(node_id);
-                if node_ids.len() >= self.node_count {
-                    self.activation.set_active(true);
-                }
-            }
-            Entry::Vacant(entry) => {
-                let mut node_ids = HashSet::with_capacity(self.node_count);
-                node_ids.insert(node_id);
-                entry.insert(node_ids);
-            }
+        let node_ids = match guard.entry(data) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(HashSet::new()),
+        };
+        node_ids.insert(node_id);
+        if self.quorum.is_reached(node_ids.iter()) {
+            self.activation.set_active(true);
         }
     }
 }
@@ -145,7 +139,7 @@ impl NetworkResponseReader for NetworkResponseWithVote {
         {
             let guard = self.multi_data.lock().unwrap();
             for (data, node_ids) in &*guard {
-                if node_ids.len() >= self.node_count {
+                if self.quorum.is_reached(node_ids.iter()) {
                     let node_ids: Vec<NodeId> = node_ids.iter().cloned().collect();
                     return Ok((node_ids, data.clone()));
                 }
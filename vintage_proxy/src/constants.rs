@@ -7,8 +7,19 @@ pub(crate) const VIN_2_WORKER: &str = "vin2worker";
 pub(crate) const ACTION_NEW_BLOCK_HEIGHT: &str = "block_height";
 pub(crate) const ACTION_UPLOAD_WASM: &str = "upload_wasm";
 pub(crate) const ACTION_UPGRADE_WASM: &str = "upgrade_wasm";
+pub(crate) const ACTION_UPDATE_VALIDATORS: &str = "update_validators";
 
 // worker:protocol action
 pub(crate) const ACTION_POST: &str = "post";
+pub(crate) const ACTION_TX_STATUS: &str = "tx_status";
+// the ack of a post or update_index, the tx is in the pool, not committed
+pub(crate) const ACTION_TX_SUBMITTED: &str = "tx_submitted";
 pub(crate) const ACTION_UPDATE_INDEX: &str = "update_index";
 pub(crate) const ACTION_CHECK_PAIR_LIST: &str = "check_pair_list";
+pub(crate) const ACTION_GET_ENTITY_PROOF: &str = "get_entity_proof";
+pub(crate) const ACTION_GET_TX_PROOF: &str = "get_tx_proof";
+pub(crate) const ACTION_GET_TX_STATUS: &str = "get_tx_status";
+pub(crate) const ACTION_GET_BLOCK: &str = "get_block";
+pub(crate) const ACTION_GET_BLOCK_BY_HASH: &str = "get_block_by_hash";
+pub(crate) const ACTION_GET_TX: &str = "get_tx";
+pub(crate) const ACTION_GET_TXS: &str = "get_txs";
@@ -3,7 +3,7 @@ use futures::StreamExt;
 use redis::Msg;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use vintage_msg::{Action, EntityHash, EntityId, Model, Proto, ReqId};
+use vintage_msg::{Action, BlockHeight, EntityHash, EntityId, Model, Proto, ReqId};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct InputOutputObject {
@@ -19,11 +19,11 @@ pub(crate) async fn read_msg(
     channel_name: &str,
 ) -> anyhow::Result<InputOutputObject> {
     let msg = pubsub_stream.next().await;
-    log::info!("received msg from channel {}", channel_name);
+    tracing::info!("received msg from channel {}", channel_name);
 
     let msg_payload: Vec<u8> = msg.unwrap().get_payload()?;
     let msg_obj: InputOutputObject = serde_json::from_slice(&msg_payload).unwrap();
-    log::info!(
+    tracing::info!(
         "from redis, msg_obj: {} {} {} {} {}",
         msg_obj.action,
         msg_obj.proto,
@@ -51,3 +51,15 @@ pub(crate) struct Payload<TReqData> {
 }
 
 pub(crate) type EntitiesPayload = Payload<Vec<(EntityId, EntityHash)>>;
+pub(crate) type EntityProofPayload = Payload<EntityId>;
+// block height and hex encoded tx id
+pub(crate) type TxProofPayload = Payload<(BlockHeight, String)>;
+// hex encoded tx id
+pub(crate) type TxStatusPayload = Payload<String>;
+pub(crate) type BlockPayload = Payload<BlockHeight>;
+// hex encoded block hash
+pub(crate) type BlockByHashPayload = Payload<String>;
+// hex encoded tx id
+pub(crate) type TxPayload = Payload<String>;
+// begin and end height, inclusive
+pub(crate) type TxsPayload = Payload<(BlockHeight, BlockHeight)>;
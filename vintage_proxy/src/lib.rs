@@ -34,7 +34,7 @@ impl Proxy {
     where
         TApi: BlockChainApi + Send + Sync + 'static,
     {
-        log::info!("connect to redis: {}", config.redis_addr);
+        tracing::info!("connect to redis: {}", config.redis_addr);
         let redis_client = redis::Client::open(config.redis_addr)?;
 
         let vin2worker_conn = redis_client.get_async_connection().await?;
@@ -1,9 +1,9 @@
-use crate::constants::{ACTION_UPLOAD_WASM, ADMIN_2_VIN};
+use crate::constants::{ACTION_UPDATE_VALIDATORS, ACTION_UPLOAD_WASM, ADMIN_2_VIN};
 use crate::io_object::{read_msg, InputOutputObject};
 use async_trait::async_trait;
 use redis::aio::PubSub;
 use tokio::sync::mpsc;
-use vintage_msg::{MsgToBlockChain, UploadWasm};
+use vintage_msg::{MsgToBlockChain, UploadWasm, ValidatorSetTx};
 use vintage_utils::{SendMsg, Service};
 
 pub struct Admin2Vin {
@@ -32,6 +32,8 @@ impl Service for Admin2Vin {
 
             if &msg_obj.action == ACTION_UPLOAD_WASM {
                 self.upload_wasm(msg_obj);
+            } else if msg_obj.action == ACTION_UPDATE_VALIDATORS {
+                self.update_validators(msg_obj);
             }
         }
     }
@@ -47,4 +49,17 @@ impl Admin2Vin {
                 block_interval: 10,
             }));
     }
+
+    // data is the json of ValidatorSetTx
+    fn update_validators(&self, object: InputOutputObject) {
+        match serde_json::from_slice::<ValidatorSetTx>(&object.data) {
+            Ok(validator_set_tx) => {
+                self.blockchain_msg_sender
+                    .send_msg(MsgToBlockChain::ValidatorSetTx(validator_set_tx));
+            }
+            Err(err) => {
+                tracing::error!("update_validators, invalid data: {:?}", err);
+            }
+        }
+    }
 }
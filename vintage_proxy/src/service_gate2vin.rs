@@ -1,13 +1,21 @@
-use crate::constants::{ACTION_CHECK_PAIR_LIST, ACTION_POST, ACTION_UPDATE_INDEX};
+use crate::constants::{
+    ACTION_CHECK_PAIR_LIST, ACTION_GET_BLOCK, ACTION_GET_BLOCK_BY_HASH, ACTION_GET_ENTITY_PROOF,
+    ACTION_GET_TX, ACTION_GET_TXS, ACTION_GET_TX_PROOF, ACTION_GET_TX_STATUS, ACTION_POST,
+    ACTION_TX_SUBMITTED, ACTION_UPDATE_INDEX,
+};
 use crate::io_object::read_msg;
-use crate::{payload_json, EntitiesPayload, InputOutputObject};
+use crate::{
+    payload_json, BlockByHashPayload, BlockPayload, EntitiesPayload, EntityProofPayload,
+    InputOutputObject, TxPayload, TxProofPayload, TxStatusPayload, TxsPayload,
+};
 use crate::{GATE_2_VIN, VIN_2_WORKER};
 use async_trait::async_trait;
 use redis::aio::{Connection, PubSub};
 use redis::AsyncCommands;
+use serde_json::json;
 use tokio::sync::mpsc;
 use vintage_msg::{ActTx, BlockChainApi, Entity, MsgToBlockChain, UpdateEntityTx};
-use vintage_utils::{SendMsg, Service};
+use vintage_utils::{CalcHash, SendMsg, Service};
 
 pub struct Gate2Vin<TApi> {
     redis_conn: Connection,
@@ -45,12 +53,44 @@ where
             let msg_obj = read_msg(&mut pubsub_stream, GATE_2_VIN).await?;
 
             if &msg_obj.action == ACTION_POST {
-                self.post(msg_obj);
+                if let Err(err) = self.post(msg_obj).await {
+                    tracing::error!("{} err: {:?}", ACTION_POST, err)
+                }
             } else if &msg_obj.action == ACTION_UPDATE_INDEX {
-                self.update_index(msg_obj);
+                if let Err(err) = self.update_index(msg_obj).await {
+                    tracing::error!("{} err: {:?}", ACTION_UPDATE_INDEX, err)
+                }
             } else if &msg_obj.action == ACTION_CHECK_PAIR_LIST {
                 if let Err(err) = self.check_pair_list(msg_obj).await {
-                    log::error!("{} err: {:?}", ACTION_CHECK_PAIR_LIST, err)
+                    tracing::error!("{} err: {:?}", ACTION_CHECK_PAIR_LIST, err)
+                }
+            } else if &msg_obj.action == ACTION_GET_ENTITY_PROOF {
+                if let Err(err) = self.get_entity_proof(msg_obj).await {
+                    tracing::error!("{} err: {:?}", ACTION_GET_ENTITY_PROOF, err)
+                }
+            } else if &msg_obj.action == ACTION_GET_TX_PROOF {
+                if let Err(err) = self.get_tx_proof(msg_obj).await {
+                    tracing::error!("{} err: {:?}", ACTION_GET_TX_PROOF, err)
+                }
+            } else if &msg_obj.action == ACTION_GET_TX_STATUS {
+                if let Err(err) = self.get_tx_status(msg_obj).await {
+                    tracing::error!("{} err: {:?}", ACTION_GET_TX_STATUS, err)
+                }
+            } else if &msg_obj.action == ACTION_GET_BLOCK {
+                if let Err(err) = self.get_block(msg_obj).await {
+                    tracing::error!("{} err: {:?}", ACTION_GET_BLOCK, err)
+                }
+            } else if &msg_obj.action == ACTION_GET_BLOCK_BY_HASH {
+                if let Err(err) = self.get_block_by_hash(msg_obj).await {
+                    tracing::error!("{} err: {:?}", ACTION_GET_BLOCK_BY_HASH, err)
+                }
+            } else if &msg_obj.action == ACTION_GET_TX {
+                if let Err(err) = self.get_tx(msg_obj).await {
+                    tracing::error!("{} err: {:?}", ACTION_GET_TX, err)
+                }
+            } else if &msg_obj.action == ACTION_GET_TXS {
+                if let Err(err) = self.get_txs(msg_obj).await {
+                    tracing::error!("{} err: {:?}", ACTION_GET_TXS, err)
                 }
             }
         }
@@ -61,31 +101,42 @@ impl<TApi> Gate2Vin<TApi>
 where
     TApi: BlockChainApi,
 {
-    fn post(&self, object: InputOutputObject) {
+    // the tx id is returned with the ext of the request, the tx status follows as tx_status
+    async fn post(&mut self, msg_obj: InputOutputObject) -> anyhow::Result<()> {
+        let act_tx = ActTx {
+            action: msg_obj.action.clone(),
+            proto: msg_obj.proto.clone(),
+            model: msg_obj.model.clone(),
+            data: msg_obj.data.clone(),
+        };
+        let tx_id = act_tx.calc_hash();
         self.blockchain_msg_sender
-            .send_msg(MsgToBlockChain::ActTx(ActTx {
-                action: object.action,
-                proto: object.proto,
-                model: object.model,
-                data: object.data,
-            }));
+            .send_msg(MsgToBlockChain::ActTx(act_tx));
+
+        let ret_payload = json!({ "tx_id": tx_id });
+        self.send_submitted_to_worker(msg_obj, ret_payload).await
     }
 
-    fn update_index(&self, object: InputOutputObject) {
-        let payload: EntitiesPayload = serde_json::from_slice(&object.data).unwrap();
+    // the tx id is returned with the reqid, the tx status follows as tx_status
+    async fn update_index(&mut self, msg_obj: InputOutputObject) -> anyhow::Result<()> {
+        let payload: EntitiesPayload = serde_json::from_slice(&msg_obj.data)?;
         let entities = payload
             .reqdata
             .into_iter()
             .map(|(id, hash)| Entity { id, hash })
             .collect();
-
+        let ue_tx = UpdateEntityTx {
+            proto: msg_obj.proto.clone(),
+            model: msg_obj.model.clone(),
+            req_id: payload.reqid.clone(),
+            entities,
+        };
+        let tx_id = ue_tx.calc_hash();
         self.blockchain_msg_sender
-            .send_msg(MsgToBlockChain::UpdateEntityTx(UpdateEntityTx {
-                proto: object.proto,
-                model: object.model,
-                req_id: payload.reqid,
-                entities,
-            }));
+            .send_msg(MsgToBlockChain::UpdateEntityTx(ue_tx));
+
+        let ret_payload = payload_json(&payload.reqid, tx_id);
+        self.send_submitted_to_worker(msg_obj, ret_payload).await
     }
 
     async fn check_pair_list(&mut self, msg_obj: InputOutputObject) -> anyhow::Result<()> {
@@ -102,18 +153,140 @@ where
             .await;
 
         let ret_payload = payload_json(&payload.reqid, check_boolean.to_string());
-        println!(
-            "from redis: check_pair_list: ret_payload: {:?}",
-            ret_payload
-        );
+        tracing::debug!(?ret_payload, "from redis: check_pair_list");
+
+        self.send_to_worker(msg_obj, ret_payload).await
+    }
+
+    // the proof is null if the entity is not found
+    async fn get_entity_proof(&mut self, msg_obj: InputOutputObject) -> anyhow::Result<()> {
+        let payload: EntityProofPayload = serde_json::from_slice(&msg_obj.data)?;
+        let proof = self
+            .blockchain_api
+            .get_entity_proof(
+                msg_obj.proto.clone(),
+                msg_obj.model.clone(),
+                payload.reqdata,
+            )
+            .await
+            .map_err(|err| tracing::warn!("get entity proof err: {:?}", err))
+            .ok();
+
+        let ret_payload = payload_json(&payload.reqid, proof);
+        self.send_to_worker(msg_obj, ret_payload).await
+    }
+
+    // the proof is null if the tx is not in the block
+    async fn get_tx_proof(&mut self, msg_obj: InputOutputObject) -> anyhow::Result<()> {
+        let payload: TxProofPayload = serde_json::from_slice(&msg_obj.data)?;
+        let (height, tx_id) = payload.reqdata;
+        let proof = self
+            .blockchain_api
+            .get_tx_proof(height, tx_id.parse()?)
+            .await
+            .map_err(|err| tracing::warn!("get tx proof err: {:?}", err))
+            .ok();
+
+        let ret_payload = payload_json(&payload.reqid, proof);
+        self.send_to_worker(msg_obj, ret_payload).await
+    }
+
+    // the receipt is null if the tx is unknown to the node
+    async fn get_tx_status(&mut self, msg_obj: InputOutputObject) -> anyhow::Result<()> {
+        let payload: TxStatusPayload = serde_json::from_slice(&msg_obj.data)?;
+        let receipt = self
+            .blockchain_api
+            .get_tx_receipt(payload.reqdata.parse()?)
+            .await
+            .map_err(|err| tracing::warn!("get tx receipt err: {:?}", err))
+            .ok();
+
+        let ret_payload = payload_json(&payload.reqid, receipt);
+        self.send_to_worker(msg_obj, ret_payload).await
+    }
+
+    // the block is null if not found
+    async fn get_block(&mut self, msg_obj: InputOutputObject) -> anyhow::Result<()> {
+        let payload: BlockPayload = serde_json::from_slice(&msg_obj.data)?;
+        let block = self
+            .blockchain_api
+            .get_block(payload.reqdata)
+            .await
+            .map_err(|err| tracing::warn!("get block err: {:?}", err))
+            .ok();
+
+        let ret_payload = payload_json(&payload.reqid, block);
+        self.send_to_worker(msg_obj, ret_payload).await
+    }
+
+    // the block is null if not found
+    async fn get_block_by_hash(&mut self, msg_obj: InputOutputObject) -> anyhow::Result<()> {
+        let payload: BlockByHashPayload = serde_json::from_slice(&msg_obj.data)?;
+        let block = self
+            .blockchain_api
+            .get_block_by_hash(payload.reqdata.parse()?)
+            .await
+            .map_err(|err| tracing::warn!("get block by hash err: {:?}", err))
+            .ok();
+
+        let ret_payload = payload_json(&payload.reqid, block);
+        self.send_to_worker(msg_obj, ret_payload).await
+    }
+
+    // the tx is null if it is not in any block
+    async fn get_tx(&mut self, msg_obj: InputOutputObject) -> anyhow::Result<()> {
+        let payload: TxPayload = serde_json::from_slice(&msg_obj.data)?;
+        let tx = self
+            .blockchain_api
+            .get_tx(payload.reqdata.parse()?)
+            .await
+            .map_err(|err| tracing::warn!("get tx err: {:?}", err))
+            .ok();
+
+        let ret_payload = payload_json(&payload.reqid, tx);
+        self.send_to_worker(msg_obj, ret_payload).await
+    }
+
+    // the txs are null if the range is invalid
+    async fn get_txs(&mut self, msg_obj: InputOutputObject) -> anyhow::Result<()> {
+        let payload: TxsPayload = serde_json::from_slice(&msg_obj.data)?;
+        let (begin_height, end_height) = payload.reqdata;
+        let txs = self
+            .blockchain_api
+            .get_txs(begin_height, end_height)
+            .await
+            .map_err(|err| tracing::warn!("get txs err: {:?}", err))
+            .ok();
+
+        let ret_payload = payload_json(&payload.reqid, txs);
+        self.send_to_worker(msg_obj, ret_payload).await
+    }
+
+    // acked as tx_submitted, the committed tx comes later under the action of the request
+    async fn send_submitted_to_worker(
+        &mut self,
+        msg_obj: InputOutputObject,
+        ret_payload: serde_json::Value,
+    ) -> anyhow::Result<()> {
+        let msg_obj = InputOutputObject {
+            action: ACTION_TX_SUBMITTED.to_owned(),
+            ..msg_obj
+        };
+        self.send_to_worker(msg_obj, ret_payload).await
+    }
 
-        // send packet back to the spin runtime
+    async fn send_to_worker(
+        &mut self,
+        msg_obj: InputOutputObject,
+        ret_payload: serde_json::Value,
+    ) -> anyhow::Result<()> {
+        // send packet back to the spin runtime, the ext of the request is echoed
         let output = InputOutputObject {
             action: msg_obj.action,
             proto: msg_obj.proto.clone(),
             model: msg_obj.model,
             data: ret_payload.to_string().as_bytes().to_vec(),
-            ext: vec![],
+            ext: msg_obj.ext,
         };
         let output_string = serde_json::to_vec(&output).unwrap();
         let channel = format!("{VIN_2_WORKER}:{}", msg_obj.proto);
@@ -1,5 +1,6 @@
 use crate::constants::{
-    ACTION_NEW_BLOCK_HEIGHT, ACTION_UPDATE_INDEX, ACTION_UPGRADE_WASM, ACTION_UPLOAD_WASM,
+    ACTION_NEW_BLOCK_HEIGHT, ACTION_TX_STATUS, ACTION_UPDATE_INDEX, ACTION_UPGRADE_WASM,
+    ACTION_UPLOAD_WASM,
 };
 use crate::VIN_2_WORKER;
 use crate::{payload_json, InputOutputObject};
@@ -8,8 +9,10 @@ use redis::aio::Connection;
 use redis::AsyncCommands;
 use serde_json::json;
 use tokio::sync::mpsc;
-use vintage_msg::{ActEvent, BlockHeight, MsgToProxy, Proto, UpdateEntityEvent, WasmHash, WasmId};
-use vintage_utils::{Service, Timestamp};
+use vintage_msg::{
+    ActEvent, BlockHeight, MsgToProxy, Proto, TxReceipt, UpdateEntityEvent, WasmHash, WasmId,
+};
+use vintage_utils::Service;
 
 pub struct Vin2Worker {
     redis_conn: Connection,
@@ -39,7 +42,7 @@ impl Service for Vin2Worker {
                             self.on_ue_event(ue_event).await;
                         }
                         for act_event in event.act_events {
-                            self.on_act_event(event.timestamp, act_event).await;
+                            self.on_act_event(act_event).await;
                         }
                         self.on_block_height_event(event.height).await;
                         for wasm_id in event.upgrade_wasm_ids {
@@ -49,6 +52,9 @@ impl Service for Vin2Worker {
                     MsgToProxy::WasmBinary(wasm_hash, wasm_binary) => {
                         self.on_upload_wasm_event(wasm_hash, wasm_binary).await;
                     }
+                    MsgToProxy::TxStatus(proto, receipt) => {
+                        self.on_tx_status_event(proto, receipt).await;
+                    }
                 },
                 None => {
                     break;
@@ -94,9 +100,9 @@ impl Vin2Worker {
         }
     }
 
-    async fn on_act_event(&mut self, timestamp: Timestamp, event: ActEvent) {
+    async fn on_act_event(&mut self, event: ActEvent) {
         let ext = json!({
-            "time": timestamp,
+            "time": event.timestamp,
             "nonce": event.act_number,
             "randomvec": event.random,
         });
@@ -113,8 +119,20 @@ impl Vin2Worker {
         self.publish_vin_2_worker(Some(&proto), &output).await;
     }
 
+    async fn on_tx_status_event(&mut self, proto: Proto, receipt: TxReceipt) {
+        let output = InputOutputObject {
+            action: ACTION_TX_STATUS.to_owned(),
+            proto: proto.clone(),
+            model: "".to_owned(),
+            data: serde_json::to_vec(&receipt).unwrap(),
+            ext: vec![],
+        };
+
+        self.publish_vin_2_worker(Some(&proto), &output).await;
+    }
+
     async fn on_upload_wasm_event(&mut self, wasm_hash: WasmHash, wasm_binary: Vec<u8>) {
-        log::info!(
+        tracing::info!(
             "upload wasm event to worker, hash: {}, size: {}B",
             wasm_hash,
             wasm_binary.len()
@@ -131,7 +149,7 @@ impl Vin2Worker {
     }
 
     async fn on_upgrade_wasm_event(&mut self, block_height: BlockHeight, wasm_id: WasmId) {
-        log::info!(
+        tracing::info!(
             "upgrade wasm event to worker, height: {}, proto: {}, hash: {}",
             block_height,
             wasm_id.proto,
@@ -155,10 +173,7 @@ impl Vin2Worker {
         };
         let output_bytes = serde_json::to_vec(output).unwrap();
 
-        let result: Result<u32, redis::RedisError> =
+        let _result: Result<u32, redis::RedisError> =
             self.redis_conn.publish(channel, output_bytes).await;
-        if let Err(_err) = result {
-            // log::error!("Error publishing to redis: {:?}", err);
-        }
     }
 }
@@ -0,0 +1,41 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde_json::json;
+use vintage_msg::ApiError;
+
+pub(crate) struct RpcError {
+    status: StatusCode,
+    msg: String,
+}
+
+impl RpcError {
+    pub fn bad_request(err: impl ToString) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            msg: err.to_string(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(err: anyhow::Error) -> Self {
+        let status = match err.downcast_ref::<ApiError>() {
+            Some(ApiError::NotFound(_)) => StatusCode::NOT_FOUND,
+            Some(ApiError::InvalidArg(_)) => StatusCode::BAD_REQUEST,
+            None => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self {
+            status,
+            msg: err.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for RpcError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.msg }))).into_response()
+    }
+}
+
+pub(crate) type RpcResult<T> = Result<Json<T>, RpcError>;
@@ -0,0 +1,42 @@
+mod error;
+mod routes;
+mod service;
+
+use self::error::*;
+pub use self::service::*;
+
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use vintage_msg::{ArcConsensusRound, ArcConsensusRoundRecords, BlockChainApi};
+use vintage_network::peer_manager::PeerManager;
+use vintage_utils::ServiceStarter;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RpcConfig {
+    pub listen_addr: SocketAddr,
+}
+
+pub enum Rpc {}
+
+impl Rpc {
+    pub async fn create<TApi>(
+        config: RpcConfig,
+        blockchain_api: TApi,
+        peer_manager: Arc<PeerManager>,
+        consensus_round: ArcConsensusRound,
+        round_records: ArcConsensusRoundRecords,
+    ) -> anyhow::Result<ServiceStarter<RpcService<TApi>>>
+    where
+        TApi: BlockChainApi + Send + Sync + 'static,
+    {
+        tracing::info!("rpc listen on: {}", config.listen_addr);
+        let listener = TcpListener::bind(config.listen_addr).await?;
+
+        Ok(ServiceStarter::new_with_input(
+            RpcService::new(blockchain_api, peer_manager, consensus_round, round_records),
+            listener,
+        ))
+    }
+}
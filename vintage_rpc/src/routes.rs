@@ -0,0 +1,319 @@
+use crate::{RpcError, RpcResult, RpcState};
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use vintage_msg::{
+    BlockChainApi, BlockHeight, BlockWithHash, ConsensusRound, ConsensusRoundRecord, EntityHash,
+    EntityId, EntityVersion, Model, NodeId, Proto, TxInBlock, TxPoolContents, TxReceipt,
+    ValidatorNode, WasmId,
+};
+use vintage_utils::Hashed;
+
+pub(crate) fn router<TApi>(state: Arc<RpcState<TApi>>) -> Router
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/height", get(get_height))
+        .route("/blocks/:height", get(get_block))
+        .route("/blocks/hash/:hash", get(get_block_by_hash))
+        .route("/txs", get(get_txs))
+        .route("/txs/:tx_id", get(get_tx))
+        .route("/txs/:tx_id/receipt", get(get_tx_receipt))
+        .route("/entities/:proto/:model/:entity_id", get(get_entity))
+        .route(
+            "/entities/:proto/:model/:entity_id/history",
+            get(get_entity_history),
+        )
+        .route("/wasm/:wasm_hash", get(get_wasm_binary))
+        .route("/wasm_upgrades", get(get_upgrade_schedule))
+        .route("/validators", get(get_validators))
+        .route("/pool", get(get_pool_contents))
+        .route("/peers", get(get_peers))
+        .route("/consensus", get(get_consensus_round))
+        .route("/consensus/rounds", get(get_consensus_round_records))
+        .with_state(state)
+}
+
+fn parse_hashed(s: &str) -> Result<Hashed, RpcError> {
+    Hashed::from_str(s).map_err(RpcError::bad_request)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// blocks
+
+#[derive(Serialize)]
+struct HeightView {
+    height: BlockHeight,
+}
+
+async fn get_height<TApi>(State(state): State<Arc<RpcState<TApi>>>) -> RpcResult<HeightView>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    let height = state.blockchain_api.get_block_height().await?;
+    Ok(Json(HeightView { height }))
+}
+
+async fn get_block<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+    Path(height): Path<BlockHeight>,
+) -> RpcResult<BlockWithHash>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    Ok(Json(state.blockchain_api.get_block(height).await?))
+}
+
+async fn get_block_by_hash<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+    Path(hash): Path<String>,
+) -> RpcResult<BlockWithHash>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    let hash = parse_hashed(&hash)?;
+    Ok(Json(state.blockchain_api.get_block_by_hash(hash).await?))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// txs
+
+#[derive(Deserialize)]
+struct TxsQuery {
+    begin: BlockHeight,
+    end: BlockHeight,
+}
+
+async fn get_txs<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+    Query(query): Query<TxsQuery>,
+) -> RpcResult<Vec<TxInBlock>>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    Ok(Json(
+        state.blockchain_api.get_txs(query.begin, query.end).await?,
+    ))
+}
+
+async fn get_tx<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+    Path(tx_id): Path<String>,
+) -> RpcResult<TxInBlock>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    let tx_id = parse_hashed(&tx_id)?;
+    Ok(Json(state.blockchain_api.get_tx(tx_id).await?))
+}
+
+async fn get_tx_receipt<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+    Path(tx_id): Path<String>,
+) -> RpcResult<TxReceipt>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    let tx_id = parse_hashed(&tx_id)?;
+    Ok(Json(state.blockchain_api.get_tx_receipt(tx_id).await?))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// entities
+
+#[derive(Serialize)]
+struct EntityView {
+    id: EntityId,
+    hash: EntityHash,
+}
+
+async fn get_entity<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+    Path((proto, model, entity_id)): Path<(Proto, Model, EntityId)>,
+) -> RpcResult<EntityView>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    let hash = state
+        .blockchain_api
+        .get_entity(proto, model, entity_id.clone())
+        .await?;
+    Ok(Json(EntityView {
+        id: entity_id,
+        hash,
+    }))
+}
+
+#[derive(Deserialize)]
+struct EntityHistoryQuery {
+    begin: Option<BlockHeight>,
+}
+
+// a page of versions, the next page begins after the height of the last version
+async fn get_entity_history<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+    Path((proto, model, entity_id)): Path<(Proto, Model, EntityId)>,
+    Query(query): Query<EntityHistoryQuery>,
+) -> RpcResult<Vec<EntityVersion>>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    Ok(Json(
+        state
+            .blockchain_api
+            .entity_history(proto, model, entity_id, query.begin.unwrap_or(0))
+            .await?,
+    ))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// wasm
+
+async fn get_wasm_binary<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+    Path(wasm_hash): Path<String>,
+) -> Result<impl IntoResponse, RpcError>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    let wasm_hash = parse_hashed(&wasm_hash)?;
+    let wasm_binary = state.blockchain_api.get_wasm_binary(wasm_hash).await?;
+    Ok((
+        [(header::CONTENT_TYPE, "application/octet-stream")],
+        wasm_binary,
+    ))
+}
+
+#[derive(Deserialize)]
+struct UpgradeScheduleQuery {
+    begin: Option<BlockHeight>,
+}
+
+#[derive(Serialize)]
+struct UpgradeView {
+    height: BlockHeight,
+    wasm_ids: Vec<WasmId>,
+}
+
+async fn get_upgrade_schedule<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+    Query(query): Query<UpgradeScheduleQuery>,
+) -> RpcResult<Vec<UpgradeView>>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    // the pending upgrades by default
+    let begin = match query.begin {
+        Some(begin) => begin,
+        None => state.blockchain_api.get_block_height().await? + 1,
+    };
+    let schedule = state.blockchain_api.get_upgrade_schedule(begin).await?;
+    Ok(Json(
+        schedule
+            .into_iter()
+            .map(|(height, wasm_ids)| UpgradeView { height, wasm_ids })
+            .collect(),
+    ))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// node
+
+#[derive(Deserialize)]
+struct ValidatorsQuery {
+    height: Option<BlockHeight>,
+}
+
+// a validator set tx is approved by the validators of the next block
+async fn get_validators<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+    Query(query): Query<ValidatorsQuery>,
+) -> RpcResult<Vec<ValidatorNode>>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    let height = match query.height {
+        Some(height) => height,
+        None => state.blockchain_api.get_block_height().await? + 1,
+    };
+    Ok(Json(state.blockchain_api.get_validators(height).await?))
+}
+
+async fn get_pool_contents<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+) -> RpcResult<TxPoolContents>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    Ok(Json(state.blockchain_api.get_pool_contents().await?))
+}
+
+#[derive(Serialize)]
+struct PeerView {
+    node_id: NodeId,
+    name: String,
+    address: SocketAddr,
+    connected_once: bool,
+    failed_attempts: u32,
+    last_seen_secs_ago: u64,
+}
+
+async fn get_peers<TApi>(State(state): State<Arc<RpcState<TApi>>>) -> RpcResult<Vec<PeerView>>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    let peers = state.peer_manager.get_peer_statuses().await;
+    Ok(Json(
+        peers
+            .into_iter()
+            .map(|status| PeerView {
+                node_id: status.node_id,
+                name: status.info.name,
+                address: status.info.address,
+                connected_once: status.connected_once,
+                failed_attempts: status.failed_attempts,
+                last_seen_secs_ago: status.last_seen.elapsed().as_secs(),
+            })
+            .collect(),
+    ))
+}
+
+async fn get_consensus_round<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+) -> RpcResult<ConsensusRound>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    let consensus_round = state.consensus_round.read().unwrap().clone();
+    Ok(Json(consensus_round))
+}
+
+#[derive(Deserialize)]
+struct RoundRecordsQuery {
+    height: Option<BlockHeight>,
+}
+
+// the recent rounds, oldest first
+async fn get_consensus_round_records<TApi>(
+    State(state): State<Arc<RpcState<TApi>>>,
+    Query(query): Query<RoundRecordsQuery>,
+) -> RpcResult<Vec<ConsensusRoundRecord>>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    let records = state.round_records.read().unwrap();
+    Ok(Json(
+        records
+            .iter()
+            .filter(|record| query.height.is_none() || query.height == Some(record.height))
+            .cloned()
+            .collect(),
+    ))
+}
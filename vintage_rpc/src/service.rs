@@ -0,0 +1,50 @@
+use crate::routes::router;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use vintage_msg::{ArcConsensusRound, ArcConsensusRoundRecords, BlockChainApi};
+use vintage_network::peer_manager::PeerManager;
+use vintage_utils::Service;
+
+pub(crate) struct RpcState<TApi> {
+    pub blockchain_api: TApi,
+    pub peer_manager: Arc<PeerManager>,
+    pub consensus_round: ArcConsensusRound,
+    pub round_records: ArcConsensusRoundRecords,
+}
+
+pub struct RpcService<TApi> {
+    state: Arc<RpcState<TApi>>,
+}
+
+impl<TApi> RpcService<TApi> {
+    pub(crate) fn new(
+        blockchain_api: TApi,
+        peer_manager: Arc<PeerManager>,
+        consensus_round: ArcConsensusRound,
+        round_records: ArcConsensusRoundRecords,
+    ) -> Self {
+        Self {
+            state: Arc::new(RpcState {
+                blockchain_api,
+                peer_manager,
+                consensus_round,
+                round_records,
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl<TApi> Service for RpcService<TApi>
+where
+    TApi: BlockChainApi + Send + Sync + 'static,
+{
+    type Input = TcpListener;
+    type Output = anyhow::Result<()>;
+
+    async fn service(self, listener: Self::Input) -> Self::Output {
+        axum::serve(listener, router(self.state)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{CalcHash, Hashed};
+use anyhow::anyhow;
+use blst::min_pk::{AggregateSignature, PublicKey, SecretKey, Signature};
+use blst::BLST_ERROR;
+use rand::RngCore;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+// BLS12-381, public keys in G1 (48 bytes), signatures in G2 (96 bytes).
+// Every validator key comes with a proof of possession, verified before the key joins a validator
+// set, so aggregated signatures are safe to check with fast_aggregate_verify.
+
+const BLS_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+const BLS_POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+pub const BLS_PRIVATE_KEY_SIZE: usize = 32;
+pub const BLS_PUBLIC_KEY_SIZE: usize = 48;
+pub const BLS_SIGNATURE_SIZE: usize = 96;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// BlsPrivateKey
+
+#[derive(Clone)]
+pub struct BlsPrivateKey(SecretKey);
+
+impl BlsPrivateKey {
+    pub fn generate() -> Self {
+        let mut ikm = [0u8; BLS_PRIVATE_KEY_SIZE];
+        rand::thread_rng().fill_bytes(&mut ikm);
+        Self(SecretKey::key_gen(&ikm, &[]).unwrap())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let key = SecretKey::from_bytes(bytes).map_err(bls_err)?;
+        Ok(Self(key))
+    }
+
+    pub fn to_bytes(&self) -> [u8; BLS_PRIVATE_KEY_SIZE] {
+        self.0.to_bytes()
+    }
+
+    // the key file contains the hex encoded private key
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .map_err(|err| anyhow!("read key file {:?} err: {}", path.as_ref(), err))?;
+        Self::from_bytes(&hex::decode(content.trim())?)
+    }
+
+    // the key file is only readable by the owner, an existing file is not overwritten
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(path.as_ref())
+            .map_err(|err| anyhow!("create key file {:?} err: {}", path.as_ref(), err))?;
+        std::io::Write::write_all(&mut file, hex::encode(self.to_bytes()).as_bytes())?;
+        Ok(())
+    }
+
+    // only for the dev modes, a mistyped path would silently become a new identity
+    pub fn load_or_create(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        if path.as_ref().exists() {
+            Self::load(path)
+        } else {
+            let key = Self::generate();
+            key.save(path.as_ref())?;
+            tracing::info!(
+                "key file {:?} created, public key: {}",
+                path.as_ref(),
+                key.public_key()
+            );
+            Ok(key)
+        }
+    }
+
+    pub fn public_key(&self) -> BlsPublicKey {
+        BlsPublicKey(self.0.sk_to_pk())
+    }
+
+    pub fn sign(&self, msg: &[u8]) -> BlsSignature {
+        BlsSignature(self.0.sign(msg, BLS_DST, &[]))
+    }
+
+    // the public key signed in its own domain, so it can not be replayed as a signature
+    pub fn prove_possession(&self) -> BlsSignature {
+        let public_key = self.public_key().to_bytes();
+        BlsSignature(self.0.sign(&public_key, BLS_POP_DST, &[]))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// BlsPublicKey
+
+#[derive(Debug, Clone, Copy)]
+pub struct BlsPublicKey(PublicKey);
+
+impl BlsPublicKey {
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let key = PublicKey::key_validate(bytes).map_err(bls_err)?;
+        Ok(Self(key))
+    }
+
+    pub fn from_hex(hex_str: &str) -> anyhow::Result<Self> {
+        Self::from_bytes(&hex::decode(hex_str.trim())?)
+    }
+
+    pub fn to_bytes(&self) -> [u8; BLS_PUBLIC_KEY_SIZE] {
+        self.0.to_bytes()
+    }
+}
+
+// the node id is the sha256 of the public key
+impl CalcHash for BlsPublicKey {
+    fn calc_hash(&self) -> Hashed {
+        self.to_bytes().calc_hash()
+    }
+}
+
+impl Display for BlsPublicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(self.to_bytes()))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// BlsSignature
+
+#[derive(Debug, Clone, Copy)]
+pub struct BlsSignature(Signature);
+
+impl BlsSignature {
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let signature = Signature::from_bytes(bytes).map_err(bls_err)?;
+        Ok(Self(signature))
+    }
+
+    pub fn from_hex(hex_str: &str) -> anyhow::Result<Self> {
+        Self::from_bytes(&hex::decode(hex_str.trim())?)
+    }
+
+    pub fn to_bytes(&self) -> [u8; BLS_SIGNATURE_SIZE] {
+        self.0.to_bytes()
+    }
+
+    pub fn aggregate(signatures: &[BlsSignature]) -> anyhow::Result<Self> {
+        let signatures: Vec<&Signature> = signatures.iter().map(|sig| &sig.0).collect();
+        let aggregated = AggregateSignature::aggregate(&signatures, true).map_err(bls_err)?;
+        Ok(Self(aggregated.to_signature()))
+    }
+
+    pub fn verify(&self, msg: &[u8], public_key: &BlsPublicKey) -> anyhow::Result<()> {
+        match self.0.verify(true, msg, BLS_DST, &[], &public_key.0, false) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            err => Err(bls_err(err)),
+        }
+    }
+
+    pub fn verify_possession(&self, public_key: &BlsPublicKey) -> anyhow::Result<()> {
+        match self.0.verify(
+            true,
+            &public_key.to_bytes(),
+            BLS_POP_DST,
+            &[],
+            &public_key.0,
+            false,
+        ) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            err => Err(bls_err(err)),
+        }
+    }
+
+    // the public keys must have their possession proved
+    pub fn verify_aggregated(
+        &self,
+        msg: &[u8],
+        public_keys: &[BlsPublicKey],
+    ) -> anyhow::Result<()> {
+        let public_keys: Vec<&PublicKey> = public_keys.iter().map(|key| &key.0).collect();
+        match self
+            .0
+            .fast_aggregate_verify(true, msg, BLS_DST, &public_keys)
+        {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            err => Err(bls_err(err)),
+        }
+    }
+}
+
+impl Display for BlsSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(self.to_bytes()))
+    }
+}
+
+fn bls_err(err: BLST_ERROR) -> anyhow::Error {
+    anyhow!("bls err: {:?}", err)
+}
@@ -4,6 +4,7 @@ use digest::Digest;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 pub const HASH_SIZE: usize = 32;
 pub type HashBytes = [u8; HASH_SIZE];
@@ -31,6 +32,20 @@ impl Display for Hashed {
     }
 }
 
+impl FromStr for Hashed {
+    type Err = anyhow::Error;
+
+    fn from_str(hex_str: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(hex_str.trim())?;
+        if bytes.len() != HASH_SIZE {
+            return Err(anyhow!("hex len is {}", hex_str.len()));
+        }
+        let mut hash = ZERO_HASH;
+        hash.copy_from_slice(&bytes);
+        Ok(Self(hash))
+    }
+}
+
 impl AsRef<[u8]> for Hashed {
     fn as_ref(&self) -> &[u8] {
         &self.0
@@ -1,21 +1,29 @@
 mod activation;
 mod bincode;
+mod bls;
 mod calc_hash;
 mod channel;
 mod data;
 mod hash;
+mod merkle;
+mod quorum;
 mod redb;
 mod send_msg;
 mod service;
+mod smt;
 mod timestamp;
 
 pub use self::activation::*;
 pub use self::bincode::*;
+pub use self::bls::*;
 pub use self::calc_hash::*;
 pub use self::channel::*;
 pub use self::data::*;
 pub use self::hash::*;
+pub use self::merkle::*;
+pub use self::quorum::*;
 pub use self::redb::*;
 pub use self::send_msg::*;
 pub use self::service::*;
+pub use self::smt::*;
 pub use self::timestamp::*;
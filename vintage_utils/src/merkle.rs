@@ -0,0 +1,91 @@
+use crate::Hashed;
+use digest::Digest;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+// Binary merkle tree over a list of hashes.
+// Leaves and inner nodes are hashed with distinct prefixes, so a leaf can not be passed off as
+// an inner node. The last node of an odd level is promoted to the next level unchanged.
+
+const MERKLE_LEAF_PREFIX: u8 = 0;
+const MERKLE_NODE_PREFIX: u8 = 1;
+
+pub fn merkle_leaf(item: &Hashed) -> Hashed {
+    let mut hasher = Sha256::new();
+    hasher.update([MERKLE_LEAF_PREFIX]);
+    hasher.update(item);
+    hasher.into()
+}
+
+pub fn merkle_node(left: &Hashed, right: &Hashed) -> Hashed {
+    let mut hasher = Sha256::new();
+    hasher.update([MERKLE_NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.into()
+}
+
+// the root of an empty list is the zero hash
+pub fn merkle_root(items: &[Hashed]) -> Hashed {
+    if items.is_empty() {
+        return Hashed::zero_hash();
+    }
+    let mut level: Vec<Hashed> = items.iter().map(merkle_leaf).collect();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.pop().unwrap()
+}
+
+fn next_level(level: &[Hashed]) -> Vec<Hashed> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => merkle_node(left, right),
+            [single] => single.clone(),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// proof
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MerkleSibling {
+    Left(Hashed),
+    Right(Hashed),
+}
+
+// siblings from the leaf to the root, a promoted level has no sibling
+pub type MerklePath = Vec<MerkleSibling>;
+
+pub fn merkle_path(items: &[Hashed], index: usize) -> Option<MerklePath> {
+    if index >= items.len() {
+        return None;
+    }
+    let mut path = MerklePath::new();
+    let mut level: Vec<Hashed> = items.iter().map(merkle_leaf).collect();
+    let mut index = index;
+    while level.len() > 1 {
+        if index % 2 == 1 {
+            path.push(MerkleSibling::Left(level[index - 1].clone()));
+        } else if index + 1 < level.len() {
+            path.push(MerkleSibling::Right(level[index + 1].clone()));
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    Some(path)
+}
+
+pub fn merkle_verify(item: &Hashed, path: &[MerkleSibling], root: &Hashed) -> bool {
+    let mut hash = merkle_leaf(item);
+    for sibling in path {
+        hash = match sibling {
+            MerkleSibling::Left(left) => merkle_node(left, &hash),
+            MerkleSibling::Right(right) => merkle_node(&hash, right),
+        };
+    }
+    hash == *root
+}
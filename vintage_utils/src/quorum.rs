@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+
+pub type ArcQuorum<TVoter> = Arc<RwLock<Quorum<TVoter>>>;
+
+// BFT quorum over the vote weights of a validator set, reached by more than 2/3 of the total
+// weight, that is 2f+1 out of 3f+1 when all the weights are equal
+#[derive(Debug, Clone)]
+pub struct Quorum<TVoter> {
+    weights: HashMap<TVoter, u64>,
+    total_weight: u64,
+}
+
+impl<TVoter> Quorum<TVoter>
+where
+    TVoter: Hash + Eq,
+{
+    pub fn new(weights: impl IntoIterator<Item = (TVoter, u32)>) -> Self {
+        let weights: HashMap<TVoter, u64> = weights
+            .into_iter()
+            .map(|(voter, weight)| (voter, weight as u64))
+            .collect();
+        let total_weight = weights.values().sum();
+        Self {
+            weights,
+            total_weight,
+        }
+    }
+
+    pub fn total_weight(&self) -> u64 {
+        self.total_weight
+    }
+
+    pub fn voters(&self) -> impl Iterator<Item = &TVoter> {
+        self.weights.keys()
+    }
+
+    pub fn threshold(&self) -> u64 {
+        self.total_weight * 2 / 3 + 1
+    }
+
+    // voters outside the set weigh nothing
+    pub fn weight_of<'a>(&self, voters: impl IntoIterator<Item = &'a TVoter>) -> u64
+    where
+        TVoter: 'a,
+    {
+        voters
+            .into_iter()
+            .filter_map(|voter| self.weights.get(voter))
+            .sum()
+    }
+
+    // the voters must be distinct
+    pub fn is_reached<'a>(&self, voters: impl IntoIterator<Item = &'a TVoter>) -> bool
+    where
+        TVoter: 'a,
+    {
+        self.weight_of(voters) >= self.threshold()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn equal_weights(count: u32) -> Quorum<u32> {
+        Quorum::new((0..count).map(|voter| (voter, 1)))
+    }
+
+    #[test]
+    fn single_node() {
+        let quorum = equal_weights(1);
+        assert_eq!(quorum.threshold(), 1);
+        assert!(!quorum.is_reached(&[]));
+        assert!(quorum.is_reached(&[0]));
+    }
+
+    #[test]
+    fn four_nodes() {
+        // f = 1, 2f+1 = 3
+        let quorum = equal_weights(4);
+        assert_eq!(quorum.threshold(), 3);
+        assert!(!quorum.is_reached(&[0, 1]));
+        assert!(quorum.is_reached(&[0, 1, 2]));
+        assert!(quorum.is_reached(&[0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn seven_nodes() {
+        // f = 2, 2f+1 = 5
+        let quorum = equal_weights(7);
+        assert_eq!(quorum.threshold(), 5);
+        assert!(!quorum.is_reached(&[0, 1, 2, 3]));
+        assert!(quorum.is_reached(&[0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn more_than_two_thirds() {
+        // 2/3 of 6 is not enough
+        let quorum = equal_weights(6);
+        assert_eq!(quorum.threshold(), 5);
+        assert!(!quorum.is_reached(&[0, 1, 2, 3]));
+        assert!(quorum.is_reached(&[0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn unequal_weights() {
+        let quorum = Quorum::new([("a", 5), ("b", 2), ("c", 1), ("d", 1)]);
+        assert_eq!(quorum.total_weight(), 9);
+        assert_eq!(quorum.threshold(), 7);
+        assert!(!quorum.is_reached(&["b", "c", "d"]));
+        assert!(!quorum.is_reached(&["a", "c"]));
+        assert!(quorum.is_reached(&["a", "b"]));
+        assert!(quorum.is_reached(&["a", "c", "d"]));
+    }
+
+    #[test]
+    fn voters_outside_the_set() {
+        let quorum = equal_weights(4);
+        assert_eq!(quorum.weight_of(&[0, 1, 9]), 2);
+        assert!(!quorum.is_reached(&[0, 1, 9]));
+    }
+}
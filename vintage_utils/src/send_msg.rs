@@ -10,7 +10,7 @@ impl<TMsg> SendMsg for mpsc::Sender<TMsg> {
 
     fn send_msg(&self, msg: Self::Msg) -> bool {
         if let Err(err) = self.try_send(msg) {
-            log::trace!("mpsc::Sender try_send err: {}", err);
+            tracing::trace!("mpsc::Sender try_send err: {}", err);
             false
         } else {
             true
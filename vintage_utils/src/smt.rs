@@ -0,0 +1,213 @@
+use crate::{HashBytes, Hashed, HASH_SIZE};
+use digest::Digest;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+
+// Sparse merkle tree with 256 bit keys.
+// A node at height h covers the keys sharing the top 256 - h bits, the leaves are at height 0
+// and the root is at height 256. An empty subtree hashes to the zero hash and is not stored,
+// so only the nodes on the paths of the existing keys are persisted.
+
+pub const SMT_DEPTH: u16 = 256;
+pub const SMT_NODE_KEY_SIZE: usize = 2 + HASH_SIZE;
+
+const SMT_LEAF_PREFIX: u8 = 0;
+const SMT_NODE_PREFIX: u8 = 1;
+
+pub fn smt_leaf(key: &Hashed, value: &Hashed) -> Hashed {
+    let mut hasher = Sha256::new();
+    hasher.update([SMT_LEAF_PREFIX]);
+    hasher.update(key);
+    hasher.update(value);
+    hasher.into()
+}
+
+pub fn smt_node(left: &Hashed, right: &Hashed) -> Hashed {
+    let zero_hash = Hashed::zero_hash();
+    if *left == zero_hash && *right == zero_hash {
+        return zero_hash;
+    }
+    let mut hasher = Sha256::new();
+    hasher.update([SMT_NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.into()
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// SmtNodeKey
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SmtNodeKey {
+    height: u16,
+    prefix: HashBytes,
+}
+
+impl SmtNodeKey {
+    pub fn root() -> Self {
+        Self {
+            height: SMT_DEPTH,
+            prefix: [0; HASH_SIZE],
+        }
+    }
+
+    // the node at the height on the path of the key
+    fn of(key: &Hashed, height: u16) -> Self {
+        let mut prefix = *key.as_bytes();
+        for bit in 0..height {
+            set_bit(&mut prefix, bit, false);
+        }
+        Self { height, prefix }
+    }
+
+    fn sibling(&self) -> Self {
+        let mut prefix = self.prefix;
+        let value = get_bit(&prefix, self.height);
+        set_bit(&mut prefix, self.height, !value);
+        Self {
+            height: self.height,
+            prefix,
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; SMT_NODE_KEY_SIZE] {
+        let mut bytes = [0; SMT_NODE_KEY_SIZE];
+        bytes[..2].copy_from_slice(&self.height.to_be_bytes());
+        bytes[2..].copy_from_slice(&self.prefix);
+        bytes
+    }
+}
+
+// bit 0 is the lowest bit of the key, the path from the root starts from bit 255
+fn get_bit(bytes: &HashBytes, bit: u16) -> bool {
+    let bit = bit as usize;
+    bytes[HASH_SIZE - 1 - bit / 8] & (1 << (bit % 8)) != 0
+}
+
+fn set_bit(bytes: &mut HashBytes, bit: u16, value: bool) {
+    let bit = bit as usize;
+    let byte = &mut bytes[HASH_SIZE - 1 - bit / 8];
+    if value {
+        *byte |= 1 << (bit % 8);
+    } else {
+        *byte &= !(1 << (bit % 8));
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// SmtStore
+
+pub trait SmtStore {
+    // None if the subtree is empty
+    fn get_node(&self, node_key: &SmtNodeKey) -> anyhow::Result<Option<Hashed>>;
+}
+
+// the updates are kept in memory, the store is not changed
+pub struct SmtOverlay<'a, TStore> {
+    store: &'a TStore,
+    nodes: HashMap<SmtNodeKey, Hashed>,
+}
+
+impl<'a, TStore> SmtOverlay<'a, TStore>
+where
+    TStore: SmtStore,
+{
+    pub fn new(store: &'a TStore) -> Self {
+        Self {
+            store,
+            nodes: HashMap::new(),
+        }
+    }
+
+    pub fn root(&self) -> anyhow::Result<Hashed> {
+        self.get_node(&SmtNodeKey::root())
+    }
+
+    pub fn update(&mut self, key: &Hashed, value: &Hashed) -> anyhow::Result<()> {
+        self.set_leaf(key, smt_leaf(key, value))
+    }
+
+    // the leaf becomes an empty subtree, as if the key was never updated
+    pub fn remove(&mut self, key: &Hashed) -> anyhow::Result<()> {
+        self.set_leaf(key, Hashed::zero_hash())
+    }
+
+    fn set_leaf(&mut self, key: &Hashed, leaf: Hashed) -> anyhow::Result<()> {
+        let mut hash = leaf;
+        for height in 0..SMT_DEPTH {
+            let node_key = SmtNodeKey::of(key, height);
+            let sibling = self.get_node(&node_key.sibling())?;
+            let parent = if get_bit(key.as_bytes(), height) {
+                smt_node(&sibling, &hash)
+            } else {
+                smt_node(&hash, &sibling)
+            };
+            self.nodes.insert(node_key, hash);
+            hash = parent;
+        }
+        self.nodes.insert(SmtNodeKey::root(), hash);
+        Ok(())
+    }
+
+    // the changed nodes, a zero hash means the node is removed
+    pub fn into_nodes(self) -> HashMap<SmtNodeKey, Hashed> {
+        self.nodes
+    }
+
+    fn get_node(&self, node_key: &SmtNodeKey) -> anyhow::Result<Hashed> {
+        match self.nodes.get(node_key) {
+            Some(hash) => Ok(hash.clone()),
+            None => Ok(self
+                .store
+                .get_node(node_key)?
+                .unwrap_or_else(Hashed::zero_hash)),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// SmtProof
+
+// the non-empty siblings from the leaf to the root, with their heights
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmtProof {
+    pub siblings: Vec<(u16, Hashed)>,
+}
+
+impl SmtProof {
+    pub fn create(store: &impl SmtStore, key: &Hashed) -> anyhow::Result<Self> {
+        let mut siblings = Vec::new();
+        for height in 0..SMT_DEPTH {
+            if let Some(sibling) = store.get_node(&SmtNodeKey::of(key, height).sibling())? {
+                siblings.push((height, sibling));
+            }
+        }
+        Ok(Self { siblings })
+    }
+
+    // a None value proves that the key is not in the tree
+    pub fn calc_root(&self, key: &Hashed, value: Option<&Hashed>) -> Hashed {
+        let mut hash = match value {
+            Some(value) => smt_leaf(key, value),
+            None => Hashed::zero_hash(),
+        };
+        let mut siblings = self.siblings.iter().peekable();
+        for height in 0..SMT_DEPTH {
+            let sibling = match siblings.next_if(|(sibling_height, _)| *sibling_height == height) {
+                Some((_, sibling)) => sibling.clone(),
+                None => Hashed::zero_hash(),
+            };
+            hash = if get_bit(key.as_bytes(), height) {
+                smt_node(&sibling, &hash)
+            } else {
+                smt_node(&hash, &sibling)
+            };
+        }
+        hash
+    }
+
+    pub fn verify(&self, key: &Hashed, value: Option<&Hashed>, root: &Hashed) -> bool {
+        self.calc_root(key, value) == *root
+    }
+}